@@ -0,0 +1,395 @@
+use crate::desktop::{
+    collect_desktop_entries_from_with_report, DesktopAction, DesktopEntry, ScanReport,
+};
+use crate::history::write_atomic;
+use crate::json::{self, Value};
+use crate::{log_debug, log_error, log_info};
+use std::collections::HashSet;
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Layout version written to the cache. A cache in another layout is
+/// ignored and rewritten.
+pub const CACHE_FORMAT: u64 = 2;
+
+/// Returns `$XDG_CACHE_HOME/access-launcher/entries.json`, falling back to
+/// `~/.cache`.
+pub fn entries_cache_path() -> Option<PathBuf> {
+    env::var("XDG_CACHE_HOME")
+        .ok()
+        .filter(|value| !value.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| {
+            env::var("HOME")
+                .ok()
+                .map(|home| PathBuf::from(home).join(".cache"))
+        })
+        .map(|dir| dir.join("access-launcher/entries.json"))
+}
+
+/// Everything besides the desktop files that decides what a directory scan
+/// returns. A cache written under a different key is not used.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CacheKey {
+    /// The scanned directories that exist, so one appearing or going away
+    /// invalidates the cache.
+    pub dirs: Vec<PathBuf>,
+    pub excluded_subdirs: Vec<String>,
+    /// `LANG`, which picks the localized names.
+    pub lang: Option<String>,
+    /// `XDG_CURRENT_DESKTOP`, which `OnlyShowIn` and `NotShowIn` test.
+    pub desktops: Option<String>,
+    /// `PATH`, which decides whether each `Exec` program can be found.
+    pub path: Option<String>,
+}
+
+impl CacheKey {
+    /// The key for scanning `dirs` in the current environment.
+    pub fn current(dirs: &[PathBuf], excluded_subdirs: &[String]) -> Self {
+        Self {
+            dirs: dirs.iter().filter(|dir| dir.is_dir()).cloned().collect(),
+            excluded_subdirs: excluded_subdirs.to_vec(),
+            lang: env::var("LANG").ok(),
+            desktops: env::var("XDG_CURRENT_DESKTOP").ok(),
+            path: env::var("PATH").ok(),
+        }
+    }
+
+    fn write_json(&self, out: &mut String) {
+        out.push_str("{\"dirs\": ");
+        write_string_array(out, self.dirs.iter().map(|dir| dir.to_string_lossy()));
+        out.push_str(", \"excluded_subdirs\": ");
+        write_string_array(out, self.excluded_subdirs.iter().map(String::as_str));
+        for (name, value) in [
+            ("lang", &self.lang),
+            ("desktops", &self.desktops),
+            ("path", &self.path),
+        ] {
+            out.push_str(&format!(", \"{name}\": "));
+            write_optional_string(out, value.as_deref());
+        }
+        out.push('}');
+    }
+
+    fn parse(value: &Value) -> Option<Self> {
+        let optional = |name: &str| match value.get(name)? {
+            Value::Null => Some(None),
+            value => value.as_str().map(|value| Some(value.to_string())),
+        };
+        Some(Self {
+            dirs: string_array(value.get("dirs")?)?
+                .into_iter()
+                .map(PathBuf::from)
+                .collect(),
+            excluded_subdirs: string_array(value.get("excluded_subdirs")?)?,
+            lang: optional("lang")?,
+            desktops: optional("desktops")?,
+            path: optional("path")?,
+        })
+    }
+}
+
+/// Entries and broken `Exec` files (see [`ScanReport::broken_exec`]) read
+/// back from a cache.
+#[derive(Clone, Debug, Default)]
+pub struct CachedScan {
+    pub entries: Vec<DesktopEntry>,
+    pub broken_exec: Vec<PathBuf>,
+}
+
+/// Scans `dirs` like [`collect_desktop_entries_from_with_report`], but
+/// reuses the entries cache while it is fresh, and writes a new one after a
+/// full scan. Without a cache path it always scans.
+///
+/// A report built from the cache only carries `broken_exec`; the skip
+/// counts are those of the scan that wrote it, and are not kept.
+pub fn collect_with_cache(
+    dirs: &[PathBuf],
+    excluded_subdirs: &[String],
+) -> (Vec<DesktopEntry>, ScanReport) {
+    let Some(path) = entries_cache_path() else {
+        return collect_desktop_entries_from_with_report(dirs, excluded_subdirs);
+    };
+    let key = CacheKey::current(dirs, excluded_subdirs);
+    if let Some(cached) = read_entries_cache(&path, &key) {
+        log_info!(
+            "Loaded {} applications from {}",
+            cached.entries.len(),
+            path.display()
+        );
+        let report = ScanReport {
+            broken_exec: cached.broken_exec,
+            ..Default::default()
+        };
+        return (cached.entries, report);
+    }
+    let (entries, report) = collect_desktop_entries_from_with_report(dirs, excluded_subdirs);
+    match write_entries_cache(&path, &key, &entries, &report.broken_exec) {
+        Ok(()) => log_debug!("Wrote {}", path.display()),
+        Err(err) => log_error!("Failed to write {}: {err}", path.display()),
+    }
+    (entries, report)
+}
+
+/// Writes `entries` and `broken_exec` as found by scanning under `key` to
+/// the cache at `path`, replacing it atomically.
+///
+/// The cache is a JSON object: `format` ([`CACHE_FORMAT`]), `key` (the
+/// [`CacheKey`] fields), `broken_exec` (paths), and `entries`, an array of
+/// objects with one member per parsed [`DesktopEntry`] field and
+/// `modified` as `[seconds, nanoseconds]` since the Unix epoch. Paths that
+/// are not UTF-8 cannot be stored, so such a scan is not cached.
+pub fn write_entries_cache(
+    path: &Path,
+    key: &CacheKey,
+    entries: &[DesktopEntry],
+    broken_exec: &[PathBuf],
+) -> io::Result<()> {
+    let not_utf8 = key
+        .dirs
+        .iter()
+        .chain(broken_exec)
+        .chain(entries.iter().map(|entry| &entry.path))
+        .find(|path| path.to_str().is_none());
+    if let Some(bad) = not_utf8 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{} is not valid UTF-8", bad.display()),
+        ));
+    }
+
+    let mut out = format!("{{\n  \"format\": {CACHE_FORMAT},\n  \"key\": ");
+    key.write_json(&mut out);
+    out.push_str(",\n  \"broken_exec\": ");
+    write_string_array(
+        &mut out,
+        broken_exec.iter().map(|path| path.to_string_lossy()),
+    );
+    out.push_str(",\n  \"entries\": [");
+    for (i, entry) in entries.iter().enumerate() {
+        out.push_str(if i > 0 { ",\n    " } else { "\n    " });
+        write_entry(&mut out, entry);
+    }
+    if !entries.is_empty() {
+        out.push_str("\n  ");
+    }
+    out.push_str("]\n}\n");
+    write_atomic(path, &out)
+}
+
+/// Reads the cache at `path` written under `key`. `None` when it is
+/// missing, malformed, in another format, written under another key, or
+/// older than a scanned directory or desktop file.
+pub fn read_entries_cache(path: &Path, key: &CacheKey) -> Option<CachedScan> {
+    let written = fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()?;
+    if newest_change(&key.dirs).is_some_and(|changed| changed >= written) {
+        log_debug!(
+            "{} is older than the application directories",
+            path.display()
+        );
+        return None;
+    }
+    let value = json::parse(&fs::read_to_string(path).ok()?)?;
+    if value.get("format")?.as_u64()? != CACHE_FORMAT {
+        return None;
+    }
+    if CacheKey::parse(value.get("key")?)? != *key {
+        log_debug!("{} was written for other settings", path.display());
+        return None;
+    }
+    let broken_exec = string_array(value.get("broken_exec")?)?
+        .into_iter()
+        .map(PathBuf::from)
+        .collect();
+    let entries = value
+        .get("entries")?
+        .as_array()?
+        .iter()
+        .map(read_entry)
+        .collect::<Option<_>>()?;
+    Some(CachedScan {
+        entries,
+        broken_exec,
+    })
+}
+
+/// The newest modification time of `dirs`, the directories below them, and
+/// the files in any of those. Symlinks count with their targets, and
+/// symlinked directories are walked once, as the scan walks them.
+fn newest_change(dirs: &[PathBuf]) -> Option<SystemTime> {
+    let mut newest = None;
+    let mut visited = HashSet::new();
+    let mut pending: Vec<PathBuf> = dirs.to_vec();
+    while let Some(dir) = pending.pop() {
+        if fs::canonicalize(&dir).is_ok_and(|canonical| !visited.insert(canonical)) {
+            continue;
+        }
+        newest = newest.max(fs::metadata(&dir).and_then(|meta| meta.modified()).ok());
+        let Ok(children) = fs::read_dir(&dir) else {
+            continue;
+        };
+        for child in children.flatten() {
+            let link = child.metadata().and_then(|meta| meta.modified()).ok();
+            newest = newest.max(link);
+            let Ok(metadata) = fs::metadata(child.path()) else {
+                continue;
+            };
+            if metadata.is_dir() {
+                pending.push(child.path());
+            } else {
+                newest = newest.max(metadata.modified().ok());
+            }
+        }
+    }
+    newest
+}
+
+fn write_entry(out: &mut String, entry: &DesktopEntry) {
+    out.push('{');
+    let strings = [
+        ("id", Some(entry.id.as_str())),
+        ("name", Some(entry.name.as_str())),
+        ("base_name", entry.base_name.as_deref()),
+        ("generic_name", entry.generic_name.as_deref()),
+        ("comment", entry.comment.as_deref()),
+        ("exec", Some(entry.exec.as_str())),
+        ("try_exec", entry.try_exec.as_deref()),
+        ("categories", Some(entry.categories.as_str())),
+        ("keywords", Some(entry.keywords.as_str())),
+        ("path", entry.path.to_str()),
+        ("icon", entry.icon.as_deref()),
+        ("working_dir", entry.working_dir.as_deref()),
+        ("startup_wm_class", entry.startup_wm_class.as_deref()),
+        ("version", entry.version.as_deref()),
+    ];
+    for (name, value) in strings {
+        json::write_string(out, name);
+        out.push_str(": ");
+        write_optional_string(out, value);
+        out.push_str(", ");
+    }
+    let flags = [
+        ("terminal", entry.terminal),
+        ("startup_notify", entry.startup_notify),
+        ("dbus_activatable", entry.dbus_activatable),
+        ("prefers_non_default_gpu", entry.prefers_non_default_gpu),
+        ("no_display", entry.no_display),
+        ("hidden", entry.hidden),
+        ("uses_notifications", entry.uses_notifications),
+    ];
+    for (name, value) in flags {
+        out.push_str(&format!("\"{name}\": {value}, "));
+    }
+    out.push_str("\"implements\": ");
+    write_string_array(out, entry.implements.iter().map(String::as_str));
+    out.push_str(", \"actions\": [");
+    for (i, action) in entry.actions.iter().enumerate() {
+        if i > 0 {
+            out.push_str(", ");
+        }
+        out.push_str("{\"id\": ");
+        json::write_string(out, &action.id);
+        out.push_str(", \"name\": ");
+        json::write_string(out, &action.name);
+        out.push_str(", \"exec\": ");
+        json::write_string(out, &action.exec);
+        out.push('}');
+    }
+    out.push_str("], \"modified\": ");
+    match entry
+        .modified
+        .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+    {
+        Some(since) => out.push_str(&format!("[{}, {}]", since.as_secs(), since.subsec_nanos())),
+        None => out.push_str("null"),
+    }
+    out.push('}');
+}
+
+fn read_entry(value: &Value) -> Option<DesktopEntry> {
+    let text = |name: &str| value.get(name)?.as_str().map(str::to_string);
+    let optional = |name: &str| match value.get(name)? {
+        Value::Null => Some(None),
+        value => value.as_str().map(|value| Some(value.to_string())),
+    };
+    let flag = |name: &str| value.get(name)?.as_bool();
+    let actions = value
+        .get("actions")?
+        .as_array()?
+        .iter()
+        .map(|action| {
+            Some(DesktopAction {
+                id: action.get("id")?.as_str()?.to_string(),
+                name: action.get("name")?.as_str()?.to_string(),
+                exec: action.get("exec")?.as_str()?.to_string(),
+            })
+        })
+        .collect::<Option<_>>()?;
+    let modified = match value.get("modified")? {
+        Value::Null => None,
+        modified => match modified.as_array()? {
+            [secs, nanos] => Some(
+                UNIX_EPOCH + Duration::new(secs.as_u64()?, u32::try_from(nanos.as_u64()?).ok()?),
+            ),
+            _ => return None,
+        },
+    };
+    Some(DesktopEntry {
+        id: text("id")?,
+        name: text("name")?,
+        base_name: optional("base_name")?,
+        generic_name: optional("generic_name")?,
+        comment: optional("comment")?,
+        exec: text("exec")?,
+        try_exec: optional("try_exec")?,
+        categories: text("categories")?,
+        keywords: text("keywords")?,
+        path: PathBuf::from(text("path")?),
+        icon: optional("icon")?,
+        terminal: flag("terminal")?,
+        working_dir: optional("working_dir")?,
+        startup_notify: flag("startup_notify")?,
+        startup_wm_class: optional("startup_wm_class")?,
+        dbus_activatable: flag("dbus_activatable")?,
+        prefers_non_default_gpu: flag("prefers_non_default_gpu")?,
+        no_display: flag("no_display")?,
+        hidden: flag("hidden")?,
+        version: optional("version")?,
+        implements: string_array(value.get("implements")?)?,
+        uses_notifications: flag("uses_notifications")?,
+        actions,
+        modified,
+        ..Default::default()
+    })
+}
+
+fn write_optional_string(out: &mut String, value: Option<&str>) {
+    match value {
+        Some(value) => json::write_string(out, value),
+        None => out.push_str("null"),
+    }
+}
+
+fn write_string_array<S: AsRef<str>>(out: &mut String, values: impl Iterator<Item = S>) {
+    out.push('[');
+    for (i, value) in values.enumerate() {
+        if i > 0 {
+            out.push_str(", ");
+        }
+        json::write_string(out, value.as_ref());
+    }
+    out.push(']');
+}
+
+fn string_array(value: &Value) -> Option<Vec<String>> {
+    value
+        .as_array()?
+        .iter()
+        .map(|value| value.as_str().map(str::to_string))
+        .collect()
+}
@@ -0,0 +1,53 @@
+use crate::desktop::{build_category_map_with_rules, exec_program, CategoryRule, DesktopEntry};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// The scanned entries together with the category map derived from them.
+///
+/// Category map indices point into `entries`, so the two are only ever
+/// replaced together: a rescan builds a new catalog and swaps it in whole.
+/// After editing `entries` in place, call [`Catalog::rebuild`].
+#[derive(Clone, Debug, Default)]
+pub struct Catalog {
+    pub entries: Vec<DesktopEntry>,
+    pub category_map: BTreeMap<String, Vec<usize>>,
+    /// Pattern rules from `categories.conf`, kept for [`Catalog::rebuild`].
+    pub category_rules: Vec<CategoryRule>,
+}
+
+impl Catalog {
+    pub fn new(entries: Vec<DesktopEntry>) -> Self {
+        Self::with_rules(entries, Vec::new())
+    }
+
+    /// Like [`Catalog::new`], also bucketing categories by `rules`.
+    pub fn with_rules(entries: Vec<DesktopEntry>, category_rules: Vec<CategoryRule>) -> Self {
+        let mut catalog = Self {
+            entries,
+            category_map: BTreeMap::new(),
+            category_rules,
+        };
+        catalog.rebuild();
+        catalog
+    }
+
+    /// Recomputes the category map from the current entries.
+    pub fn rebuild(&mut self) {
+        self.category_map = build_category_map_with_rules(&self.entries, &self.category_rules);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// The entry whose `Exec` runs `program`, comparing file names so
+    /// `gnome-terminal` finds `org.gnome.Terminal.desktop` through its
+    /// `Exec=gnome-terminal --window`.
+    pub fn find_by_program(&self, program: &str) -> Option<&DesktopEntry> {
+        let file_name = Path::new(program).file_name()?;
+        self.entries.iter().find(|entry| {
+            exec_program(&entry.exec)
+                .is_some_and(|exec| Path::new(&exec).file_name() == Some(file_name))
+        })
+    }
+}
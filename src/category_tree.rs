@@ -0,0 +1,341 @@
+use crate::desktop::DesktopEntry;
+
+/// Freedesktop main categories, in sidebar order, with their label and the
+/// registered additional categories shown beneath them.
+///
+/// `Audio` and `Video` are main categories in the spec but require
+/// `AudioVideo`, so they are nested under it here.
+const CATEGORY_TREE: [(&str, &str, &[&str]); 11] = [
+    (
+        "AudioVideo",
+        "Audio/Video",
+        &[
+            "Audio",
+            "Video",
+            "Midi",
+            "Mixer",
+            "Sequencer",
+            "Tuner",
+            "TV",
+            "AudioVideoEditing",
+            "Player",
+            "Recorder",
+            "DiscBurning",
+        ],
+    ),
+    (
+        "Development",
+        "Development",
+        &[
+            "Building",
+            "Debugger",
+            "IDE",
+            "GUIDesigner",
+            "Profiling",
+            "RevisionControl",
+            "Translation",
+            "WebDevelopment",
+        ],
+    ),
+    (
+        "Education",
+        "Education",
+        &[
+            "Art",
+            "Construction",
+            "Music",
+            "Languages",
+            "ArtificialIntelligence",
+            "Astronomy",
+            "Biology",
+            "Chemistry",
+            "ComputerScience",
+            "DataVisualization",
+            "Economy",
+            "Electricity",
+            "Geography",
+            "Geology",
+            "Geoscience",
+            "History",
+            "Humanities",
+            "Literature",
+            "Maps",
+            "Math",
+            "MedicalSoftware",
+            "Physics",
+            "Robotics",
+            "Spirituality",
+            "Sports",
+        ],
+    ),
+    (
+        "Game",
+        "Games",
+        &[
+            "ActionGame",
+            "AdventureGame",
+            "ArcadeGame",
+            "BoardGame",
+            "BlocksGame",
+            "CardGame",
+            "KidsGame",
+            "LogicGame",
+            "RolePlaying",
+            "Shooter",
+            "Simulation",
+            "SportsGame",
+            "StrategyGame",
+            "Emulator",
+        ],
+    ),
+    (
+        "Graphics",
+        "Graphics",
+        &[
+            "2DGraphics",
+            "VectorGraphics",
+            "RasterGraphics",
+            "3DGraphics",
+            "Scanning",
+            "OCR",
+            "Photography",
+            "Publishing",
+            "Viewer",
+        ],
+    ),
+    (
+        "Network",
+        "Internet",
+        &[
+            "Dialup",
+            "InstantMessaging",
+            "Chat",
+            "IRCClient",
+            "Feed",
+            "FileTransfer",
+            "HamRadio",
+            "News",
+            "P2P",
+            "RemoteAccess",
+            "Telephony",
+            "VideoConference",
+            "WebBrowser",
+            "Email",
+        ],
+    ),
+    (
+        "Office",
+        "Office",
+        &[
+            "Calendar",
+            "ContactManagement",
+            "Database",
+            "Dictionary",
+            "Chart",
+            "Email",
+            "Finance",
+            "FlowChart",
+            "PDA",
+            "ProjectManagement",
+            "Presentation",
+            "Spreadsheet",
+            "WordProcessor",
+            "Publishing",
+            "Viewer",
+        ],
+    ),
+    (
+        "Science",
+        "Science",
+        &[
+            "ArtificialIntelligence",
+            "Astronomy",
+            "Biology",
+            "Chemistry",
+            "ComputerScience",
+            "DataVisualization",
+            "Electricity",
+            "Geology",
+            "Geoscience",
+            "ImageProcessing",
+            "Math",
+            "NumericalAnalysis",
+            "MedicalSoftware",
+            "ParallelComputing",
+            "Physics",
+            "Robotics",
+        ],
+    ),
+    (
+        "Settings",
+        "Settings",
+        &[
+            "DesktopSettings",
+            "HardwareSettings",
+            "Printing",
+            "PackageManager",
+            "Security",
+            "Accessibility",
+        ],
+    ),
+    (
+        "System",
+        "System",
+        &[
+            "Emulator",
+            "FileTools",
+            "FileManager",
+            "TerminalEmulator",
+            "Filesystem",
+            "Monitor",
+            "Security",
+            "Accessibility",
+        ],
+    ),
+    (
+        "Utility",
+        "Utilities",
+        &[
+            "TextTools",
+            "TelephonyTools",
+            "Archiving",
+            "Compression",
+            "FileTools",
+            "Accessibility",
+            "Calculator",
+            "Clock",
+            "TextEditor",
+        ],
+    ),
+];
+
+/// Bucket for entries that list no recognized category.
+pub const OTHER_CATEGORY_ID: &str = "Other";
+
+/// A node of the hierarchical category view.
+///
+/// `entries` holds indices into the scanned entries, in their original order.
+/// A main category's `entries` include every app in its subcategories.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CategoryNode {
+    pub id: String,
+    pub label: String,
+    pub entries: Vec<usize>,
+    pub children: Vec<CategoryNode>,
+}
+
+/// Turns a freedesktop category ID into a readable label, e.g.
+/// `WebBrowser` into "Web Browser" and `IRCClient` into "IRC Client".
+pub fn humanize_category(id: &str) -> String {
+    let chars: Vec<char> = id.chars().collect();
+    let mut label = String::with_capacity(id.len() + 4);
+    for (i, &c) in chars.iter().enumerate() {
+        if i > 0 && c.is_ascii_uppercase() {
+            let previous = chars[i - 1];
+            let next_is_lower = chars.get(i + 1).is_some_and(|n| n.is_ascii_lowercase());
+            if previous.is_ascii_lowercase() || (previous.is_ascii_uppercase() && next_is_lower) {
+                label.push(' ');
+            }
+        }
+        label.push(c);
+    }
+    label
+}
+
+/// Builds the main/subcategory tree for `entries`, dropping empty nodes.
+///
+/// A subcategory is placed under each main category the entry also lists.
+/// When the entry lists none of its parents, it goes under the first one
+/// registered. Entries with no recognized category land in "Other".
+pub fn build_category_tree(entries: &[DesktopEntry]) -> Vec<CategoryNode> {
+    let mut nodes: Vec<CategoryNode> = CATEGORY_TREE
+        .iter()
+        .map(|(id, label, children)| CategoryNode {
+            id: id.to_string(),
+            label: label.to_string(),
+            entries: Vec::new(),
+            children: children
+                .iter()
+                .map(|child| CategoryNode {
+                    id: child.to_string(),
+                    label: humanize_category(child),
+                    ..Default::default()
+                })
+                .collect(),
+        })
+        .collect();
+    let mut other = Vec::new();
+
+    for (index, entry) in entries.iter().enumerate() {
+        let listed: Vec<&str> = entry
+            .categories
+            .split(';')
+            .filter(|category| !category.is_empty())
+            .collect();
+        let mut placed = false;
+
+        for &category in &listed {
+            if let Some(main) = CATEGORY_TREE.iter().position(|(id, _, _)| *id == category) {
+                push_unique(&mut nodes[main].entries, index);
+                placed = true;
+                continue;
+            }
+
+            let parents: Vec<usize> = CATEGORY_TREE
+                .iter()
+                .enumerate()
+                .filter(|(_, (_, _, children))| children.contains(&category))
+                .map(|(main, _)| main)
+                .collect();
+            let Some(&first_parent) = parents.first() else {
+                continue;
+            };
+            let mut targets: Vec<usize> = parents
+                .iter()
+                .copied()
+                .filter(|&main| listed.contains(&CATEGORY_TREE[main].0))
+                .collect();
+            if targets.is_empty() {
+                targets.push(first_parent);
+            }
+            for main in targets {
+                let node = &mut nodes[main];
+                push_unique(&mut node.entries, index);
+                if let Some(child) = node.children.iter_mut().find(|c| c.id == category) {
+                    push_unique(&mut child.entries, index);
+                }
+            }
+            placed = true;
+        }
+
+        if !placed {
+            other.push(index);
+        }
+    }
+
+    let mut tree: Vec<CategoryNode> = nodes
+        .into_iter()
+        .filter(|node| !node.entries.is_empty())
+        .map(|mut node| {
+            node.children.retain(|child| !child.entries.is_empty());
+            node.children.sort_by(|a, b| a.label.cmp(&b.label));
+            node
+        })
+        .collect();
+    if !other.is_empty() {
+        tree.push(CategoryNode {
+            id: OTHER_CATEGORY_ID.to_string(),
+            label: OTHER_CATEGORY_ID.to_string(),
+            entries: other,
+            children: Vec::new(),
+        });
+    }
+    tree
+}
+
+/// Entries are visited in order, so a repeat can only be the last index.
+fn push_unique(indices: &mut Vec<usize>, index: usize) {
+    if indices.last() != Some(&index) {
+        indices.push(index);
+    }
+}
@@ -0,0 +1,876 @@
+use crate::desktop::{
+    parse_bool, CategoryRule, CATEGORIES, DEFAULT_EXCLUDED_SUBDIRS, MAX_CATEGORY_RULES,
+};
+use crate::history::write_atomic;
+use crate::log_error;
+use crate::search::{SearchMode, SearchScope};
+use std::collections::{BTreeMap, BTreeSet};
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Layout of the programs pane.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ProgramView {
+    #[default]
+    List,
+    Grid,
+}
+
+impl ProgramView {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "list" => Some(Self::List),
+            "grid" => Some(Self::Grid),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::List => "list",
+            Self::Grid => "grid",
+        }
+    }
+}
+
+/// Order of the regular categories in the sidebar. Pseudo-categories such
+/// as "Recent" always stay at the top.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CategoryOrder {
+    /// The built-in order of [`crate::desktop::CATEGORIES`].
+    #[default]
+    Fixed,
+    Alphabetical,
+    /// Most launched categories first.
+    Frequency,
+}
+
+impl CategoryOrder {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "fixed" => Some(Self::Fixed),
+            "alphabetical" => Some(Self::Alphabetical),
+            "frequency" => Some(Self::Frequency),
+            _ => None,
+        }
+    }
+}
+
+/// Preset for how much room list rows and grid tiles get.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum RowDensity {
+    /// The built-in spacing.
+    #[default]
+    Compact,
+    /// Padded, taller rows: larger click targets for users with motor
+    /// impairments.
+    Comfortable,
+}
+
+impl RowDensity {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "compact" => Some(Self::Compact),
+            "comfortable" => Some(Self::Comfortable),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Compact => "compact",
+            Self::Comfortable => "comfortable",
+        }
+    }
+
+    pub fn toggled(self) -> Self {
+        match self {
+            Self::Compact => Self::Comfortable,
+            Self::Comfortable => Self::Compact,
+        }
+    }
+
+    /// Padding and minimum height of a row, in pixels, or `None` to keep
+    /// the theme's.
+    fn spacing(self) -> (Option<u32>, Option<u32>) {
+        match self {
+            Self::Compact => (None, None),
+            Self::Comfortable => (Some(12), Some(56)),
+        }
+    }
+
+    /// Margin around lists, labels, and other widgets, in pixels. Windows
+    /// and dialogs get twice this around their content.
+    fn margin(self) -> u32 {
+        match self {
+            Self::Compact => 6,
+            Self::Comfortable => 12,
+        }
+    }
+}
+
+/// Largest accepted `row_padding`, in pixels.
+pub const MAX_ROW_PADDING: u32 = 48;
+/// Largest accepted `row_min_height`, in pixels.
+pub const MAX_ROW_MIN_HEIGHT: u32 = 200;
+/// Largest accepted `widget_margin`, in pixels.
+pub const MAX_WIDGET_MARGIN: u32 = 24;
+
+/// Default for [`Config::activate_shortcut`].
+pub const DEFAULT_ACTIVATE_SHORTCUT: &str = "<Control>Return";
+
+/// Settings read from `$XDG_CONFIG_HOME/access-launcher/config`.
+///
+/// The file holds `key=value` lines; unknown keys are ignored so older
+/// builds keep working with newer config files.
+#[derive(Clone, Debug)]
+pub struct Config {
+    pub show_empty_categories: bool,
+    /// Start with the freedesktop category tree instead of the flat list.
+    pub category_tree: bool,
+    pub search_mode: SearchMode,
+    /// Which entry fields a search matches.
+    pub search_scope: SearchScope,
+    /// Programs pane layout; the list is the accessible default.
+    pub program_view: ProgramView,
+    /// Maximum number of entries in the "Most Used" category.
+    pub most_used_limit: usize,
+    /// Rows the programs list renders before a "… and N more" row; 0 shows all.
+    pub max_visible_results: usize,
+    pub category_order: CategoryOrder,
+    /// Category selected when the window opens, if it exists.
+    pub default_category: Option<String>,
+    /// Reopen on the category that was selected when the window closed.
+    pub remember_last_category: bool,
+    /// Put headings above the special categories and the rest in the flat
+    /// categories list.
+    pub category_headings: bool,
+    /// Return to the program last selected in a category when it is shown
+    /// again during the session.
+    pub remember_program_position: bool,
+    /// Wrap Up/Down arrow navigation from one end of a list to the other.
+    pub wrap_navigation: bool,
+    /// Show a Flatpak and a system package of the same app as one row.
+    pub merge_duplicates: bool,
+    /// List a desktop file reached through several symlinks only once.
+    pub dedup_symlinks: bool,
+    /// Leave `Terminal=true` apps out of the launcher entirely.
+    pub hide_terminal_apps: bool,
+    /// Diagnostics mode: also list apps whose Exec program is missing,
+    /// marked with a warning.
+    pub show_broken_apps: bool,
+    /// Add a "Terminal Apps" pseudo-category listing only terminal apps.
+    pub terminal_apps_category: bool,
+    /// Apps whose desktop file changed within this many days get a "New"
+    /// badge; 0 turns it off.
+    pub new_app_days: u32,
+    /// Adds a "New" category listing those apps.
+    pub new_apps_category: bool,
+    /// Accelerator that launches the focused program, in GTK syntax such as
+    /// `<Control>Return`.
+    pub activate_shortcut: String,
+    /// Show each program's Exec command as a second line in the list.
+    pub show_exec_subtitle: bool,
+    /// Quit after this many seconds without input; 0 never quits.
+    pub idle_quit_seconds: u32,
+    /// Use GTK's high-contrast theme.
+    pub high_contrast: bool,
+    /// Text size multiplier, between 0.5 and 3.
+    pub font_scale: f64,
+    /// Spacing preset for list rows and grid tiles.
+    pub row_density: RowDensity,
+    /// Extra row padding in pixels, overriding the preset's.
+    pub row_padding: Option<u32>,
+    /// Minimum row height in pixels, overriding the preset's.
+    pub row_min_height: Option<u32>,
+    /// Margin around widgets in pixels, overriding the preset's.
+    pub widget_margin: Option<u32>,
+    /// Set once the first-run accessibility setup was saved or skipped.
+    pub first_run_completed: bool,
+    /// Close the window after an app is launched successfully.
+    pub quit_on_launch: bool,
+    /// Ask "Launch Firefox?" before each launch from the programs pane.
+    pub confirm_launch: bool,
+    /// Open the window maximized, as it always did before this was
+    /// configurable; `false` opens it at its default size.
+    pub start_maximized: bool,
+    /// Open the window fullscreen; wins over `start_maximized`.
+    pub start_fullscreen: bool,
+    /// Hide the categories pane and list every app until a search narrows
+    /// them.
+    pub compact_mode: bool,
+    /// Voice focus and launch announcements through `spd-say`.
+    pub speak: bool,
+    /// Subdivide the "Other" category by the raw freedesktop categories.
+    pub group_other: bool,
+    /// Categories listed first in the sidebar, below the pseudo-categories,
+    /// by their names in [`CATEGORIES`].
+    pub pinned_categories: Vec<String>,
+    /// Directories whose executable `*.AppImage` files are listed as apps;
+    /// empty turns the AppImage scan off.
+    pub appimage_dirs: Vec<PathBuf>,
+    /// Names of application subdirectories that are never scanned.
+    pub excluded_subdirs: Vec<String>,
+    /// Reuse the entries cache instead of parsing every desktop file while
+    /// no application directory has changed.
+    pub entries_cache: bool,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            show_empty_categories: false,
+            category_tree: false,
+            search_mode: SearchMode::default(),
+            search_scope: SearchScope::default(),
+            program_view: ProgramView::default(),
+            most_used_limit: 20,
+            max_visible_results: 500,
+            category_order: CategoryOrder::default(),
+            default_category: None,
+            remember_last_category: false,
+            category_headings: false,
+            remember_program_position: false,
+            wrap_navigation: false,
+            merge_duplicates: false,
+            dedup_symlinks: false,
+            hide_terminal_apps: false,
+            show_broken_apps: false,
+            quit_on_launch: false,
+            confirm_launch: false,
+            start_maximized: true,
+            start_fullscreen: false,
+            compact_mode: false,
+            speak: false,
+            high_contrast: false,
+            font_scale: 1.0,
+            row_density: RowDensity::default(),
+            row_padding: None,
+            row_min_height: None,
+            widget_margin: None,
+            first_run_completed: false,
+            idle_quit_seconds: 0,
+            show_exec_subtitle: false,
+            activate_shortcut: DEFAULT_ACTIVATE_SHORTCUT.to_string(),
+            terminal_apps_category: false,
+            new_app_days: 7,
+            new_apps_category: false,
+            group_other: false,
+            pinned_categories: Vec::new(),
+            appimage_dirs: Vec::new(),
+            entries_cache: false,
+            excluded_subdirs: DEFAULT_EXCLUDED_SUBDIRS
+                .iter()
+                .map(|dir| dir.to_string())
+                .collect(),
+        }
+    }
+}
+
+impl Config {
+    pub fn parse(contents: &str) -> Self {
+        let mut config = Self::default();
+        config.apply(contents);
+        config
+    }
+
+    /// Applies `key=value` lines on top of the current values.
+    pub fn apply(&mut self, contents: &str) {
+        for line in contents.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let Some((key, value)) = line.split_once('=') else {
+                continue;
+            };
+            match key.trim() {
+                "show_empty_categories" => self.show_empty_categories = parse_bool(value),
+                "category_tree" => self.category_tree = parse_bool(value),
+                "search_mode" => {
+                    if let Some(mode) = SearchMode::parse(value) {
+                        self.search_mode = mode;
+                    }
+                }
+                "search_scope" => {
+                    if let Some(scope) = SearchScope::parse(value) {
+                        self.search_scope = scope;
+                    }
+                }
+                "program_view" => {
+                    if let Some(view) = ProgramView::parse(value) {
+                        self.program_view = view;
+                    }
+                }
+                "most_used_limit" => {
+                    if let Ok(limit) = value.trim().parse() {
+                        self.most_used_limit = limit;
+                    }
+                }
+                "max_visible_results" => {
+                    if let Ok(limit) = value.trim().parse() {
+                        self.max_visible_results = limit;
+                    }
+                }
+                "category_order" => {
+                    if let Some(order) = CategoryOrder::parse(value) {
+                        self.category_order = order;
+                    }
+                }
+                "default_category" => {
+                    let value = value.trim();
+                    self.default_category = (!value.is_empty()).then(|| value.to_string());
+                }
+                "remember_last_category" => self.remember_last_category = parse_bool(value),
+                "category_headings" => self.category_headings = parse_bool(value),
+                "remember_program_position" => self.remember_program_position = parse_bool(value),
+                "wrap_navigation" => self.wrap_navigation = parse_bool(value),
+                "merge_duplicates" => self.merge_duplicates = parse_bool(value),
+                "dedup_symlinks" => self.dedup_symlinks = parse_bool(value),
+                "hide_terminal_apps" => self.hide_terminal_apps = parse_bool(value),
+                "show_broken_apps" => self.show_broken_apps = parse_bool(value),
+                "terminal_apps_category" => self.terminal_apps_category = parse_bool(value),
+                "new_app_days" => {
+                    if let Ok(days) = value.trim().parse() {
+                        self.new_app_days = days;
+                    }
+                }
+                "new_apps_category" => self.new_apps_category = parse_bool(value),
+                "activate_shortcut" => {
+                    let value = value.trim();
+                    if !value.is_empty() {
+                        self.activate_shortcut = value.to_string();
+                    }
+                }
+                "show_exec_subtitle" => self.show_exec_subtitle = parse_bool(value),
+                "idle_quit_seconds" => {
+                    if let Ok(seconds) = value.trim().parse() {
+                        self.idle_quit_seconds = seconds;
+                    }
+                }
+                "high_contrast" => self.high_contrast = parse_bool(value),
+                "font_scale" => {
+                    if let Ok(scale) = value.trim().parse::<f64>() {
+                        if scale.is_finite() {
+                            self.font_scale = scale.clamp(0.5, 3.0);
+                        }
+                    }
+                }
+                "row_density" => {
+                    if let Some(density) = RowDensity::parse(value) {
+                        self.row_density = density;
+                    }
+                }
+                "row_padding" => {
+                    if let Ok(padding) = value.trim().parse::<u32>() {
+                        self.row_padding = Some(padding.min(MAX_ROW_PADDING));
+                    }
+                }
+                "row_min_height" => {
+                    if let Ok(height) = value.trim().parse::<u32>() {
+                        self.row_min_height = Some(height.min(MAX_ROW_MIN_HEIGHT));
+                    }
+                }
+                "widget_margin" => {
+                    if let Ok(margin) = value.trim().parse::<u32>() {
+                        self.widget_margin = Some(margin.min(MAX_WIDGET_MARGIN));
+                    }
+                }
+                "first_run_completed" => self.first_run_completed = parse_bool(value),
+                "quit_on_launch" => self.quit_on_launch = parse_bool(value),
+                "confirm_launch" => self.confirm_launch = parse_bool(value),
+                "speak" => self.speak = parse_bool(value),
+                "compact_mode" => self.compact_mode = parse_bool(value),
+                "start_maximized" => self.start_maximized = parse_bool(value),
+                "start_fullscreen" => self.start_fullscreen = parse_bool(value),
+                "group_other" => self.group_other = parse_bool(value),
+                "pinned_categories" => self.pinned_categories = parse_pinned_categories(value),
+                "entries_cache" => self.entries_cache = parse_bool(value),
+                "appimage_dirs" => {
+                    self.appimage_dirs = value
+                        .split(';')
+                        .map(str::trim)
+                        .filter(|dir| !dir.is_empty())
+                        .map(expand_home)
+                        .collect();
+                }
+                "excluded_subdirs" => {
+                    self.excluded_subdirs = value
+                        .split(';')
+                        .map(str::trim)
+                        .filter(|dir| !dir.is_empty())
+                        .map(str::to_string)
+                        .collect();
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Picks the category to select on startup from `available`.
+    ///
+    /// The remembered `last` category wins when `remember_last_category` is
+    /// set, then `default_category`. Names match case-insensitively. Returns
+    /// `None` when neither names an available category, so the caller keeps
+    /// its usual first row.
+    pub fn initial_category<'a>(
+        &self,
+        last: Option<&str>,
+        available: &[&'a str],
+    ) -> Option<&'a str> {
+        let find = |wanted: &str| {
+            available
+                .iter()
+                .copied()
+                .find(|category| category.eq_ignore_ascii_case(wanted.trim()))
+        };
+        last.filter(|_| self.remember_last_category)
+            .and_then(find)
+            .or_else(|| self.default_category.as_deref().and_then(find))
+    }
+
+    /// Padding and minimum height for rows and tiles, in pixels:
+    /// `row_padding` and `row_min_height` when set, else the preset's.
+    /// `None` leaves the theme's value alone.
+    pub fn row_spacing(&self) -> (Option<u32>, Option<u32>) {
+        let (padding, min_height) = self.row_density.spacing();
+        (
+            self.row_padding.or(padding),
+            self.row_min_height.or(min_height),
+        )
+    }
+
+    /// Margin around widgets, in pixels: `widget_margin` when set, else
+    /// the preset's.
+    pub fn widget_margin(&self) -> u32 {
+        self.widget_margin
+            .unwrap_or_else(|| self.row_density.margin())
+    }
+}
+
+/// System-wide defaults set by an administrator.
+pub const SYSTEM_CONFIG_PATH: &str = "/etc/access-launcher/config";
+
+/// Loads `/etc/access-launcher/config`, then the user's config on top, so
+/// user settings override the system defaults key by key.
+pub fn load_config() -> Config {
+    let mut paths = vec![PathBuf::from(SYSTEM_CONFIG_PATH)];
+    paths.extend(config_dir().map(|dir| dir.join("config")));
+    load_config_from(&paths)
+}
+
+/// True on the very first launch: the user has no config file yet and has
+/// not finished or skipped the first-run setup.
+pub fn is_first_run(config: &Config) -> bool {
+    !config.first_run_completed && config_dir().is_some_and(|dir| !dir.join("config").exists())
+}
+
+/// Applies each file in `paths` in order; later files win. Missing or
+/// unreadable files count as empty.
+pub fn load_config_from(paths: &[PathBuf]) -> Config {
+    let mut config = Config::default();
+    for path in paths {
+        if let Ok(contents) = fs::read_to_string(path) {
+            config.apply(&contents);
+        }
+    }
+    config
+}
+
+/// Returns `contents` with `key` set to `value`: the last existing `key=`
+/// line is rewritten in place, otherwise a line is appended. Comments and
+/// other keys are kept as they are.
+pub fn set_config_value(contents: &str, key: &str, value: &str) -> String {
+    let mut lines: Vec<String> = contents.lines().map(str::to_string).collect();
+    let existing = lines.iter().rposition(|line| {
+        let line = line.trim();
+        !line.starts_with('#')
+            && line
+                .split_once('=')
+                .is_some_and(|(name, _)| name.trim() == key)
+    });
+    let updated = format!("{key}={value}");
+    match existing {
+        Some(index) => lines[index] = updated,
+        None => lines.push(updated),
+    }
+    let mut contents = lines.join("\n");
+    contents.push('\n');
+    contents
+}
+
+/// Persists a single setting in the user's config file.
+pub fn save_config_value(key: &str, value: &str) -> io::Result<()> {
+    let Some(path) = config_dir().map(|dir| dir.join("config")) else {
+        return Ok(());
+    };
+    let contents = fs::read_to_string(&path).unwrap_or_default();
+    write_atomic(&path, &set_config_value(&contents, key, value))
+}
+
+/// Returns `$XDG_CONFIG_HOME/access-launcher`, falling back to `~/.config`.
+pub fn config_dir() -> Option<PathBuf> {
+    env::var("XDG_CONFIG_HOME")
+        .ok()
+        .filter(|value| !value.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| {
+            env::var("HOME")
+                .ok()
+                .map(|home| PathBuf::from(home).join(".config"))
+        })
+        .map(|dir| dir.join("access-launcher"))
+}
+
+/// Returns the file mapping desktop IDs to user-chosen display names.
+pub fn name_overrides_path() -> Option<PathBuf> {
+    config_dir().map(|dir| dir.join("name-overrides.conf"))
+}
+
+/// Reads `desktop-id=Display Name` lines. Blank lines and `#` comments are
+/// skipped, as are lines with an empty name. A missing file has no overrides.
+pub fn read_name_overrides(path: &Path) -> BTreeMap<String, String> {
+    read_id_map(path)
+}
+
+/// Reads `desktop-id=value` lines, skipping blanks, `#` comments, and lines
+/// with an empty ID or value.
+fn read_id_map(path: &Path) -> BTreeMap<String, String> {
+    let Ok(contents) = fs::read_to_string(path) else {
+        return BTreeMap::new();
+    };
+    contents
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(|line| line.split_once('='))
+        .map(|(id, value)| (id.trim().to_string(), value.trim().to_string()))
+        .filter(|(id, value)| !id.is_empty() && !value.is_empty())
+        .collect()
+}
+
+/// Parses a comma-separated `pinned_categories` value into category names
+/// as [`CATEGORIES`] spells them, ignoring case. Unknown names are logged
+/// and dropped, as are repeats.
+fn parse_pinned_categories(value: &str) -> Vec<String> {
+    let mut pinned: Vec<String> = Vec::new();
+    for name in value
+        .split(',')
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+    {
+        match CATEGORIES
+            .iter()
+            .find(|category| category.eq_ignore_ascii_case(name))
+        {
+            Some(category) if !pinned.iter().any(|pin| pin == category) => {
+                pinned.push(category.to_string());
+            }
+            Some(_) => {}
+            None => log_error!("Ignoring unknown pinned category {name}"),
+        }
+    }
+    pinned
+}
+
+/// Returns the file of pattern rules for bucketing unknown categories.
+pub fn category_rules_path() -> Option<PathBuf> {
+    config_dir().map(|dir| dir.join("categories.conf"))
+}
+
+/// Parses `pattern=Category` lines into [`CategoryRule`]s, in file order.
+/// Lines that do not compile, and any beyond [`MAX_CATEGORY_RULES`], are
+/// skipped, each with a message in the returned list.
+pub fn parse_category_rules(contents: &str) -> (Vec<CategoryRule>, Vec<String>) {
+    let mut rules = Vec::new();
+    let mut warnings = Vec::new();
+    for (number, line) in contents.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if rules.len() == MAX_CATEGORY_RULES {
+            warnings.push(format!(
+                "line {}: only the first {MAX_CATEGORY_RULES} rules are used",
+                number + 1
+            ));
+            break;
+        }
+        // Split at the last `=` so a pattern may contain one.
+        let Some((pattern, category)) = line.rsplit_once('=') else {
+            warnings.push(format!("line {}: expected pattern=category", number + 1));
+            continue;
+        };
+        match CategoryRule::new(pattern, category) {
+            Ok(rule) => rules.push(rule),
+            Err(err) => warnings.push(format!("line {}: {err}", number + 1)),
+        }
+    }
+    (rules, warnings)
+}
+
+/// Reads `categories.conf`, logging skipped lines. A missing file has no
+/// rules.
+pub fn load_category_rules() -> Vec<CategoryRule> {
+    let Some(path) = category_rules_path() else {
+        return Vec::new();
+    };
+    let Ok(contents) = fs::read_to_string(&path) else {
+        return Vec::new();
+    };
+    let (rules, warnings) = parse_category_rules(&contents);
+    for warning in warnings {
+        log_error!("Ignoring {}, {warning}", path.display());
+    }
+    rules
+}
+
+/// Returns the file mapping window actions to keyboard shortcuts.
+pub fn keymap_path() -> Option<PathBuf> {
+    config_dir().map(|dir| dir.join("keymap.conf"))
+}
+
+/// A window action whose shortcuts `keymap.conf` can change.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct KeymapAction {
+    /// Name used in `keymap.conf` and as the `app.` action name.
+    pub name: &'static str,
+    pub description: &'static str,
+    /// Accelerators used when `keymap.conf` does not map the action.
+    pub defaults: &'static [&'static str],
+}
+
+/// Action name for launching the focused app; it defaults to the
+/// `activate_shortcut` setting rather than a fixed key.
+pub const LAUNCH_ACTION: &str = "launch";
+
+pub const KEYMAP_ACTIONS: [KeymapAction; 14] = [
+    KeymapAction {
+        name: LAUNCH_ACTION,
+        description: "Launch the focused application",
+        defaults: &[],
+    },
+    KeymapAction {
+        name: "focus-search",
+        description: "Move focus to the search box",
+        defaults: &["<Control>f"],
+    },
+    KeymapAction {
+        name: "focus-other-pane",
+        description: "Move focus to the other pane",
+        defaults: &["F6", "<Shift>F6"],
+    },
+    KeymapAction {
+        name: "quick-launch",
+        description: "Open quick launch",
+        defaults: &["<Control>space"],
+    },
+    KeymapAction {
+        name: "toggle-multi-select",
+        description: "Turn multi-select on or off",
+        defaults: &["<Control>m"],
+    },
+    KeymapAction {
+        name: "toggle-compact-mode",
+        description: "Show or hide the categories pane",
+        defaults: &["F9"],
+    },
+    KeymapAction {
+        name: "toggle-row-density",
+        description: "Switch between compact and comfortable rows",
+        defaults: &["F8"],
+    },
+    KeymapAction {
+        name: "clear-history",
+        description: "Clear the launch history",
+        defaults: &["<Control><Shift>Delete"],
+    },
+    KeymapAction {
+        name: "open-terminal",
+        description: "Open a terminal",
+        defaults: &["<Control>t"],
+    },
+    KeymapAction {
+        name: "refresh",
+        description: "Rescan applications",
+        defaults: &["F5"],
+    },
+    KeymapAction {
+        name: "reload-config",
+        description: "Reload the configuration",
+        defaults: &["<Control>r"],
+    },
+    KeymapAction {
+        name: "shortcuts",
+        description: "Show keyboard shortcuts",
+        defaults: &["<Control>question"],
+    },
+    KeymapAction {
+        name: "about",
+        description: "Show the About dialog",
+        defaults: &["F1"],
+    },
+    KeymapAction {
+        name: "quit",
+        description: "Quit",
+        defaults: &["<Control>q"],
+    },
+];
+
+/// Shortcuts from `keymap.conf`, keyed by action name.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Keymap {
+    pub bindings: BTreeMap<String, Vec<String>>,
+}
+
+impl Keymap {
+    /// Parses `action=accelerator` lines; several accelerators are separated
+    /// by `;` and an empty value leaves the action without a shortcut.
+    /// Lines without `=` and unknown action names are skipped, each with a
+    /// message in the returned list. Accelerators are checked by the caller,
+    /// which has GTK at hand.
+    pub fn parse(contents: &str) -> (Self, Vec<String>) {
+        let mut keymap = Self::default();
+        let mut warnings = Vec::new();
+        for (number, line) in contents.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let Some((action, value)) = line.split_once('=') else {
+                warnings.push(format!("line {}: expected action=shortcut", number + 1));
+                continue;
+            };
+            let action = action.trim();
+            if !KEYMAP_ACTIONS.iter().any(|known| known.name == action) {
+                warnings.push(format!("line {}: unknown action {action}", number + 1));
+                continue;
+            }
+            let accels = value
+                .split(';')
+                .map(str::trim)
+                .filter(|accel| !accel.is_empty())
+                .map(str::to_string)
+                .collect();
+            keymap.bindings.insert(action.to_string(), accels);
+        }
+        (keymap, warnings)
+    }
+
+    /// Returns the accelerators for `action`: the mapped ones, or else its
+    /// defaults.
+    pub fn accels(&self, action: &KeymapAction) -> Vec<String> {
+        match self.bindings.get(action.name) {
+            Some(accels) => accels.clone(),
+            None => action
+                .defaults
+                .iter()
+                .map(|accel| accel.to_string())
+                .collect(),
+        }
+    }
+}
+
+/// Reads `keymap.conf`, logging skipped lines. A missing file gives the
+/// default shortcuts.
+pub fn load_keymap() -> Keymap {
+    let Some(path) = keymap_path() else {
+        return Keymap::default();
+    };
+    let Ok(contents) = fs::read_to_string(&path) else {
+        return Keymap::default();
+    };
+    let (keymap, warnings) = Keymap::parse(&contents);
+    for warning in warnings {
+        log_error!("Ignoring {}, {warning}", path.display());
+    }
+    keymap
+}
+
+/// Returns the path of the optional pre-launch hook executable.
+pub fn pre_launch_hook_path() -> Option<PathBuf> {
+    config_dir().map(|dir| dir.join("pre-launch"))
+}
+
+/// Returns the file mapping desktop IDs to launch wrapper commands.
+pub fn wrappers_path() -> Option<PathBuf> {
+    config_dir().map(|dir| dir.join("wrappers.conf"))
+}
+
+/// Reads `desktop-id=wrapper command` lines, e.g. `steam.desktop=gamemoderun`.
+/// IDs may leave out `.desktop`; keys always include it.
+pub fn read_wrappers(path: &Path) -> BTreeMap<String, String> {
+    read_id_map(path)
+        .into_iter()
+        .map(|(id, wrapper)| {
+            let id = if id.ends_with(".desktop") {
+                id
+            } else {
+                format!("{id}.desktop")
+            };
+            (id, wrapper)
+        })
+        .collect()
+}
+
+pub fn write_name_overrides(path: &Path, overrides: &BTreeMap<String, String>) -> io::Result<()> {
+    let mut contents = String::from("# Display names set with the launcher's Rename action.\n");
+    for (id, name) in overrides {
+        contents.push_str(&format!("{id}={name}\n"));
+    }
+    write_atomic(path, &contents)
+}
+
+/// Returns the file listing desktop IDs the user hid with "Hide this app".
+pub fn blocklist_path() -> Option<PathBuf> {
+    config_dir().map(|dir| dir.join("blocklist.conf"))
+}
+
+/// Reads one desktop ID per line. Blank lines and `#` comments are skipped;
+/// a missing file blocks nothing.
+pub fn read_blocklist(path: &Path) -> BTreeSet<String> {
+    let Ok(contents) = fs::read_to_string(path) else {
+        return BTreeSet::new();
+    };
+    contents
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(str::to_string)
+        .collect()
+}
+
+pub fn write_blocklist(path: &Path, blocklist: &BTreeSet<String>) -> io::Result<()> {
+    let mut contents = String::from("# Desktop IDs hidden with the launcher's Hide action.\n");
+    for id in blocklist {
+        contents.push_str(&format!("{id}\n"));
+    }
+    write_atomic(path, &contents)
+}
+
+fn expand_home(value: &str) -> PathBuf {
+    if let Some(rest) = value.strip_prefix("~/") {
+        if let Ok(home) = env::var("HOME") {
+            return PathBuf::from(home).join(rest);
+        }
+    }
+    PathBuf::from(value)
+}
+
+/// Reads a newline-separated list of extra desktop directories.
+///
+/// Blank lines and `#` comments are skipped. A missing file yields no dirs.
+pub fn read_extra_dirs(path: &Path) -> Vec<PathBuf> {
+    let contents = match fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(_) => return Vec::new(),
+    };
+    contents
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(expand_home)
+        .collect()
+}
@@ -1,16 +1,145 @@
+use crate::cache::collect_with_cache;
+use crate::config::{config_dir, read_extra_dirs, Config};
+use crate::json;
+use crate::launch::{find_in_path, is_executable_file};
+use crate::logging::{format_duration, start_timer};
+use crate::{log_debug, log_info};
 use gtk4::glib;
-use std::collections::{BTreeMap, HashSet};
+use regex::{Regex, RegexBuilder};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::env;
+use std::ffi::OsStr;
+use std::fmt;
 use std::fs;
-use std::io::{BufRead, BufReader};
+use std::io::{self, BufRead, BufReader};
+use std::os::unix::fs::MetadataExt;
 use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Default)]
 pub struct DesktopEntry {
+    /// Desktop file ID, e.g. `firefox.desktop`.
+    pub id: String,
     pub name: String,
+    /// `GenericName`, such as "Web Browser", localized like `name`.
+    pub generic_name: Option<String>,
+    /// `Comment`, a short description, localized like `name`.
+    pub comment: Option<String>,
     pub exec: String,
+    /// `TryExec` program, kept for diagnostics.
+    pub try_exec: Option<String>,
     pub categories: String,
+    /// Raw `;`-separated keywords from `Keywords` and vendor `X-*-Keywords` keys.
+    pub keywords: String,
     pub path: PathBuf,
+    pub icon: Option<String>,
+    pub terminal: bool,
+    /// Working directory from the `Path` key.
+    pub working_dir: Option<String>,
+    pub startup_notify: bool,
+    /// `StartupWMClass`: the window class the app's windows will have, so
+    /// taskbars and window lists can match windows back to this entry.
+    pub startup_wm_class: Option<String>,
+    /// `DBusActivatable=true`: the app prefers D-Bus activation over Exec.
+    pub dbus_activatable: bool,
+    /// `PrefersNonDefaultGPU=true`: on hybrid-graphics systems the app
+    /// should run on the discrete GPU.
+    pub prefers_non_default_gpu: bool,
+    /// `NoDisplay=true`: installed but kept out of menus, e.g. a helper.
+    pub no_display: bool,
+    /// `Hidden=true`: the app counts as deleted.
+    pub hidden: bool,
+    /// Spec version from the `Version` key; informational only.
+    pub version: Option<String>,
+    /// D-Bus interfaces from the `Implements` key; informational only.
+    pub implements: Vec<String>,
+    /// `X-GNOME-UsesNotifications=true`; informational only.
+    pub uses_notifications: bool,
+    /// `[Desktop Action ...]` groups named by the `Actions` key, in its order.
+    pub actions: Vec<DesktopAction>,
+    /// Name from the desktop file when `name` comes from a user override.
+    pub original_name: Option<String>,
+    /// The untranslated `Name` when `name` is a translation that differs
+    /// from it. Searches match it like the displayed name.
+    pub base_name: Option<String>,
+    /// Other packagings of the same app folded into this row by
+    /// [`merge_duplicate_entries`].
+    pub alternates: Vec<DesktopEntry>,
+    /// Program the Exec line names that does not exist. Only set on entries
+    /// listed for diagnostics by [`add_broken_entries`].
+    pub missing_program: Option<String>,
+    /// When the desktop file (or the symlink to it) last changed, roughly
+    /// when the app was installed.
+    pub modified: Option<SystemTime>,
+    /// Set by [`mark_recently_installed`] for apps installed lately.
+    pub recently_installed: bool,
+    /// Synthesized by [`appimage_entry`]: `path` is an AppImage, not a
+    /// desktop file.
+    pub appimage: bool,
+    /// Set by [`mark_duplicate_names`] when another entry shows the same
+    /// name, e.g. "Flatpak", so the two rows can be told apart.
+    pub name_suffix: Option<String>,
+}
+
+/// An additional way to start an app, from a `[Desktop Action <id>]` group.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct DesktopAction {
+    pub id: String,
+    pub name: String,
+    pub exec: String,
+}
+
+/// One line of a desktop file, as [`read_entry_line`] sees it.
+enum EntryLine<'a> {
+    /// A blank line, a comment, or a line without a key.
+    Skip,
+    /// A `[Group Name]` header, brackets included.
+    Group(&'a str),
+    Key(&'a str, &'a str),
+}
+
+/// Reads the next line of a desktop file into `line_buf`, or `None` at the
+/// end. `first_line` is set until the first line is read, so a BOM in
+/// front of it is dropped.
+fn read_entry_line<'a>(
+    reader: &mut impl BufRead,
+    line_buf: &'a mut String,
+    first_line: &mut bool,
+) -> Option<EntryLine<'a>> {
+    line_buf.clear();
+    match reader.read_line(line_buf) {
+        Ok(0) | Err(_) => return None,
+        Ok(_) => {}
+    }
+
+    // `trim` also drops the `\r` of CRLF line endings, but not a BOM,
+    // which would hide a leading `[Desktop Entry]` header.
+    let mut line = line_buf.trim();
+    if *first_line {
+        *first_line = false;
+        line = line.trim_start_matches(BOM).trim_start();
+    }
+    let Some(&first_byte) = line.as_bytes().first() else {
+        return Some(EntryLine::Skip);
+    };
+    if first_byte == b'#' {
+        return Some(EntryLine::Skip);
+    }
+    if first_byte == b'[' && line.ends_with(']') {
+        return Some(EntryLine::Group(line));
+    }
+    match line.split_once('=') {
+        Some((key, value)) if !key.is_empty() => Some(EntryLine::Key(key, value.trim())),
+        _ => Some(EntryLine::Skip),
+    }
+}
+
+/// The group of a desktop file that key lines currently belong to.
+enum Group {
+    Entry,
+    /// Index into the actions collected so far.
+    Action(usize),
+    Other,
 }
 
 fn push_unique(dirs: &mut Vec<PathBuf>, seen: &mut HashSet<PathBuf>, path: PathBuf) {
@@ -20,11 +149,9 @@ fn push_unique(dirs: &mut Vec<PathBuf>, seen: &mut HashSet<PathBuf>, path: PathB
     }
 }
 
-fn desktop_dirs() -> Vec<PathBuf> {
-    let mut dirs = Vec::new();
-    let mut seen = HashSet::new();
-
-    let data_home = env::var("XDG_DATA_HOME")
+/// Returns `$XDG_DATA_HOME`, falling back to `~/.local/share`.
+fn data_home() -> Option<PathBuf> {
+    env::var("XDG_DATA_HOME")
         .ok()
         .and_then(|value| {
             if value.is_empty() {
@@ -37,8 +164,88 @@ fn desktop_dirs() -> Vec<PathBuf> {
             env::var("HOME")
                 .ok()
                 .map(|home| PathBuf::from(home).join(".local/share"))
-        });
-    if let Some(data_home) = data_home {
+        })
+}
+
+/// Directories that hold icons: the user's and each data dir's `icons`,
+/// `~/.icons`, then the unthemed `pixmaps` directories.
+pub fn icon_search_dirs() -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+    let mut seen = HashSet::new();
+    if let Some(data_home) = data_home() {
+        push_unique(&mut dirs, &mut seen, data_home.join("icons"));
+    }
+    if let Ok(home) = env::var("HOME") {
+        push_unique(&mut dirs, &mut seen, PathBuf::from(home).join(".icons"));
+    }
+    let data_dirs: Vec<PathBuf> = env::var("XDG_DATA_DIRS")
+        .ok()
+        .filter(|value| !value.is_empty())
+        .unwrap_or_else(|| "/usr/local/share:/usr/share".to_string())
+        .split(':')
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .collect();
+    for dir in &data_dirs {
+        push_unique(&mut dirs, &mut seen, dir.join("icons"));
+    }
+    for dir in &data_dirs {
+        push_unique(&mut dirs, &mut seen, dir.join("pixmaps"));
+    }
+    push_unique(&mut dirs, &mut seen, PathBuf::from("/usr/share/pixmaps"));
+    dirs
+}
+
+/// Image extensions that mark an `Icon` value as a file name rather than a
+/// theme icon name.
+const ICON_FILE_EXTENSIONS: [&str; 3] = ["png", "svg", "xpm"];
+
+/// Returns `icon` without a `.png`, `.svg`, or `.xpm` extension, if it has one.
+pub fn strip_icon_extension(icon: &str) -> Option<&str> {
+    let (stem, extension) = icon.rsplit_once('.')?;
+    (!stem.is_empty()
+        && ICON_FILE_EXTENSIONS
+            .iter()
+            .any(|known| extension.eq_ignore_ascii_case(known)))
+    .then_some(stem)
+}
+
+/// Finds a bare icon file name such as `foo.png` directly inside one of
+/// `dirs`, the way legacy apps install into `/usr/share/pixmaps`.
+pub fn find_icon_file(icon: &str, dirs: &[PathBuf]) -> Option<PathBuf> {
+    if icon.contains('/') || strip_icon_extension(icon).is_none() {
+        return None;
+    }
+    dirs.iter()
+        .map(|dir| dir.join(icon))
+        .find(|path| path.is_file())
+}
+
+/// A UTF-8 byte order mark, which editors on Windows put at the start of
+/// files.
+const BOM: char = '\u{feff}';
+
+/// Splits a comma-separated `--entries-dir` value into directories,
+/// ignoring empty items.
+pub fn parse_entries_dirs(value: &str) -> Vec<PathBuf> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .collect()
+}
+
+/// Returns `dirs` when given, otherwise the standard search path.
+fn scan_dirs(dirs: Option<&[PathBuf]>) -> Vec<PathBuf> {
+    dirs.map_or_else(desktop_dirs, <[PathBuf]>::to_vec)
+}
+
+fn desktop_dirs() -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+    let mut seen = HashSet::new();
+
+    if let Some(data_home) = data_home() {
         push_unique(&mut dirs, &mut seen, data_home.join("applications"));
         push_unique(
             &mut dirs,
@@ -77,6 +284,13 @@ fn desktop_dirs() -> Vec<PathBuf> {
         PathBuf::from("/var/lib/flatpak/exports/share/applications"),
     );
 
+    // Snap exports are not always present in XDG_DATA_DIRS either.
+    push_unique(
+        &mut dirs,
+        &mut seen,
+        PathBuf::from("/var/lib/snapd/desktop/applications"),
+    );
+
     // NixOS profiles are not always present in XDG_DATA_DIRS.
     push_unique(
         &mut dirs,
@@ -113,26 +327,91 @@ fn desktop_dirs() -> Vec<PathBuf> {
             );
         }
     }
+
+    // User-configured extras come last so built-in precedence is unchanged.
+    if let Some(config_dir) = config_dir() {
+        for dir in read_extra_dirs(&config_dir.join("dirs.conf")) {
+            push_unique(&mut dirs, &mut seen, dir);
+        }
+    }
     dirs
 }
 
-fn walk_desktop_files(dir: &Path, cb: &mut impl FnMut(PathBuf)) {
+/// Subdirectories of an applications directory skipped by default.
+/// `screensavers` holds xscreensaver hacks rather than applications.
+pub const DEFAULT_EXCLUDED_SUBDIRS: [&str; 1] = ["screensavers"];
+
+/// Returns true when a subdirectory should not be scanned: its name is in
+/// `excluded`, it contains a `.hidden` marker file, or its `.directory`
+/// file sets `NoDisplay=true` or `Hidden=true`.
+fn subdir_is_hidden(dir: &Path, excluded: &[String]) -> bool {
+    let name = dir.file_name().and_then(|name| name.to_str()).unwrap_or("");
+    if excluded.iter().any(|excluded| excluded == name) {
+        return true;
+    }
+    if dir.join(".hidden").exists() {
+        return true;
+    }
+    let Ok(contents) = fs::read_to_string(dir.join(".directory")) else {
+        return false;
+    };
+    contents.lines().any(|line| {
+        line.split_once('=').is_some_and(|(key, value)| {
+            matches!(key.trim(), "NoDisplay" | "Hidden") && parse_bool(value)
+        })
+    })
+}
+
+/// Calls `cb` with every `.desktop` file under `dir`. Directories that
+/// cannot be listed are added to `unreadable` and skipped; the rest of the
+/// walk goes on. Symlinked directories are followed, and `visited` holds the
+/// device and inode of each directory already walked so a symlink cycle is
+/// entered only once.
+fn walk_desktop_files(
+    dir: &Path,
+    excluded: &[String],
+    visited: &mut HashSet<(u64, u64)>,
+    unreadable: &mut Vec<UnreadableDir>,
+    cb: &mut impl FnMut(PathBuf),
+) {
+    if let Ok(meta) = fs::metadata(dir) {
+        if !visited.insert((meta.dev(), meta.ino())) {
+            log_debug!("Skipping already scanned directory {}", dir.display());
+            return;
+        }
+    }
     let entries = match fs::read_dir(dir) {
         Ok(entries) => entries,
-        Err(_) => return,
+        Err(err) => {
+            log_info!("Cannot read directory {}: {err}", dir.display());
+            unreadable.push(UnreadableDir {
+                path: dir.to_path_buf(),
+                reason: err.to_string(),
+            });
+            return;
+        }
     };
 
     for entry in entries.flatten() {
-        let path = entry.path();
+        // The type comes from the directory listing where the filesystem
+        // reports it, so only entries of unknown type cost an extra lstat.
+        // That matters on network filesystems with thousands of files.
         let file_type = match entry.file_type() {
             Ok(file_type) => file_type,
             Err(_) => continue,
         };
 
-        if file_type.is_dir() {
-            walk_desktop_files(&path, cb);
+        let path = entry.path();
+        let is_dir = file_type.is_dir()
+            || (file_type.is_symlink() && fs::metadata(&path).is_ok_and(|meta| meta.is_dir()));
+        if is_dir {
+            if subdir_is_hidden(&path, excluded) {
+                log_debug!("Skipping hidden directory {}", path.display());
+                continue;
+            }
+            walk_desktop_files(&path, excluded, visited, unreadable, cb);
         } else if (file_type.is_file() || file_type.is_symlink())
-            && path.extension().and_then(|ext| ext.to_str()) == Some("desktop")
+            && Path::new(&entry.file_name()).extension() == Some(OsStr::new("desktop"))
         {
             cb(path);
         }
@@ -163,18 +442,105 @@ pub fn parse_bool(value: &str) -> bool {
     value.eq_ignore_ascii_case("true") || value == "1" || value.eq_ignore_ascii_case("yes")
 }
 
-fn desktop_list_matches(value: &str, current_desktops: &[String]) -> bool {
-    for part in value.split(';') {
-        if part.is_empty() {
-            continue;
+/// Returns true for `Keywords` and vendor extensions such as `X-GNOME-Keywords`,
+/// with or without a locale suffix that matches `current_lang`.
+fn is_keywords_key(key: &str, current_lang: Option<&str>) -> bool {
+    let (base, tag) = match key.find('[') {
+        Some(idx) => (&key[..idx], key[idx + 1..].strip_suffix(']')),
+        None => (key, None),
+    };
+    let base_matches =
+        base == "Keywords" || (base.starts_with("X-") && base.ends_with("-Keywords"));
+    if !base_matches {
+        return false;
+    }
+    match tag {
+        Some(tag) => current_lang.is_some_and(|lang| matches_lang_tag(tag, lang)),
+        None => !key.contains('['),
+    }
+}
+
+fn push_keywords(keywords: &mut String, value: &str) {
+    if value.is_empty() {
+        return;
+    }
+    if !keywords.is_empty() && !keywords.ends_with(';') {
+        keywords.push(';');
+    }
+    keywords.push_str(value);
+}
+
+/// Splits `XDG_CURRENT_DESKTOP`, e.g. `ubuntu:GNOME`, into desktop names.
+pub fn parse_current_desktops(value: &str) -> Vec<String> {
+    value
+        .split(':')
+        .map(str::trim)
+        .filter(|desktop| !desktop.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Compares desktop names case-insensitively, ignoring a vendor `X-` prefix
+/// so `X-Cinnamon` matches `Cinnamon`.
+fn desktop_names_match(a: &str, b: &str) -> bool {
+    fn strip_vendor(name: &str) -> &str {
+        match name.get(..2) {
+            Some(prefix) if prefix.eq_ignore_ascii_case("X-") => &name[2..],
+            _ => name,
         }
-        for desktop in current_desktops {
-            if desktop == part {
-                return true;
-            }
+    }
+    strip_vendor(a.trim()).eq_ignore_ascii_case(strip_vendor(b.trim()))
+}
+
+fn desktop_list_matches(value: &str, current_desktops: &[String]) -> bool {
+    value
+        .split(';')
+        .filter(|part| !part.is_empty())
+        .any(|part| {
+            current_desktops
+                .iter()
+                .any(|desktop| desktop_names_match(desktop, part))
+        })
+}
+
+/// Why a desktop file did not produce a launchable entry.
+#[derive(Debug)]
+pub enum DesktopParseError {
+    Io(io::Error),
+    /// `Type` is missing or not `Application`.
+    NotApplication,
+    NoDisplay,
+    Hidden,
+    /// Excluded for the current desktop by `OnlyShowIn` or `NotShowIn`.
+    NotShownIn,
+    MissingExec,
+    InvalidExec(String),
+}
+
+impl fmt::Display for DesktopParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "could not be read: {err}"),
+            Self::NotApplication => write!(f, "is not an application"),
+            Self::NoDisplay => write!(f, "sets NoDisplay=true"),
+            Self::Hidden => write!(f, "sets Hidden=true"),
+            Self::NotShownIn => write!(f, "is not shown in the current desktop"),
+            Self::MissingExec => write!(f, "has no Exec line"),
+            Self::InvalidExec(exec) => write!(f, "has an Exec that cannot run: {exec}"),
         }
     }
-    false
+}
+
+impl std::error::Error for DesktopParseError {}
+
+impl DesktopParseError {
+    /// Whether a file rejected this way still claims its desktop ID, hiding
+    /// copies in later directories. Only a broken Exec lets a later copy
+    /// through; Hidden, NoDisplay and filtered entries claim the ID, which
+    /// is how a user-level copy hides a system application.
+    pub fn claims_id(&self) -> bool {
+        !matches!(self, Self::MissingExec | Self::InvalidExec(_))
+    }
 }
 
 pub fn parse_desktop_entry(
@@ -183,68 +549,172 @@ pub fn parse_desktop_entry(
     current_desktops: Option<&[String]>,
     line_buf: &mut String,
 ) -> Option<DesktopEntry> {
-    let file = fs::File::open(path).ok()?;
+    parse_desktop_entry_result(path, current_lang, current_desktops, line_buf).ok()
+}
+
+/// Like [`parse_desktop_entry`], but reports why a file was rejected.
+pub fn parse_desktop_entry_result(
+    path: &Path,
+    current_lang: Option<&str>,
+    current_desktops: Option<&[String]>,
+    line_buf: &mut String,
+) -> Result<DesktopEntry, DesktopParseError> {
+    let entry = read_desktop_entry(path, current_lang, current_desktops, false, line_buf)?;
+    validate_exec(&entry, &mut ProgramCache::default())?;
+    Ok(entry)
+}
+
+/// Like [`parse_desktop_entry_result`], but keeps `NoDisplay` and `Hidden`
+/// entries with [`DesktopEntry::no_display`] and [`DesktopEntry::hidden`]
+/// set, for views that list them on purpose.
+pub fn parse_desktop_entry_with_hidden(
+    path: &Path,
+    current_lang: Option<&str>,
+    current_desktops: Option<&[String]>,
+    line_buf: &mut String,
+) -> Result<DesktopEntry, DesktopParseError> {
+    let entry = read_desktop_entry(path, current_lang, current_desktops, true, line_buf)?;
+    validate_exec(&entry, &mut ProgramCache::default())?;
+    Ok(entry)
+}
+
+fn validate_exec(entry: &DesktopEntry, cache: &mut ProgramCache) -> Result<(), DesktopParseError> {
+    if entry.exec.trim().is_empty() {
+        Err(DesktopParseError::MissingExec)
+    } else if !exec_looks_valid_cached(&entry.exec, cache) {
+        Err(DesktopParseError::InvalidExec(entry.exec.clone()))
+    } else {
+        Ok(())
+    }
+}
+
+/// The error for a file setting both `OnlyShowIn` and `NotShowIn`.
+fn both_show_in_keys(path: &Path) -> DesktopParseError {
+    log_info!(
+        "{} sets both OnlyShowIn and NotShowIn; hiding it",
+        path.display()
+    );
+    DesktopParseError::NotShownIn
+}
+
+/// Parses an entry without validating its Exec line.
+///
+/// Fails for files that should stay hidden: unreadable files,
+/// non-applications, `NoDisplay`/`Hidden` entries, and entries filtered out
+/// by `OnlyShowIn`/`NotShowIn`. A missing Exec yields an empty `exec`.
+/// Both keys are ignored when `current_desktops` is `None` or empty. With
+/// `keep_hidden`, `NoDisplay` and `Hidden` are recorded instead of failing.
+fn read_desktop_entry(
+    path: &Path,
+    current_lang: Option<&str>,
+    current_desktops: Option<&[String]>,
+    keep_hidden: bool,
+    line_buf: &mut String,
+) -> Result<DesktopEntry, DesktopParseError> {
+    // Sandboxes such as Flatpak may pass an empty XDG_CURRENT_DESKTOP; treat
+    // it like an unknown desktop instead of one that matches nothing.
+    let current_desktops = current_desktops.filter(|desktops| !desktops.is_empty());
+    let file = fs::File::open(path).map_err(DesktopParseError::Io)?;
+    let modified = file_modified(path, &file);
     let mut reader = BufReader::new(file);
 
-    let mut in_entry = false;
+    // The whole file is read and keys are routed by group, so the
+    // `[Desktop Entry]` group is found even when other groups come first.
+    let mut group = Group::Other;
+    let mut actions: Vec<DesktopAction> = Vec::new();
+    let mut localized_action_names: Vec<bool> = Vec::new();
+    let mut action_ids: Vec<String> = Vec::new();
     let mut name: Option<String> = None;
     let mut localized_name: Option<String> = None;
+    let mut generic_name: Option<String> = None;
+    let mut localized_generic_name: Option<String> = None;
+    let mut comment: Option<String> = None;
+    let mut localized_comment: Option<String> = None;
     let mut exec: Option<String> = None;
+    let mut try_exec: Option<String> = None;
     let mut categories: Option<String> = None;
+    let mut keywords = String::new();
+    let mut icon: Option<String> = None;
+    let mut localized_icon: Option<String> = None;
+    let mut terminal = false;
+    let mut working_dir: Option<String> = None;
+    let mut startup_notify = false;
+    let mut startup_wm_class: Option<String> = None;
+    let mut dbus_activatable = false;
+    let mut prefers_non_default_gpu = false;
+    let mut no_display = false;
+    let mut hidden = false;
+    let mut version: Option<String> = None;
+    let mut implements = Vec::new();
+    let mut uses_notifications = false;
+    // The spec makes OnlyShowIn and NotShowIn mutually exclusive; a file
+    // with both is hidden rather than guessing which one wins.
+    let mut has_only_show_in = false;
+    let mut has_not_show_in = false;
     let mut is_application = false;
+    let mut first_line = true;
 
     loop {
-        line_buf.clear();
-        match reader.read_line(&mut *line_buf) {
-            Ok(0) => break,
-            Ok(_) => {}
-            Err(_) => break,
-        }
-
-        let line = line_buf.trim();
-        if line.is_empty() {
-            continue;
-        }
-
-        let first_byte = line.as_bytes()[0];
-        if first_byte == b'#' {
-            continue;
-        }
-
-        if first_byte == b'[' && line.ends_with(']') {
-            if in_entry {
-                break;
+        let (key, value) = match read_entry_line(&mut reader, line_buf, &mut first_line) {
+            None => break,
+            Some(EntryLine::Skip) => continue,
+            Some(EntryLine::Key(key, value)) => (key, value),
+            Some(EntryLine::Group(line)) => {
+                group = if line == "[Desktop Entry]" {
+                    Group::Entry
+                } else if let Some(id) = line
+                    .strip_prefix("[Desktop Action ")
+                    .and_then(|rest| rest.strip_suffix(']'))
+                {
+                    actions.push(DesktopAction {
+                        id: id.to_string(),
+                        ..Default::default()
+                    });
+                    localized_action_names.push(false);
+                    Group::Action(actions.len() - 1)
+                } else {
+                    Group::Other
+                };
+                continue;
             }
-            in_entry = line == "[Desktop Entry]";
-            continue;
-        }
-        if !in_entry {
-            continue;
-        }
-
-        let eq_idx = match line.find('=') {
-            Some(idx) => idx,
-            None => continue,
         };
-
-        let key = &line[..eq_idx];
-        if key.is_empty() {
-            continue;
+        match group {
+            Group::Entry => {}
+            Group::Action(index) => {
+                let action = &mut actions[index];
+                if key == "Exec" {
+                    action.exec = value.to_string();
+                } else if key == "Name" && !localized_action_names[index] {
+                    action.name = value.to_string();
+                } else if let Some(tag) =
+                    key.strip_prefix("Name[").and_then(|k| k.strip_suffix(']'))
+                {
+                    if current_lang.is_some_and(|lang| matches_lang_tag(tag, lang)) {
+                        action.name = value.to_string();
+                        localized_action_names[index] = true;
+                    }
+                }
+                continue;
+            }
+            Group::Other => continue,
         }
-
-        let value = line[eq_idx + 1..].trim();
         match key.as_bytes()[0] {
             b'N' => {
                 if key == "Name" {
                     name = Some(value.to_string());
                 } else if key == "NoDisplay" {
-                    if parse_bool(value) {
-                        return None;
+                    no_display = parse_bool(value);
+                    if no_display && !keep_hidden {
+                        return Err(DesktopParseError::NoDisplay);
                     }
                 } else if key == "NotShowIn" {
+                    has_not_show_in = true;
+                    if has_only_show_in {
+                        return Err(both_show_in_keys(path));
+                    }
                     if let Some(current_desktops) = current_desktops {
                         if desktop_list_matches(value, current_desktops) {
-                            return None;
+                            return Err(DesktopParseError::NotShownIn);
                         }
                     }
                 } else if let Some(tag) =
@@ -257,69 +727,277 @@ pub fn parse_desktop_entry(
                     }
                 }
             }
-            b'E' => {
-                if key == "Exec" {
-                    exec = Some(value.to_string());
+            b'A' if key == "Actions" => {
+                action_ids = value
+                    .split(';')
+                    .map(str::trim)
+                    .filter(|id| !id.is_empty())
+                    .map(str::to_string)
+                    .collect();
+            }
+            b'G' => {
+                if key == "GenericName" {
+                    generic_name = Some(value.to_string());
+                } else if let Some(tag) = key
+                    .strip_prefix("GenericName[")
+                    .and_then(|k| k.strip_suffix(']'))
+                {
+                    if current_lang.is_some_and(|lang| matches_lang_tag(tag, lang)) {
+                        localized_generic_name = Some(value.to_string());
+                    }
                 }
             }
+            b'E' if key == "Exec" => {
+                exec = Some(value.to_string());
+            }
             b'C' => {
                 if key == "Categories" {
                     // Store raw string to avoid vector allocation
                     categories = Some(value.to_string());
+                } else if key == "Comment" {
+                    comment = Some(value.to_string());
+                } else if let Some(tag) = key
+                    .strip_prefix("Comment[")
+                    .and_then(|k| k.strip_suffix(']'))
+                {
+                    if current_lang.is_some_and(|lang| matches_lang_tag(tag, lang)) {
+                        localized_comment = Some(value.to_string());
+                    }
+                }
+            }
+            b'T' if key == "Type" => {
+                if value != "Application" {
+                    return Err(DesktopParseError::NotApplication);
                 }
+                is_application = true;
+            }
+            b'T' if key == "TryExec" => {
+                try_exec = Some(value.to_string());
+            }
+            b'T' if key == "Terminal" => {
+                terminal = parse_bool(value);
+            }
+            b'I' if key == "Icon" => {
+                icon = Some(value.to_string());
             }
-            b'T' => {
-                if key == "Type" {
-                    if value != "Application" {
-                        return None;
+            b'I' if key == "Implements" => {
+                implements = value
+                    .split(';')
+                    .map(str::trim)
+                    .filter(|interface| !interface.is_empty())
+                    .map(str::to_string)
+                    .collect();
+            }
+            b'I' => {
+                if let Some(tag) = key.strip_prefix("Icon[").and_then(|k| k.strip_suffix(']')) {
+                    if current_lang.is_some_and(|lang| matches_lang_tag(tag, lang)) {
+                        localized_icon = Some(value.to_string());
                     }
-                    is_application = true;
                 }
             }
-            b'H' => {
-                if key == "Hidden" && parse_bool(value) {
-                    return None;
+            b'V' if key == "Version" => {
+                version = Some(value.to_string());
+            }
+            b'D' if key == "DBusActivatable" => {
+                dbus_activatable = parse_bool(value);
+            }
+            b'P' if key == "Path" => {
+                working_dir = Some(value.to_string());
+            }
+            b'P' if key == "PrefersNonDefaultGPU" => {
+                prefers_non_default_gpu = parse_bool(value);
+            }
+            b'H' if key == "Hidden" => {
+                hidden = parse_bool(value);
+                if hidden && !keep_hidden {
+                    return Err(DesktopParseError::Hidden);
                 }
             }
-            b'O' => {
-                if key == "OnlyShowIn" {
-                    if let Some(current_desktops) = current_desktops {
-                        if !desktop_list_matches(value, current_desktops) {
-                            return None;
-                        }
+            b'O' if key == "OnlyShowIn" => {
+                has_only_show_in = true;
+                if has_not_show_in {
+                    return Err(both_show_in_keys(path));
+                }
+                if let Some(current_desktops) = current_desktops {
+                    if !desktop_list_matches(value, current_desktops) {
+                        return Err(DesktopParseError::NotShownIn);
                     }
                 }
             }
+            b'X' if key == "X-GNOME-UsesNotifications" => {
+                uses_notifications = parse_bool(value);
+            }
+            b'K' | b'X' if is_keywords_key(key, current_lang) => {
+                push_keywords(&mut keywords, value);
+            }
+            b'S' => {
+                if key == "StartupNotify" {
+                    startup_notify = parse_bool(value);
+                } else if key == "StartupWMClass" {
+                    startup_wm_class = Some(value.to_string());
+                }
+            }
             _ => {}
         }
     }
 
     if !is_application {
-        return None;
+        return Err(DesktopParseError::NotApplication);
     }
 
-    // Exec is required. If not found, return None.
-    let exec = exec?;
-
-    if !exec_looks_valid(&exec) {
-        return None;
-    }
+    // Exec is required; parse_desktop_entry rejects a missing or invalid one.
+    let exec = exec.unwrap_or_default();
 
-    let name = localized_name.or(name).or_else(|| {
+    let base_name = name.clone().filter(|base| {
+        localized_name
+            .as_ref()
+            .is_some_and(|localized| localized != base)
+    });
+    let name = localized_name.or(name).unwrap_or_else(|| {
         path.file_stem()
             .and_then(|stem| stem.to_str())
-            .map(|stem| stem.to_string())
-    })?;
+            .map(fallback_name)
+            .unwrap_or_default()
+    });
 
-    Some(DesktopEntry {
+    // Only actions the `Actions` key lists are offered, in its order.
+    let actions = action_ids
+        .iter()
+        .filter_map(|id| actions.iter().find(|action| &action.id == id).cloned())
+        .collect();
+
+    let id = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or_default()
+        .to_string();
+
+    Ok(DesktopEntry {
+        id,
         name,
+        generic_name: localized_generic_name
+            .or(generic_name)
+            .filter(|generic_name| !generic_name.is_empty()),
+        comment: localized_comment
+            .or(comment)
+            .filter(|comment| !comment.is_empty()),
         exec,
+        try_exec,
         categories: categories.unwrap_or_default(),
+        keywords,
         path: path.to_path_buf(),
+        icon: localized_icon.or(icon),
+        terminal,
+        working_dir,
+        startup_notify,
+        startup_wm_class,
+        dbus_activatable,
+        prefers_non_default_gpu,
+        no_display,
+        hidden,
+        version,
+        implements,
+        uses_notifications,
+        actions,
+        original_name: None,
+        base_name,
+        alternates: Vec::new(),
+        missing_program: None,
+        modified,
+        recently_installed: false,
+        appimage: false,
+        name_suffix: None,
     })
 }
 
+/// Returns the later of the file's and, for a symlink, the link's own
+/// modification time. Flatpak and Nix install apps as symlinks to files
+/// with old or zeroed timestamps, so the link tells when they arrived.
+fn file_modified(path: &Path, file: &fs::File) -> Option<SystemTime> {
+    let target = file
+        .metadata()
+        .and_then(|metadata| metadata.modified())
+        .ok();
+    let link = fs::symlink_metadata(path)
+        .ok()
+        .filter(|metadata| metadata.file_type().is_symlink())
+        .and_then(|metadata| metadata.modified().ok());
+    target.max(link)
+}
+
+/// Newest desktop entry specification version the parser fully supports.
+pub const SUPPORTED_SPEC_VERSION: &str = "1.5";
+
+/// Whether a `Version` value is one the parser understands: a
+/// `major.minor` version no newer than [`SUPPORTED_SPEC_VERSION`].
+pub fn spec_version_supported(version: &str) -> bool {
+    fn parse(version: &str) -> Option<(u32, u32)> {
+        let (major, minor) = version.trim().split_once('.')?;
+        Some((major.parse().ok()?, minor.parse().ok()?))
+    }
+    match (parse(version), parse(SUPPORTED_SPEC_VERSION)) {
+        (Some(declared), Some(supported)) => declared <= supported,
+        _ => false,
+    }
+}
+
+/// Memoizes program lookups for one scan, so an Exec program shared by many
+/// entries (such as a wrapper script) costs one filesystem check. A new
+/// cache per scan keeps refreshes from seeing stale results.
+#[derive(Default)]
+struct ProgramCache {
+    exists: HashMap<String, bool>,
+    in_path: HashMap<String, bool>,
+}
+
+impl ProgramCache {
+    fn path_exists(&mut self, path: &str) -> bool {
+        if let Some(&exists) = self.exists.get(path) {
+            return exists;
+        }
+        let exists = Path::new(path).exists();
+        self.exists.insert(path.to_string(), exists);
+        exists
+    }
+
+    fn is_in_path(&mut self, program: &str) -> bool {
+        if let Some(&found) = self.in_path.get(program) {
+            return found;
+        }
+        let found = find_in_path(program).is_some();
+        self.in_path.insert(program.to_string(), found);
+        found
+    }
+}
+
+/// Display name for a file without `Name`, from its file stem. A
+/// reverse-DNS ID such as `org.mozilla.firefox` becomes "Firefox"; other
+/// stems are used as they are.
+pub fn fallback_name(stem: &str) -> String {
+    let parts: Vec<&str> = stem.split('.').collect();
+    let last = parts.last().copied().unwrap_or_default();
+    if parts.len() < 3 || parts.iter().any(|part| part.is_empty()) {
+        return stem.to_string();
+    }
+    let mut chars = last.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => stem.to_string(),
+    }
+}
+
+/// Characters the Desktop Entry spec reserves in `Exec`: an argument that
+/// holds one must be quoted. Whitespace is left out because it separates
+/// arguments either way.
+const EXEC_RESERVED_CHARS: [char; 16] = [
+    '"', '\'', '\\', '>', '<', '~', '|', '&', ';', '$', '*', '?', '#', '(', ')', '`',
+];
+
 pub fn exec_looks_valid(exec: &str) -> bool {
+    exec_looks_valid_cached(exec, &mut ProgramCache::default())
+}
+
+fn exec_looks_valid_cached(exec: &str, cache: &mut ProgramCache) -> bool {
     let exec = exec.trim();
     if exec.is_empty() {
         return false;
@@ -327,61 +1005,390 @@ pub fn exec_looks_valid(exec: &str) -> bool {
 
     // Optimization: avoid glib parse/allocation for common cases.
     // Most Exec lines are simple commands or absolute paths without quotes.
-    if !exec.contains(['"', '\'', '\\']) {
-        let command = exec.split_whitespace().next().unwrap_or("");
-        if command.starts_with('/') {
-            return Path::new(command).exists();
-        } else {
-            return true;
-        }
+    // Reserved characters, quoted or not, go through the shell parser.
+    if !exec.contains(EXEC_RESERVED_CHARS) {
+        let args: Vec<&str> = exec.split_whitespace().collect();
+        return command_looks_valid(&args, cache);
     }
 
-    let argv = match glib::shell_parse_argv(exec) {
-        Ok(argv) => argv,
-        Err(_) => return true,
-    };
-    let Some(command) = argv.first().and_then(|arg| arg.to_str()) else {
-        return true;
+    // GIO splits Exec the same way to launch it, so a line the parser
+    // rejects (an unclosed quote, or nothing but a comment) cannot run.
+    let Ok(argv) = glib::shell_parse_argv(exec) else {
+        return false;
     };
+    let args: Vec<&str> = argv.iter().filter_map(|arg| arg.to_str()).collect();
+    command_looks_valid(&args, cache)
+}
+
+/// Checks the program an Exec argv would run.
+///
+/// An `env` prefix is looked through: its `KEY=VALUE` assignments and flags
+/// are skipped and the following program is checked instead (a bare
+/// `env FOO=bar` is checked as `env` itself). `flatpak run <app-id>` is valid
+/// when the `flatpak` binary exists and an app ID follows. Any other
+/// absolute program must exist; relative programs are accepted.
+fn command_looks_valid(args: &[&str], cache: &mut ProgramCache) -> bool {
+    if args.is_empty() {
+        return false;
+    }
+    let args = skip_env_prefix(args);
+    let command = args[0];
+    if program_name(command) == "flatpak" && args.get(1) == Some(&"run") {
+        let has_app_id = args[2..].iter().any(|arg| !arg.starts_with('-'));
+        return has_app_id && cache.is_in_path(command);
+    }
+
     if command.starts_with('/') {
-        Path::new(command).exists()
+        cache.path_exists(command)
     } else {
         true
     }
 }
 
-fn cmp_ignore_ascii_case(a: &str, b: &str) -> std::cmp::Ordering {
-    let a_bytes = a.as_bytes();
-    let b_bytes = b.as_bytes();
-    let len = a_bytes.len().min(b_bytes.len());
+/// Drops an `env` prefix with its assignments and flags from a non-empty
+/// argv, unless nothing follows them.
+fn skip_env_prefix<'a, 'b>(args: &'a [&'b str]) -> &'a [&'b str] {
+    if program_name(args[0]) != "env" {
+        return args;
+    }
+    let rest = &args[1..];
+    let skip = rest
+        .iter()
+        .take_while(|arg| arg.contains('=') || arg.starts_with('-'))
+        .count();
+    if skip < rest.len() {
+        &rest[skip..]
+    } else {
+        args
+    }
+}
+
+/// Returns the program an Exec line runs, looking through an `env` prefix
+/// as [`exec_looks_valid`] does.
+pub fn exec_program(exec: &str) -> Option<String> {
+    let argv: Vec<String> = match glib::shell_parse_argv(exec.trim()) {
+        Ok(argv) => argv
+            .iter()
+            .filter_map(|arg| arg.to_str().map(str::to_string))
+            .collect(),
+        Err(_) => exec.split_whitespace().map(str::to_string).collect(),
+    };
+    let args: Vec<&str> = argv.iter().map(String::as_str).collect();
+    if args.is_empty() {
+        return None;
+    }
+    Some(skip_env_prefix(&args)[0].to_string())
+}
+
+fn program_name(command: &str) -> &str {
+    command.rsplit('/').next().unwrap_or(command)
+}
+
+/// Sorts `entries` by name with the locale's collation (`g_utf8_collate`),
+/// ignoring case, so "Éditeur" sorts among the E's in a French locale.
+/// Names the collation treats as equal fall back to ASCII case-insensitive
+/// order. Before GTK sets the locale (and under `C`) this is code point order
+/// of the lowercased names.
+pub fn sort_by_name(entries: &mut [DesktopEntry]) {
+    entries.sort_by_cached_key(|entry| {
+        (
+            glib::CollationKey::from(entry.name.to_lowercase()),
+            entry.name.to_ascii_lowercase(),
+        )
+    });
+}
+
+/// Scans the standard desktop directories, or only `dirs` when given (the
+/// `--entries-dir` override, which skips the built-in directories).
+///
+/// `config` supplies the scan settings: `entries_cache`, `excluded_subdirs`,
+/// and `appimage_dirs`. The user's blocklist and display-name overrides are
+/// left to the caller ([`remove_blocklisted`], [`apply_name_overrides`]).
+pub fn collect_desktop_entries(dirs: Option<&[PathBuf]>, config: &Config) -> Vec<DesktopEntry> {
+    collect_desktop_entries_with_report(dirs, config).0
+}
+
+/// Like [`collect_desktop_entries`], also reporting what the scan skipped.
+pub fn collect_desktop_entries_with_report(
+    dirs: Option<&[PathBuf]>,
+    config: &Config,
+) -> (Vec<DesktopEntry>, ScanReport) {
+    let dirs = scan_dirs(dirs);
+    let (mut entries, report) = if config.entries_cache {
+        collect_with_cache(&dirs, &config.excluded_subdirs)
+    } else {
+        collect_desktop_entries_from_with_report(&dirs, &config.excluded_subdirs)
+    };
+    add_appimage_entries(&mut entries, &config.appimage_dirs);
+    (entries, report)
+}
+
+/// Guesses an app name from an AppImage file stem by dropping the version
+/// and architecture parts, e.g. `Krita-5.2.1-x86_64` becomes `Krita`.
+pub fn appimage_name(stem: &str) -> String {
+    const ARCHES: [&str; 9] = [
+        "x86", "x64", "amd64", "i386", "i686", "aarch64", "arm64", "armhf", "linux",
+    ];
+    let words: Vec<&str> = stem
+        .split(['-', '_'])
+        .take_while(|word| {
+            let version = word.starts_with(|c: char| c.is_ascii_digit())
+                || (word.starts_with(['v', 'V'])
+                    && word[1..].starts_with(|c: char| c.is_ascii_digit()));
+            !version && !ARCHES.iter().any(|arch| arch.eq_ignore_ascii_case(word))
+        })
+        .filter(|word| !word.is_empty())
+        .collect();
+    if words.is_empty() {
+        stem.to_string()
+    } else {
+        words.join(" ")
+    }
+}
+
+/// Synthesizes an entry for the AppImage at `path`, which ships no
+/// installed desktop file: named by [`appimage_name`], running the file
+/// itself, with no categories so it is listed under "Other". `None` unless
+/// `path` is an executable `*.AppImage` file.
+pub fn appimage_entry(path: &Path) -> Option<DesktopEntry> {
+    let is_appimage = path
+        .extension()
+        .and_then(OsStr::to_str)
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("AppImage"));
+    if !is_appimage || !is_executable_file(path) {
+        return None;
+    }
+    let stem = path.file_stem()?.to_str()?;
+    let path_text = path.to_str()?;
+    // Exec arguments go through environment variable expansion, which a
+    // `$` in the path would break.
+    if path_text.contains('$') {
+        log_debug!("Skipping AppImage {}: $ in its path", path.display());
+        return None;
+    }
+    let exec = glib::shell_quote(path_text.replace('%', "%%"))
+        .to_string_lossy()
+        .into_owned();
+    Some(DesktopEntry {
+        id: format!("appimage-{stem}.desktop"),
+        name: appimage_name(stem),
+        exec,
+        path: path.to_path_buf(),
+        modified: fs::File::open(path)
+            .ok()
+            .and_then(|file| file_modified(path, &file)),
+        appimage: true,
+        ..Default::default()
+    })
+}
 
-    for i in 0..len {
-        let c1 = a_bytes[i];
-        let c2 = b_bytes[i];
-        if c1 == c2 {
+/// Adds an entry from [`appimage_entry`] for each AppImage directly inside
+/// `dirs`, skipping IDs already listed. The result is sorted by name again.
+pub fn add_appimage_entries(entries: &mut Vec<DesktopEntry>, dirs: &[PathBuf]) {
+    let mut ids: HashSet<String> = entries.iter().map(|entry| entry.id.clone()).collect();
+    let mut added = false;
+    for dir in dirs {
+        let Ok(files) = fs::read_dir(dir) else {
+            log_debug!("Cannot read AppImage directory {}", dir.display());
             continue;
+        };
+        let mut paths: Vec<PathBuf> = files.flatten().map(|file| file.path()).collect();
+        paths.sort();
+        for entry in paths.iter().filter_map(|path| appimage_entry(path)) {
+            if ids.insert(entry.id.clone()) {
+                log_debug!("Listing AppImage {}", entry.path.display());
+                entries.push(entry);
+                added = true;
+            }
         }
-        match c1.to_ascii_lowercase().cmp(&c2.to_ascii_lowercase()) {
-            std::cmp::Ordering::Equal => continue,
-            ord => return ord,
+    }
+    if added {
+        sort_by_name(entries);
+    }
+}
+
+/// Adds the files in `broken` (see [`ScanReport::broken_exec`]) to
+/// `entries` with [`DesktopEntry::missing_program`] set, for the diagnostics
+/// view. IDs already listed keep their working entry, and files that no
+/// longer parse are left out. The result is sorted by name again; the
+/// blocklist is the caller's to apply afterwards.
+pub fn add_broken_entries(entries: &mut Vec<DesktopEntry>, broken: &[PathBuf]) {
+    if broken.is_empty() {
+        return;
+    }
+    let current_lang = env::var("LANG").ok();
+    let current_desktops = env::var("XDG_CURRENT_DESKTOP")
+        .ok()
+        .map(|value| parse_current_desktops(&value));
+    let mut ids: HashSet<String> = entries.iter().map(|entry| entry.id.clone()).collect();
+    let mut line_buf = String::new();
+    for path in broken {
+        let Ok(mut entry) = read_desktop_entry(
+            path,
+            current_lang.as_deref(),
+            current_desktops.as_deref(),
+            false,
+            &mut line_buf,
+        ) else {
+            continue;
+        };
+        if !ids.insert(entry.id.clone()) {
+            continue;
+        }
+        entry.missing_program = exec_program(&entry.exec);
+        entries.push(entry);
+    }
+    sort_by_name(entries);
+}
+
+/// Drops entries whose desktop ID is in `blocklist`, including merged
+/// alternates.
+pub fn remove_blocklisted(entries: &mut Vec<DesktopEntry>, blocklist: &BTreeSet<String>) {
+    if blocklist.is_empty() {
+        return;
+    }
+    let before = entries.len();
+    entries.retain(|entry| !blocklist.contains(&entry.id));
+    for entry in entries.iter_mut() {
+        entry
+            .alternates
+            .retain(|alternate| !blocklist.contains(&alternate.id));
+    }
+    log_info!("Hid {} blocklisted applications", before - entries.len());
+}
+
+/// Renames entries whose desktop ID has an override, keeping the parsed
+/// name in `original_name`, then re-sorts by the displayed name.
+pub fn apply_name_overrides(entries: &mut [DesktopEntry], overrides: &BTreeMap<String, String>) {
+    if overrides.is_empty() {
+        return;
+    }
+    for entry in entries.iter_mut() {
+        let Some(name) = overrides.get(&entry.id) else {
+            continue;
+        };
+        if name.is_empty() || *name == entry.name {
+            continue;
         }
+        let original = std::mem::replace(&mut entry.name, name.clone());
+        entry.original_name = Some(original);
     }
-    a_bytes.len().cmp(&b_bytes.len())
+    sort_by_name(entries);
+}
+
+/// Counts from one scan: how many desktop files were looked at, why the
+/// ones that did not become entries were skipped, and which directories
+/// could not be read.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ScanReport {
+    pub files_seen: usize,
+    pub no_display: usize,
+    pub hidden: usize,
+    /// `Type` is missing or not `Application`.
+    pub not_application: usize,
+    /// Filtered out by `OnlyShowIn`/`NotShowIn`.
+    pub not_shown_in: usize,
+    /// Exec is missing or cannot run.
+    pub invalid_exec: usize,
+    /// Shadowed by an entry with the same ID earlier in search order.
+    pub duplicate: usize,
+    pub unreadable_files: usize,
+    pub unreadable_dirs: Vec<UnreadableDir>,
+    /// Files skipped because their Exec names a program that does not
+    /// exist, for [`add_broken_entries`].
+    pub broken_exec: Vec<PathBuf>,
 }
 
-pub fn collect_desktop_entries() -> Vec<DesktopEntry> {
+/// A directory the scan could not list, such as a mount with the wrong
+/// permissions, and the error that stopped it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UnreadableDir {
+    pub path: PathBuf,
+    pub reason: String,
+}
+
+impl ScanReport {
+    /// Number of desktop files that did not become entries.
+    pub fn skipped(&self) -> usize {
+        self.no_display
+            + self.hidden
+            + self.not_application
+            + self.not_shown_in
+            + self.invalid_exec
+            + self.duplicate
+            + self.unreadable_files
+    }
+
+    fn record_skip(&mut self, err: &DesktopParseError) {
+        let count = match err {
+            DesktopParseError::Io(_) => &mut self.unreadable_files,
+            DesktopParseError::NotApplication => &mut self.not_application,
+            DesktopParseError::NoDisplay => &mut self.no_display,
+            DesktopParseError::Hidden => &mut self.hidden,
+            DesktopParseError::NotShownIn => &mut self.not_shown_in,
+            DesktopParseError::MissingExec | DesktopParseError::InvalidExec(_) => {
+                &mut self.invalid_exec
+            }
+        };
+        *count += 1;
+    }
+}
+
+impl fmt::Display for ScanReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} desktop files seen, {} skipped (NoDisplay {}, Hidden {}, not an application {}, \
+             not shown in this desktop {}, invalid Exec {}, duplicate {}, unreadable {}), \
+             {} unreadable directories",
+            self.files_seen,
+            self.skipped(),
+            self.no_display,
+            self.hidden,
+            self.not_application,
+            self.not_shown_in,
+            self.invalid_exec,
+            self.duplicate,
+            self.unreadable_files,
+            self.unreadable_dirs.len()
+        )
+    }
+}
+
+/// Scans `dirs` in order and returns the application entries, sorted by name.
+///
+/// When several directories provide the same desktop ID, the first one in
+/// search order wins, as the Desktop Entry spec requires. That includes a
+/// `Hidden=true` or `NoDisplay=true` copy, which hides the ID entirely. The
+/// one exception is an entry whose Exec fails [`exec_looks_valid`]: it is
+/// ignored and the next directory's copy of the ID is used instead.
+///
+/// Subdirectories named in `excluded_subdirs` or marked hidden are skipped.
+pub fn collect_desktop_entries_from(
+    dirs: &[PathBuf],
+    excluded_subdirs: &[String],
+) -> Vec<DesktopEntry> {
+    collect_desktop_entries_from_with_report(dirs, excluded_subdirs).0
+}
+
+/// Like [`collect_desktop_entries_from`], also returning a [`ScanReport`].
+pub fn collect_desktop_entries_from_with_report(
+    dirs: &[PathBuf],
+    excluded_subdirs: &[String],
+) -> (Vec<DesktopEntry>, ScanReport) {
     let current_lang = env::var("LANG").ok();
-    let current_desktops = env::var("XDG_CURRENT_DESKTOP").ok().map(|value| {
-        value
-            .split(':')
-            .filter(|entry| !entry.is_empty())
-            .map(|entry| entry.to_string())
-            .collect::<Vec<_>>()
-    });
+    let current_desktops = env::var("XDG_CURRENT_DESKTOP")
+        .ok()
+        .map(|value| parse_current_desktops(&value));
 
     let mut entries = Vec::new();
     let mut seen_ids = HashSet::new();
     let mut line_buf = String::new();
+    let mut report = ScanReport::default();
+    let mut unreadable_dirs = Vec::new();
+    let mut programs = ProgramCache::default();
+    let scan_timer = start_timer();
+    let mut parse_time = std::time::Duration::ZERO;
 
     let mut cb = |path: PathBuf| {
         let id_str = match path.file_name().and_then(|name| name.to_str()) {
@@ -392,35 +1399,691 @@ pub fn collect_desktop_entries() -> Vec<DesktopEntry> {
         if id_str == "access-launcher.desktop" {
             return;
         }
+        report.files_seen += 1;
 
         if seen_ids.contains(id_str) {
+            report.duplicate += 1;
+            log_debug!(
+                "Skipping {}: shadowed by an earlier {id_str}",
+                path.display()
+            );
             return;
         }
-        seen_ids.insert(id_str.to_string());
 
-        if let Some(entry) = parse_desktop_entry(
+        let parse_timer = start_timer();
+        let entry = read_desktop_entry(
             &path,
             current_lang.as_deref(),
             current_desktops.as_deref(),
+            false,
             &mut line_buf,
-        ) {
-            // exec_looks_valid is now checked inside parse_desktop_entry
-            entries.push(entry);
+        )
+        .and_then(|entry| validate_exec(&entry, &mut programs).map(|()| entry));
+        if let Some(timer) = parse_timer {
+            parse_time += timer.elapsed();
+        }
+        match entry {
+            Ok(entry) => {
+                if let Some(version) = entry
+                    .version
+                    .as_deref()
+                    .filter(|version| !spec_version_supported(version))
+                {
+                    log_info!(
+                        "{} declares desktop entry spec Version={version}; \
+                         only up to {SUPPORTED_SPEC_VERSION} is fully supported",
+                        path.display()
+                    );
+                }
+                seen_ids.insert(id_str.to_string());
+                entries.push(entry);
+            }
+            // A broken Exec does not claim the ID, so a later directory can
+            // still provide a working copy.
+            Err(err) if !err.claims_id() => {
+                log_debug!("Skipping {}: {err}", path.display());
+                report.record_skip(&err);
+                if matches!(err, DesktopParseError::InvalidExec(_)) {
+                    report.broken_exec.push(path);
+                }
+            }
+            Err(err) => {
+                log_debug!("Skipping {}: {err}", path.display());
+                report.record_skip(&err);
+                seen_ids.insert(id_str.to_string());
+            }
         }
     };
 
-    for dir in desktop_dirs() {
-        walk_desktop_files(&dir, &mut cb);
+    for dir in dirs {
+        if dir.is_dir() {
+            log_info!("Scanning {}", dir.display());
+            walk_desktop_files(
+                dir,
+                excluded_subdirs,
+                &mut HashSet::new(),
+                &mut unreadable_dirs,
+                &mut cb,
+            );
+        } else {
+            log_debug!("Skipping missing directory {}", dir.display());
+        }
+    }
+
+    report.unreadable_dirs = unreadable_dirs;
+
+    log_info!("Found {} applications", entries.len());
+    log_info!("Scan report: {report}");
+    if let Some(timer) = scan_timer {
+        let total = timer.elapsed();
+        log_info!(
+            "Timing: scanned {} desktop files into {} entries in {} \
+             (walking directories {}, parsing {})",
+            report.files_seen,
+            entries.len(),
+            format_duration(total),
+            format_duration(total.saturating_sub(parse_time)),
+            format_duration(parse_time)
+        );
+    }
+    sort_by_name(&mut entries);
+    (entries, report)
+}
+
+/// Returns true for entries exported by Flatpak, detected by a Flatpak
+/// export directory (such as `/var/lib/flatpak/exports`) or a
+/// `flatpak run` Exec line.
+pub fn is_flatpak_entry(entry: &DesktopEntry) -> bool {
+    if entry.path.to_string_lossy().contains("/flatpak/exports/") {
+        return true;
+    }
+    let mut args = entry.exec.split_whitespace();
+    let program = args.next().map(program_name);
+    program == Some("flatpak") && args.next() == Some("run")
+}
+
+/// Names the packaging of `entry` for menus, e.g. "Launch Flatpak version".
+pub fn variant_label(entry: &DesktopEntry) -> &'static str {
+    if is_flatpak_entry(entry) {
+        "Flatpak"
+    } else {
+        "system"
+    }
+}
+
+/// Names where `entry` was installed from, to tell apart apps with the same
+/// name: "AppImage", "Flatpak", "Snap", "user" for the user's own
+/// applications directory, or else "system".
+pub fn source_label(entry: &DesktopEntry) -> &'static str {
+    if entry.appimage {
+        "AppImage"
+    } else if is_flatpak_entry(entry) {
+        "Flatpak"
+    } else if entry.path.to_string_lossy().contains("/snapd/") {
+        "Snap"
+    } else if data_home().is_some_and(|home| entry.path.starts_with(home)) {
+        "user"
+    } else {
+        "system"
     }
+}
 
-    entries.sort_by(|a, b| cmp_ignore_ascii_case(&a.name, &b.name));
+/// Returns whether `candidate` should be folded into `primary`: the names
+/// match case-insensitively and exactly one of them is a Flatpak.
+///
+/// Two system packages or two Flatpaks with the same name are kept apart,
+/// since those are usually distinct apps.
+pub fn should_merge_duplicate(primary: &DesktopEntry, candidate: &DesktopEntry) -> bool {
+    primary.alternates.is_empty()
+        && primary.name.to_lowercase() == candidate.name.to_lowercase()
+        && is_flatpak_entry(primary) != is_flatpak_entry(candidate)
+}
+
+/// Folds Flatpak/system duplicates into one entry, keeping the system
+/// version as the primary and the other in `alternates`. Order is preserved.
+pub fn merge_duplicate_entries(entries: Vec<DesktopEntry>) -> Vec<DesktopEntry> {
+    let mut merged: Vec<DesktopEntry> = Vec::with_capacity(entries.len());
+    let mut by_name: HashMap<String, usize> = HashMap::new();
+    for entry in entries {
+        let key = entry.name.to_lowercase();
+        match by_name.get(&key) {
+            Some(&index) if should_merge_duplicate(&merged[index], &entry) => {
+                let primary = &mut merged[index];
+                if is_flatpak_entry(primary) {
+                    let flatpak = std::mem::replace(primary, entry);
+                    primary.alternates.push(flatpak);
+                } else {
+                    primary.alternates.push(entry);
+                }
+            }
+            Some(_) => merged.push(entry),
+            None => {
+                by_name.insert(key, merged.len());
+                merged.push(entry);
+            }
+        }
+    }
+    merged
+}
+
+/// Drops entries whose desktop file resolves, through symlinks, to the same
+/// file as an earlier entry, so one file linked under several IDs is listed
+/// once. The entry read from the real file wins over its symlinks; files
+/// that cannot be resolved are kept. Order is preserved.
+pub fn dedup_symlinked_entries(entries: Vec<DesktopEntry>) -> Vec<DesktopEntry> {
+    let targets: Vec<Option<PathBuf>> = entries
+        .iter()
+        .map(|entry| fs::canonicalize(&entry.path).ok())
+        .collect();
+    let mut keep: HashMap<&Path, usize> = HashMap::new();
+    for (index, (entry, target)) in entries.iter().zip(&targets).enumerate() {
+        let Some(target) = target else {
+            continue;
+        };
+        let is_real = entry.path == *target;
+        keep.entry(target)
+            .and_modify(|kept| {
+                if is_real && entries[*kept].path != *target {
+                    *kept = index;
+                }
+            })
+            .or_insert(index);
+    }
     entries
+        .into_iter()
+        .zip(&targets)
+        .enumerate()
+        .filter(|(index, (entry, target))| match target {
+            Some(target) if keep[target.as_path()] != *index => {
+                log_debug!(
+                    "Skipping {}: same file as another entry ({})",
+                    entry.path.display(),
+                    target.display()
+                );
+                false
+            }
+            _ => true,
+        })
+        .map(|(_, (entry, _))| entry)
+        .collect()
+}
+
+/// Pseudo-category listing recently installed apps.
+pub const NEW_APPS_CATEGORY: &str = "New";
+
+/// Sets [`DesktopEntry::recently_installed`] on entries whose file changed
+/// within `days` days before `now`. A `days` of zero marks nothing. Files
+/// dated in the future count as new.
+pub fn mark_recently_installed(entries: &mut [DesktopEntry], now: SystemTime, days: u32) {
+    let window = Duration::from_secs(u64::from(days) * 24 * 60 * 60);
+    for entry in entries {
+        entry.recently_installed = days > 0
+            && entry.modified.is_some_and(|modified| {
+                now.duration_since(modified)
+                    .map_or(true, |age| age <= window)
+            });
+    }
+}
+
+/// Sets [`DesktopEntry::name_suffix`] on entries whose name another entry
+/// shares, ignoring case: their [`source_label`], or their desktop ID when
+/// that label is shared too. Returns each duplicated name once, sorted.
+pub fn mark_duplicate_names(entries: &mut [DesktopEntry]) -> Vec<String> {
+    let mut groups: BTreeMap<String, Vec<usize>> = BTreeMap::new();
+    for (index, entry) in entries.iter_mut().enumerate() {
+        entry.name_suffix = None;
+        groups
+            .entry(entry.name.to_lowercase())
+            .or_default()
+            .push(index);
+    }
+    let mut names = Vec::new();
+    for indices in groups.values().filter(|indices| indices.len() > 1) {
+        let labels: Vec<&str> = indices
+            .iter()
+            .map(|&index| source_label(&entries[index]))
+            .collect();
+        for (&index, label) in indices.iter().zip(&labels) {
+            let entry = &mut entries[index];
+            let suffix = if labels.iter().filter(|other| *other == label).count() == 1 {
+                label.to_string()
+            } else {
+                entry.id.trim_end_matches(".desktop").to_string()
+            };
+            entry.name_suffix = Some(suffix);
+        }
+        names.push(entries[indices[0]].name.clone());
+    }
+    names
+}
+
+/// Indices of the entries marked recently installed, in order.
+pub fn new_app_indices(entries: &[DesktopEntry]) -> Vec<usize> {
+    entries
+        .iter()
+        .enumerate()
+        .filter(|(_, entry)| entry.recently_installed)
+        .map(|(index, _)| index)
+        .collect()
+}
+
+/// Pseudo-category listing only `Terminal=true` apps.
+pub const TERMINAL_APPS_CATEGORY: &str = "Terminal Apps";
+
+/// Drops `Terminal=true` entries when `hide` is set; otherwise returns
+/// `entries` unchanged.
+pub fn filter_terminal_apps(mut entries: Vec<DesktopEntry>, hide: bool) -> Vec<DesktopEntry> {
+    if hide {
+        entries.retain(|entry| !entry.terminal);
+    }
+    entries
+}
+
+/// Indices of the `Terminal=true` entries, in order.
+pub fn terminal_app_indices(entries: &[DesktopEntry]) -> Vec<usize> {
+    entries
+        .iter()
+        .enumerate()
+        .filter(|(_, entry)| entry.terminal)
+        .map(|(index, _)| index)
+        .collect()
+}
+
+/// Category buckets in the order they appear in the sidebar.
+pub const CATEGORIES: [&str; 12] = [
+    "Accessories",
+    "Audio/Video",
+    "Development",
+    "Games",
+    "Graphics",
+    "Text Editors",
+    "Internet",
+    "Office",
+    "System",
+    "Terminal Emulator",
+    "Utilities",
+    "Other",
+];
+
+/// Returns the sidebar categories, dropping empty buckets unless `show_empty`.
+pub fn visible_categories(
+    category_map: &BTreeMap<String, Vec<usize>>,
+    show_empty: bool,
+) -> Vec<&'static str> {
+    CATEGORIES
+        .iter()
+        .copied()
+        .filter(|category| {
+            show_empty
+                || category_map
+                    .get(*category)
+                    .is_some_and(|entries| !entries.is_empty())
+        })
+        .collect()
+}
+
+/// Moves the `pinned` categories present in `buckets` to the front, in
+/// pinned order, keeping the order of the rest.
+pub fn pin_categories(buckets: &mut Vec<&'static str>, pinned: &[String]) {
+    let mut front: Vec<&'static str> = pinned
+        .iter()
+        .filter_map(|pin| buckets.iter().copied().find(|bucket| bucket == pin))
+        .collect();
+    buckets.retain(|bucket| !front.contains(bucket));
+    front.append(buckets);
+    *buckets = front;
+}
+
+/// Group label for entries that list no categories at all.
+pub const UNCATEGORIZED_GROUP: &str = "Uncategorized";
+
+/// Subdivides `programs` by the raw `Categories` tokens their entries carry.
+///
+/// An entry appears under each distinct token it lists, so an app tagged
+/// `Science;Astronomy;` shows up in both groups. Groups are sorted by name,
+/// with [`UNCATEGORIZED_GROUP`] last; entries keep the order of `programs`.
+pub fn group_by_raw_category(
+    entries: &[DesktopEntry],
+    programs: &[usize],
+) -> Vec<(String, Vec<usize>)> {
+    let mut groups: BTreeMap<&str, Vec<usize>> = BTreeMap::new();
+    let mut uncategorized = Vec::new();
+    for &index in programs {
+        let Some(entry) = entries.get(index) else {
+            continue;
+        };
+        let mut tokens: Vec<&str> = entry
+            .categories
+            .split(';')
+            .map(str::trim)
+            .filter(|token| !token.is_empty())
+            .collect();
+        tokens.dedup();
+        if tokens.is_empty() {
+            uncategorized.push(index);
+        }
+        for token in tokens {
+            let group = groups.entry(token).or_default();
+            if group.last() != Some(&index) {
+                group.push(index);
+            }
+        }
+    }
+
+    let mut grouped: Vec<(String, Vec<usize>)> = groups
+        .into_iter()
+        .map(|(token, programs)| (token.to_string(), programs))
+        .collect();
+    if !uncategorized.is_empty() {
+        grouped.push((UNCATEGORIZED_GROUP.to_string(), uncategorized));
+    }
+    grouped
+}
+
+/// Labelled facts about `entry` for triaging launch problems: its ID, file,
+/// raw Exec and whether it validates, TryExec, the informational
+/// `Implements` and notification hints, and categories, bucketed with
+/// `rules` from `categories.conf`.
+pub fn entry_diagnostics(
+    entry: &DesktopEntry,
+    rules: &[CategoryRule],
+) -> Vec<(&'static str, String)> {
+    let exec_check = if exec_looks_valid(&entry.exec) {
+        "valid".to_string()
+    } else {
+        "cannot run".to_string()
+    };
+    let try_exec = match entry.try_exec.as_deref().filter(|value| !value.is_empty()) {
+        Some(program) if find_in_path(program).is_some() => format!("{program} (found)"),
+        Some(program) => format!("{program} (not found)"),
+        None => "not set".to_string(),
+    };
+    let version = match entry.version.as_deref() {
+        Some(version) if spec_version_supported(version) => version.to_string(),
+        Some(version) => format!("{version} (newer than supported {SUPPORTED_SPEC_VERSION})"),
+        None => "not set".to_string(),
+    };
+    let categories = if entry.categories.is_empty() {
+        "none".to_string()
+    } else {
+        entry.categories.clone()
+    };
+    let implements = if entry.implements.is_empty() {
+        "none".to_string()
+    } else {
+        entry.implements.join(", ")
+    };
+    let visibility = if entry.hidden {
+        "Hidden/deleted"
+    } else if entry.no_display {
+        "NoDisplay (kept out of menus)"
+    } else {
+        "shown"
+    };
+    let mut fields = vec![
+        ("Desktop ID", entry.id.clone()),
+        ("File", entry.path.display().to_string()),
+        ("Exec", entry.exec.clone()),
+        ("Exec check", exec_check),
+        ("TryExec", try_exec),
+        ("Spec version", version),
+        ("Visibility", visibility.to_string()),
+        ("Implements", implements),
+        (
+            "Uses notifications",
+            if entry.uses_notifications {
+                "yes"
+            } else {
+                "no"
+            }
+            .to_string(),
+        ),
+        ("Categories", categories),
+        (
+            "Listed under",
+            map_categories(&entry.categories, rules).to_string(),
+        ),
+    ];
+    if entry.appimage {
+        fields.insert(
+            2,
+            (
+                "Source",
+                "AppImage; entry synthesized, no desktop file".to_string(),
+            ),
+        );
+    }
+    fields
+}
+
+/// Serializes `entries` as a JSON array for `--list --json`. Optional keys
+/// that are not set are written as `null`.
+pub fn entries_to_json(entries: &[DesktopEntry]) -> String {
+    let mut out = String::from("[");
+    for (i, entry) in entries.iter().enumerate() {
+        out.push_str(if i > 0 { ",\n  {" } else { "\n  {" });
+        let fields = [
+            ("id", Some(entry.id.clone())),
+            ("name", Some(entry.name.clone())),
+            ("exec", Some(entry.exec.clone())),
+            ("path", Some(entry.path.display().to_string())),
+            ("categories", Some(entry.categories.clone())),
+            ("icon", entry.icon.clone()),
+            ("version", entry.version.clone()),
+            ("startup_wm_class", entry.startup_wm_class.clone()),
+        ];
+        for (name, value) in fields {
+            json::write_string(&mut out, name);
+            out.push_str(": ");
+            match value {
+                Some(value) => json::write_string(&mut out, &value),
+                None => out.push_str("null"),
+            }
+            out.push_str(", ");
+        }
+        out.push_str("\"implements\": [");
+        for (i, interface) in entry.implements.iter().enumerate() {
+            if i > 0 {
+                out.push_str(", ");
+            }
+            json::write_string(&mut out, interface);
+        }
+        out.push_str(&format!(
+            "], \"uses_notifications\": {}, \"terminal\": {}}}",
+            entry.uses_notifications, entry.terminal
+        ));
+    }
+    if !entries.is_empty() {
+        out.push('\n');
+    }
+    out.push_str("]\n");
+    out
+}
+
+/// Finds every file providing desktop ID `id` (with or without the
+/// `.desktop` suffix) in the scanned directories, in priority order. Only
+/// the first one can appear in the launcher. `dirs` overrides the search
+/// path as in [`collect_desktop_entries`], and subdirectories named in
+/// `excluded` are skipped like `excluded_subdirs` there.
+pub fn find_desktop_files(id: &str, dirs: Option<&[PathBuf]>, excluded: &[String]) -> Vec<PathBuf> {
+    let file_name = if id.ends_with(".desktop") {
+        id.to_string()
+    } else {
+        format!("{id}.desktop")
+    };
+    let mut found = Vec::new();
+    let mut unreadable = Vec::new();
+    // Like the scan, directories that do not exist are passed over quietly.
+    for dir in scan_dirs(dirs).into_iter().filter(|dir| dir.is_dir()) {
+        let mut visited = HashSet::new();
+        walk_desktop_files(
+            &dir,
+            excluded,
+            &mut visited,
+            &mut unreadable,
+            &mut |path: PathBuf| {
+                if path.file_name().and_then(|name| name.to_str()) == Some(file_name.as_str()) {
+                    found.push(path);
+                }
+            },
+        );
+    }
+    found
+}
+
+/// Which of `paths`, the files for one desktop ID in priority order as
+/// [`find_desktop_files`] returns them, the scan takes the ID from: the
+/// first that parses or [claims it](DesktopParseError::claims_id) anyway.
+/// `None` when every file has a broken Exec.
+pub fn desktop_id_owner(
+    paths: &[PathBuf],
+    current_lang: Option<&str>,
+    current_desktops: Option<&[String]>,
+) -> Option<usize> {
+    let mut line_buf = String::new();
+    paths.iter().position(|path| {
+        match parse_desktop_entry_result(path, current_lang, current_desktops, &mut line_buf) {
+            Ok(_) => true,
+            Err(err) => err.claims_id(),
+        }
+    })
+}
+
+/// Reads the raw keys of the `[Desktop Entry]` group, later keys winning.
+/// Lines are read as [`parse_desktop_entry_result`] reads them.
+fn read_entry_group(path: &Path) -> io::Result<HashMap<String, String>> {
+    let mut reader = BufReader::new(fs::File::open(path)?);
+    let mut line_buf = String::new();
+    let mut first_line = true;
+    let mut keys = HashMap::new();
+    let mut in_entry = false;
+    while let Some(line) = read_entry_line(&mut reader, &mut line_buf, &mut first_line) {
+        match line {
+            EntryLine::Group(header) => in_entry = header == "[Desktop Entry]",
+            EntryLine::Key(key, value) if in_entry => {
+                keys.insert(key.to_string(), value.to_string());
+            }
+            _ => {}
+        }
+    }
+    Ok(keys)
+}
+
+/// Explains whether the desktop file at `path` would be listed, and why:
+/// its `Type`, `NoDisplay` and `Hidden` flags, `OnlyShowIn`/`NotShowIn`
+/// against `current_desktops`, the Exec check, and the overall result.
+pub fn explain_desktop_file(
+    path: &Path,
+    current_lang: Option<&str>,
+    current_desktops: Option<&[String]>,
+) -> Vec<(&'static str, String)> {
+    let current_desktops = current_desktops.filter(|desktops| !desktops.is_empty());
+    let mut fields = vec![("File", path.display().to_string())];
+    let keys = match read_entry_group(path) {
+        Ok(keys) => keys,
+        Err(err) => {
+            fields.push(("Result", format!("hidden: could not be read: {err}")));
+            return fields;
+        }
+    };
+    let raw = |key: &str| keys.get(key).cloned();
+    let flag = |key: &str| match keys.get(key) {
+        Some(value) => format!(
+            "{value} ({})",
+            if parse_bool(value) { "hides" } else { "shows" }
+        ),
+        None => "not set".to_string(),
+    };
+    let show_in = |key: &str, hide_on_match: bool| {
+        let Some(value) = keys.get(key) else {
+            return "not set".to_string();
+        };
+        let Some(desktops) = current_desktops else {
+            return format!("{value} (ignored: XDG_CURRENT_DESKTOP is not set or empty)");
+        };
+        let matched = desktop_list_matches(value, desktops);
+        let verdict = if matched == hide_on_match {
+            "hides"
+        } else {
+            "shows"
+        };
+        let matches = if matched { "matches" } else { "does not match" };
+        format!("{value} ({matches} {}; {verdict})", desktops.join(":"))
+    };
+    fields.push(("Type", raw("Type").unwrap_or_else(|| "missing".to_string())));
+    fields.push(("NoDisplay", flag("NoDisplay")));
+    fields.push(("Hidden", flag("Hidden")));
+    fields.push(("OnlyShowIn", show_in("OnlyShowIn", false)));
+    fields.push(("NotShowIn", show_in("NotShowIn", true)));
+    let exec = match raw("Exec").filter(|exec| !exec.is_empty()) {
+        Some(exec) if exec_looks_valid(&exec) => format!("{exec} (valid)"),
+        Some(exec) => format!("{exec} (cannot run)"),
+        None => "missing".to_string(),
+    };
+    fields.push(("Exec", exec));
+
+    let mut line_buf = String::new();
+    let result =
+        match parse_desktop_entry_result(path, current_lang, current_desktops, &mut line_buf) {
+            Ok(_) => "shown".to_string(),
+            Err(err) => format!("hidden: {err}"),
+        };
+    fields.push(("Result", result));
+    fields
+}
+
+/// Links each entry's desktop file into `dir` under its desktop ID, so other
+/// tools can use the launcher's curated set as an applications directory.
+///
+/// `dir` is created if needed. Existing symlinks are replaced, which makes
+/// re-exporting refresh the directory; regular files are never overwritten
+/// and are returned as skipped. AppImage entries have no desktop file to
+/// link, so they are left out.
+pub fn export_desktop_links(entries: &[DesktopEntry], dir: &Path) -> io::Result<Vec<PathBuf>> {
+    if dir.exists() && !dir.is_dir() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("{} is not a directory", dir.display()),
+        ));
+    }
+    fs::create_dir_all(dir)?;
+    let mut skipped = Vec::new();
+    for entry in entries.iter().filter(|entry| !entry.appimage) {
+        let link = dir.join(&entry.id);
+        match fs::symlink_metadata(&link) {
+            Ok(metadata) if metadata.file_type().is_symlink() => fs::remove_file(&link)?,
+            Ok(_) => {
+                skipped.push(link);
+                continue;
+            }
+            Err(_) => {}
+        }
+        std::os::unix::fs::symlink(&entry.path, &link)?;
+    }
+    Ok(skipped)
+}
+
+/// Formats [`entry_diagnostics`] as `Label: value` lines for the clipboard.
+pub fn format_diagnostics(fields: &[(&str, String)]) -> String {
+    fields
+        .iter()
+        .map(|(label, value)| format!("{label}: {value}\n"))
+        .collect()
 }
 
 pub fn build_category_map(entries: &[DesktopEntry]) -> BTreeMap<String, Vec<usize>> {
+    build_category_map_with_rules(entries, &[])
+}
+
+/// Like [`build_category_map`], bucketing tokens that match no known
+/// category by the user's `rules`.
+pub fn build_category_map_with_rules(
+    entries: &[DesktopEntry],
+    rules: &[CategoryRule],
+) -> BTreeMap<String, Vec<usize>> {
     let mut map: BTreeMap<String, Vec<usize>> = BTreeMap::new();
     for (i, entry) in entries.iter().enumerate() {
-        let bucket = map_categories(&entry.categories);
+        let bucket = map_categories(&entry.categories, rules);
         if let Some(list) = map.get_mut(bucket) {
             list.push(i);
         } else {
@@ -430,30 +2093,133 @@ pub fn build_category_map(entries: &[DesktopEntry]) -> BTreeMap<String, Vec<usiz
     map
 }
 
-fn map_categories(categories_raw: &str) -> &'static str {
+/// Freedesktop category tokens and the sidebar bucket each maps to, with
+/// its priority (lower wins when an entry lists several).
+const CATEGORY_BUCKETS: [(&str, u8, &str); 25] = [
+    ("TerminalEmulator", 1, "Terminal Emulator"),
+    ("Terminal", 1, "Terminal Emulator"),
+    ("Network", 2, "Internet"),
+    ("WebBrowser", 2, "Internet"),
+    ("Internet", 2, "Internet"),
+    ("Game", 3, "Games"),
+    ("Games", 3, "Games"),
+    ("Audio", 4, "Audio/Video"),
+    ("AudioVideo", 4, "Audio/Video"),
+    ("AudioVideoEditing", 4, "Audio/Video"),
+    ("Video", 4, "Audio/Video"),
+    ("VideoConference", 4, "Audio/Video"),
+    ("Graphics", 5, "Graphics"),
+    ("Photography", 5, "Graphics"),
+    ("Development", 6, "Development"),
+    ("IDE", 6, "Development"),
+    ("Programming", 6, "Development"),
+    ("Accessory", 7, "Accessories"),
+    ("Accessories", 7, "Accessories"),
+    ("TextEditor", 8, "Text Editors"),
+    ("Office", 9, "Office"),
+    ("Utility", 10, "Utilities"),
+    ("Utilities", 10, "Utilities"),
+    ("System", 11, "System"),
+    ("Settings", 11, "System"),
+];
+
+/// Most pattern rules `categories.conf` may hold; later lines are ignored.
+pub const MAX_CATEGORY_RULES: usize = 64;
+
+/// Most bytes a compiled [`CategoryRule`] pattern may take, so a rule like
+/// `(a{100}){100}` is refused instead of slowing every scan down.
+const CATEGORY_RULE_SIZE_LIMIT: usize = 64 * 1024;
+
+/// A `categories.conf` rule sending category tokens that match `pattern`
+/// to a sidebar bucket, e.g. `X-.*Game.*=Games`.
+///
+/// Patterns are regular expressions that must match the whole token,
+/// ignoring case. The `regex` crate matches in linear time, so a rule
+/// cannot make scanning hang.
+#[derive(Clone, Debug)]
+pub struct CategoryRule {
+    regex: Regex,
+    pub bucket: &'static str,
+}
+
+impl CategoryRule {
+    /// Compiles `pattern` for `bucket`, one of [`CATEGORIES`] in any case.
+    pub fn new(pattern: &str, bucket: &str) -> Result<Self, String> {
+        let bucket = CATEGORIES
+            .iter()
+            .find(|category| category.eq_ignore_ascii_case(bucket.trim()))
+            .ok_or_else(|| format!("unknown category {}", bucket.trim()))?;
+        let pattern = pattern.trim();
+        if pattern.is_empty() {
+            return Err("empty pattern".to_string());
+        }
+        // The pattern is checked on its own first, so one ending in `\` or
+        // with a stray `)` cannot combine with the anchors around it.
+        let build = |pattern: &str| {
+            RegexBuilder::new(pattern)
+                .case_insensitive(true)
+                .size_limit(CATEGORY_RULE_SIZE_LIMIT)
+                .build()
+        };
+        let regex = build(pattern)
+            .and_then(|_| build(&format!("^(?:{pattern})$")))
+            .map_err(|err| match err {
+                regex::Error::CompiledTooBig(_) => format!("pattern {pattern} is too large"),
+                // Syntax errors draw a caret under the pattern; the last
+                // line says what is wrong.
+                err => {
+                    let message = err.to_string();
+                    let reason = message.lines().last().unwrap_or_default();
+                    format!(
+                        "invalid pattern {pattern}: {}",
+                        reason.trim_start_matches("error: ")
+                    )
+                }
+            })?;
+        Ok(Self { regex, bucket })
+    }
+
+    /// Returns whether the pattern matches all of `token`.
+    pub fn matches(&self, token: &str) -> bool {
+        self.regex.is_match(token)
+    }
+}
+
+/// Picks the sidebar bucket for a raw `Categories` value. Tokens are
+/// trimmed and matched case-insensitively, so sloppy files such as
+/// `utility;` or ` Development ;` still land in the right bucket. When no
+/// token is a known category, the first of `rules` matching a token wins.
+fn map_categories(categories_raw: &str, rules: &[CategoryRule]) -> &'static str {
+    let mapped = map_known_categories(categories_raw);
+    if mapped != "Other" || rules.is_empty() {
+        return mapped;
+    }
+    rules
+        .iter()
+        .find(|rule| {
+            categories_raw
+                .split(';')
+                .map(str::trim)
+                .any(|token| !token.is_empty() && rule.matches(token))
+        })
+        .map_or(mapped, |rule| rule.bucket)
+}
+
+fn map_known_categories(categories_raw: &str) -> &'static str {
     let mut best_priority = 100;
     let mut best_category = "Other";
 
     for category in categories_raw.split(';') {
+        let category = category.trim();
         if category.is_empty() {
             continue;
         }
 
-        let (priority, mapped) = match category {
-            "TerminalEmulator" | "Terminal" => (1, "Terminal Emulator"),
-            "Network" | "WebBrowser" | "Internet" => (2, "Internet"),
-            "Game" | "Games" => (3, "Games"),
-            "Audio" | "AudioVideo" | "AudioVideoEditing" | "Video" | "VideoConference" => {
-                (4, "Audio/Video")
-            }
-            "Graphics" | "Photography" => (5, "Graphics"),
-            "Development" | "IDE" | "Programming" => (6, "Development"),
-            "Accessory" | "Accessories" => (7, "Accessories"),
-            "TextEditor" => (8, "Text Editors"),
-            "Office" => (9, "Office"),
-            "Utility" | "Utilities" => (10, "Utilities"),
-            "System" | "Settings" => (11, "System"),
-            _ => continue,
+        let Some(&(_, priority, mapped)) = CATEGORY_BUCKETS
+            .iter()
+            .find(|(token, _, _)| token.eq_ignore_ascii_case(category))
+        else {
+            continue;
         };
 
         if priority < best_priority {
@@ -0,0 +1,217 @@
+use crate::desktop::DesktopEntry;
+use crate::json::{self, Value};
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+pub const RECENT_CATEGORY: &str = "Recent";
+pub const MOST_USED_CATEGORY: &str = "Most Used";
+
+/// How many recently launched IDs are remembered.
+pub const RECENT_LIMIT: usize = 20;
+
+/// Launch history stored in `$XDG_STATE_HOME/access-launcher/recent.json`.
+///
+/// `recent` holds desktop IDs, most recent first. `launch_counts` maps
+/// desktop IDs to the number of successful launches.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct History {
+    pub recent: Vec<String>,
+    pub launch_counts: BTreeMap<String, u64>,
+}
+
+/// Returns `$XDG_STATE_HOME/access-launcher`, falling back to `~/.local/state`.
+pub fn state_dir() -> Option<PathBuf> {
+    env::var("XDG_STATE_HOME")
+        .ok()
+        .filter(|value| !value.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| {
+            env::var("HOME")
+                .ok()
+                .map(|home| PathBuf::from(home).join(".local/state"))
+        })
+        .map(|dir| dir.join("access-launcher"))
+}
+
+pub fn history_path() -> Option<PathBuf> {
+    state_dir().map(|dir| dir.join("recent.json"))
+}
+
+fn last_category_path() -> Option<PathBuf> {
+    state_dir().map(|dir| dir.join("last-category"))
+}
+
+/// Reads the category saved by [`save_last_category`].
+pub fn load_last_category() -> Option<String> {
+    let contents = fs::read_to_string(last_category_path()?).ok()?;
+    let category = contents.trim();
+    (!category.is_empty()).then(|| category.to_string())
+}
+
+pub fn save_last_category(category: &str) -> io::Result<()> {
+    match last_category_path() {
+        Some(path) => write_atomic(&path, &format!("{category}\n")),
+        None => Ok(()),
+    }
+}
+
+/// Writes `contents` to a sibling temp file and renames it over `path`, so
+/// readers never observe a partially written file.
+pub fn write_atomic(path: &Path, contents: &str) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut temp = path.as_os_str().to_owned();
+    temp.push(format!(".tmp-{}", std::process::id()));
+    let temp = PathBuf::from(temp);
+    fs::write(&temp, contents)?;
+    fs::rename(&temp, path).inspect_err(|_| {
+        let _ = fs::remove_file(&temp);
+    })
+}
+
+impl History {
+    pub fn parse(contents: &str) -> Self {
+        let Some(value) = json::parse(contents) else {
+            return Self::default();
+        };
+        let recent = value
+            .get("recent")
+            .and_then(Value::as_array)
+            .unwrap_or_default()
+            .iter()
+            .filter_map(Value::as_str)
+            .map(str::to_string)
+            .collect();
+        let launch_counts = value
+            .get("launch_counts")
+            .and_then(Value::as_object)
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|(id, count)| count.as_u64().map(|count| (id.clone(), count)))
+            .collect();
+        Self {
+            recent,
+            launch_counts,
+        }
+    }
+
+    pub fn to_json(&self) -> String {
+        let mut out = String::from("{\n  \"recent\": [");
+        for (i, id) in self.recent.iter().enumerate() {
+            if i > 0 {
+                out.push_str(", ");
+            }
+            json::write_string(&mut out, id);
+        }
+        out.push_str("],\n  \"launch_counts\": {");
+        for (i, (id, count)) in self.launch_counts.iter().enumerate() {
+            out.push_str(if i > 0 { ",\n    " } else { "\n    " });
+            json::write_string(&mut out, id);
+            out.push_str(&format!(": {count}"));
+        }
+        if !self.launch_counts.is_empty() {
+            out.push_str("\n  ");
+        }
+        out.push_str("}\n}\n");
+        out
+    }
+
+    /// Loads history from `path`; a missing or malformed file is empty history.
+    pub fn load_from(path: &Path) -> Self {
+        fs::read_to_string(path)
+            .map(|contents| Self::parse(&contents))
+            .unwrap_or_default()
+    }
+
+    pub fn load() -> Self {
+        history_path()
+            .map(|path| Self::load_from(&path))
+            .unwrap_or_default()
+    }
+
+    pub fn save_to(&self, path: &Path) -> io::Result<()> {
+        write_atomic(path, &self.to_json())
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        match history_path() {
+            Some(path) => self.save_to(&path),
+            None => Ok(()),
+        }
+    }
+
+    /// Forgets every launch, leaving "Recent" and "Most Used" empty.
+    pub fn clear(&mut self) {
+        self.recent.clear();
+        self.launch_counts.clear();
+    }
+
+    /// Moves `id` to the front of the recent list and bumps its launch count.
+    pub fn record_launch(&mut self, id: &str) {
+        self.recent.retain(|recent| recent != id);
+        self.recent.insert(0, id.to_string());
+        self.recent.truncate(RECENT_LIMIT);
+        *self.launch_counts.entry(id.to_string()).or_insert(0) += 1;
+    }
+
+    /// Indices of recently launched entries, most recent first.
+    pub fn recent_indices(&self, entries: &[DesktopEntry]) -> Vec<usize> {
+        self.recent
+            .iter()
+            .filter_map(|id| entries.iter().position(|entry| &entry.id == id))
+            .collect()
+    }
+
+    /// Total launches of the entries listed under `category`.
+    pub fn category_launch_count(
+        &self,
+        entries: &[DesktopEntry],
+        category_map: &BTreeMap<String, Vec<usize>>,
+        category: &str,
+    ) -> u64 {
+        category_map
+            .get(category)
+            .into_iter()
+            .flatten()
+            .filter_map(|&index| entries.get(index))
+            .filter_map(|entry| self.launch_counts.get(&entry.id))
+            .sum()
+    }
+
+    /// Sorts `categories` by descending [`Self::category_launch_count`];
+    /// ties keep their current order.
+    pub fn sort_categories_by_use(
+        &self,
+        categories: &mut [&str],
+        entries: &[DesktopEntry],
+        category_map: &BTreeMap<String, Vec<usize>>,
+    ) {
+        categories.sort_by_cached_key(|category| {
+            std::cmp::Reverse(self.category_launch_count(entries, category_map, category))
+        });
+    }
+
+    /// Indices of the `limit` most launched entries.
+    ///
+    /// Sorted by descending launch count; ties keep the alphabetical order of
+    /// `entries`.
+    pub fn most_used_indices(&self, entries: &[DesktopEntry], limit: usize) -> Vec<usize> {
+        let mut used: Vec<(u64, usize)> = entries
+            .iter()
+            .enumerate()
+            .filter_map(|(index, entry)| {
+                self.launch_counts
+                    .get(&entry.id)
+                    .filter(|count| **count > 0)
+                    .map(|count| (*count, index))
+            })
+            .collect();
+        used.sort_by_key(|&(count, _)| std::cmp::Reverse(count));
+        used.truncate(limit);
+        used.into_iter().map(|(_, index)| index).collect()
+    }
+}
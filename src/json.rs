@@ -0,0 +1,267 @@
+//! Minimal JSON reading and writing for the launcher's small state files.
+//!
+//! Only what the launcher stores is supported well: objects, arrays, strings,
+//! integers, booleans, and null. Numbers are kept as `f64`.
+
+use std::fmt::Write;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Value>),
+    Object(Vec<(String, Value)>),
+}
+
+impl Value {
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        match self {
+            Self::Object(fields) => fields
+                .iter()
+                .find(|(name, _)| name == key)
+                .map(|(_, value)| value),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Self::String(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn as_u64(&self) -> Option<u64> {
+        match self {
+            Self::Number(value) if *value >= 0.0 && value.fract() == 0.0 => Some(*value as u64),
+            _ => None,
+        }
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Self::Bool(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&[Value]> {
+        match self {
+            Self::Array(values) => Some(values),
+            _ => None,
+        }
+    }
+
+    pub fn as_object(&self) -> Option<&[(String, Value)]> {
+        match self {
+            Self::Object(fields) => Some(fields),
+            _ => None,
+        }
+    }
+}
+
+/// Appends `value` to `out` as a quoted, escaped JSON string.
+pub fn write_string(out: &mut String, value: &str) {
+    out.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+pub fn parse(input: &str) -> Option<Value> {
+    let mut parser = Parser {
+        bytes: input.as_bytes(),
+        input,
+        pos: 0,
+    };
+    let value = parser.value()?;
+    parser.skip_whitespace();
+    (parser.pos == parser.bytes.len()).then_some(value)
+}
+
+struct Parser<'a> {
+    bytes: &'a [u8],
+    input: &'a str,
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn skip_whitespace(&mut self) {
+        while self
+            .bytes
+            .get(self.pos)
+            .is_some_and(|b| b.is_ascii_whitespace())
+        {
+            self.pos += 1;
+        }
+    }
+
+    fn eat(&mut self, byte: u8) -> bool {
+        self.skip_whitespace();
+        if self.bytes.get(self.pos) == Some(&byte) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn literal(&mut self, text: &str, value: Value) -> Option<Value> {
+        if self.input[self.pos..].starts_with(text) {
+            self.pos += text.len();
+            Some(value)
+        } else {
+            None
+        }
+    }
+
+    fn value(&mut self) -> Option<Value> {
+        self.skip_whitespace();
+        match *self.bytes.get(self.pos)? {
+            b'{' => self.object(),
+            b'[' => self.array(),
+            b'"' => self.string().map(Value::String),
+            b't' => self.literal("true", Value::Bool(true)),
+            b'f' => self.literal("false", Value::Bool(false)),
+            b'n' => self.literal("null", Value::Null),
+            _ => self.number(),
+        }
+    }
+
+    fn object(&mut self) -> Option<Value> {
+        self.pos += 1;
+        let mut fields = Vec::new();
+        if self.eat(b'}') {
+            return Some(Value::Object(fields));
+        }
+        loop {
+            self.skip_whitespace();
+            let key = self.string()?;
+            if !self.eat(b':') {
+                return None;
+            }
+            fields.push((key, self.value()?));
+            if self.eat(b',') {
+                continue;
+            }
+            return self.eat(b'}').then_some(Value::Object(fields));
+        }
+    }
+
+    fn array(&mut self) -> Option<Value> {
+        self.pos += 1;
+        let mut values = Vec::new();
+        if self.eat(b']') {
+            return Some(Value::Array(values));
+        }
+        loop {
+            values.push(self.value()?);
+            if self.eat(b',') {
+                continue;
+            }
+            return self.eat(b']').then_some(Value::Array(values));
+        }
+    }
+
+    fn string(&mut self) -> Option<String> {
+        if self.bytes.get(self.pos) != Some(&b'"') {
+            return None;
+        }
+        self.pos += 1;
+        let mut value = String::new();
+        loop {
+            let rest = &self.input[self.pos..];
+            let end = rest.find(['"', '\\'])?;
+            value.push_str(&rest[..end]);
+            self.pos += end;
+            if self.bytes[self.pos] == b'"' {
+                self.pos += 1;
+                return Some(value);
+            }
+            self.pos += 1;
+            let escape = *self.bytes.get(self.pos)?;
+            self.pos += 1;
+            match escape {
+                b'"' => value.push('"'),
+                b'\\' => value.push('\\'),
+                b'/' => value.push('/'),
+                b'n' => value.push('\n'),
+                b'r' => value.push('\r'),
+                b't' => value.push('\t'),
+                b'b' => value.push('\u{8}'),
+                b'f' => value.push('\u{c}'),
+                b'u' => {
+                    let hex = self.input.get(self.pos..self.pos + 4)?;
+                    let code = u32::from_str_radix(hex, 16).ok()?;
+                    self.pos += 4;
+                    value.push(char::from_u32(code).unwrap_or('\u{fffd}'));
+                }
+                _ => return None,
+            }
+        }
+    }
+
+    fn number(&mut self) -> Option<Value> {
+        let start = self.pos;
+        while self
+            .bytes
+            .get(self.pos)
+            .is_some_and(|b| b.is_ascii_digit() || matches!(b, b'-' | b'+' | b'.' | b'e' | b'E'))
+        {
+            self.pos += 1;
+        }
+        self.input[start..self.pos].parse().ok().map(Value::Number)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_nested_values() {
+        let value =
+            parse(r#" {"a": [1, "two", true, null], "b": {"c": "d\"eA"}} "#).expect("valid json");
+        let a = value.get("a").and_then(Value::as_array).expect("array");
+        assert_eq!(a[0].as_u64(), Some(1));
+        assert_eq!(a[1].as_str(), Some("two"));
+        assert_eq!(a[2].as_bool(), Some(true));
+        assert_eq!(a[3], Value::Null);
+        let c = value
+            .get("b")
+            .and_then(|b| b.get("c"))
+            .and_then(Value::as_str);
+        assert_eq!(c, Some("d\"eA"));
+    }
+
+    #[test]
+    fn rejects_malformed_input() {
+        assert!(parse("{").is_none());
+        assert!(parse(r#"{"a" 1}"#).is_none());
+        assert!(parse("[1,]").is_none());
+        assert!(parse("[] trailing").is_none());
+    }
+
+    #[test]
+    fn write_string_round_trips() {
+        let mut out = String::new();
+        write_string(&mut out, "tab\tquote\"slash\\\u{1}");
+        assert_eq!(
+            parse(&out).as_ref().and_then(Value::as_str),
+            Some("tab\tquote\"slash\\\u{1}")
+        );
+    }
+}
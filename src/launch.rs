@@ -0,0 +1,889 @@
+use crate::desktop::DesktopEntry;
+use gtk4::{gio, glib};
+use std::cell::{Cell, RefCell};
+use std::env;
+use std::fmt;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+/// A fully resolved command line ready to be spawned.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LaunchCommand {
+    pub argv: Vec<String>,
+    pub working_dir: Option<PathBuf>,
+}
+
+#[derive(Debug)]
+pub enum LaunchError {
+    EmptyExec,
+    InvalidExec(String),
+    InvalidArguments(String),
+    NoTerminal,
+    Spawn(io::Error),
+    /// The process exited unsuccessfully; `None` if a signal killed it.
+    Exited(Option<i32>),
+    /// The pre-launch hook exited unsuccessfully; `None` if a signal killed it.
+    HookRejected(Option<i32>),
+    HookTimedOut(Duration),
+    HookFailed(io::Error),
+}
+
+impl LaunchError {
+    /// Whether the pre-launch hook stopped the launch, as opposed to the
+    /// launch itself failing. A stopped launch is not retried another way.
+    pub fn is_from_hook(&self) -> bool {
+        matches!(
+            self,
+            Self::HookRejected(_) | Self::HookTimedOut(_) | Self::HookFailed(_)
+        )
+    }
+}
+
+impl fmt::Display for LaunchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::EmptyExec => write!(f, "Exec is empty"),
+            Self::InvalidExec(message) => write!(f, "Exec could not be parsed: {message}"),
+            Self::InvalidArguments(message) => {
+                write!(f, "arguments could not be parsed: {message}")
+            }
+            Self::NoTerminal => write!(f, "no terminal emulator was found"),
+            Self::Spawn(err) => write!(f, "failed to start process: {err}"),
+            Self::Exited(Some(code)) => write!(f, "exited with status {code}"),
+            Self::Exited(None) => write!(f, "was killed by a signal"),
+            Self::HookRejected(Some(code)) => {
+                write!(
+                    f,
+                    "the pre-launch hook refused the launch (exit status {code})"
+                )
+            }
+            Self::HookRejected(None) => {
+                write!(
+                    f,
+                    "the pre-launch hook was killed before it allowed the launch"
+                )
+            }
+            Self::HookTimedOut(timeout) => write!(
+                f,
+                "the pre-launch hook did not finish within {} seconds",
+                timeout.as_secs()
+            ),
+            Self::HookFailed(err) => write!(f, "the pre-launch hook could not be run: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for LaunchError {}
+
+/// A terminal emulator and the arguments that precede the command it runs.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Terminal {
+    pub program: String,
+    pub exec_args: Vec<String>,
+}
+
+/// Known terminals in preference order, with the flag that introduces the command.
+const TERMINALS: [(&str, &[&str]); 10] = [
+    ("x-terminal-emulator", &["-e"]),
+    ("gnome-terminal", &["--"]),
+    ("kgx", &["--"]),
+    ("konsole", &["-e"]),
+    ("xfce4-terminal", &["-x"]),
+    ("mate-terminal", &["-x"]),
+    ("alacritty", &["-e"]),
+    ("kitty", &[]),
+    ("foot", &[]),
+    ("xterm", &["-e"]),
+];
+
+pub fn find_in_path(program: &str) -> Option<PathBuf> {
+    if program.contains('/') {
+        let path = Path::new(program);
+        return path.is_file().then(|| path.to_path_buf());
+    }
+    let paths = env::var_os("PATH")?;
+    env::split_paths(&paths)
+        .map(|dir| dir.join(program))
+        .find(|candidate| candidate.is_file())
+}
+
+/// Finds a terminal emulator, honoring `$TERMINAL` before the built-in list.
+pub fn detect_terminal() -> Option<Terminal> {
+    if let Ok(terminal) = env::var("TERMINAL") {
+        if !terminal.is_empty() && find_in_path(&terminal).is_some() {
+            let name = Path::new(&terminal)
+                .file_name()
+                .and_then(|name| name.to_str())
+                .unwrap_or(&terminal);
+            let exec_args = TERMINALS
+                .iter()
+                .find(|(known, _)| *known == name)
+                .map_or(&["-e"][..], |(_, args)| *args);
+            return Some(Terminal {
+                program: terminal.clone(),
+                exec_args: exec_args.iter().map(|arg| arg.to_string()).collect(),
+            });
+        }
+    }
+
+    TERMINALS.iter().find_map(|(program, exec_args)| {
+        find_in_path(program).map(|_| Terminal {
+            program: program.to_string(),
+            exec_args: exec_args.iter().map(|arg| arg.to_string()).collect(),
+        })
+    })
+}
+
+/// Expands `$VAR` and `${VAR}` references; unset variables expand to nothing.
+pub fn expand_env_vars(arg: &str) -> String {
+    let mut expanded = String::with_capacity(arg.len());
+    let mut chars = arg.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '$' {
+            expanded.push(c);
+            continue;
+        }
+        let braced = chars.peek() == Some(&'{');
+        if braced {
+            chars.next();
+        }
+        let mut name = String::new();
+        while let Some(&next) = chars.peek() {
+            if next.is_ascii_alphanumeric() || next == '_' {
+                name.push(next);
+                chars.next();
+            } else {
+                break;
+            }
+        }
+        if braced {
+            if chars.peek() == Some(&'}') {
+                chars.next();
+            } else {
+                // Not a valid reference; keep it literally.
+                expanded.push_str("${");
+                expanded.push_str(&name);
+                continue;
+            }
+        }
+        if name.is_empty() {
+            expanded.push('$');
+            if braced {
+                expanded.push_str("{}");
+            }
+            continue;
+        }
+        if let Ok(value) = env::var(&name) {
+            expanded.push_str(&value);
+        }
+    }
+    expanded
+}
+
+/// Splits an Exec value into argv and expands its field codes.
+///
+/// `%f`/`%u` take the first of `files` and `%F`/`%U` take all of them; they
+/// are dropped when `files` is empty. `%i` becomes `--icon <Icon>`, `%c` the
+/// name, `%k` the desktop file path, and `%%` a literal percent sign.
+/// Deprecated and unknown codes are removed. Environment variables are
+/// expanded in the Exec text but never in substituted values.
+pub fn expand_exec(
+    exec: &str,
+    entry: &DesktopEntry,
+    files: &[String],
+) -> Result<Vec<String>, LaunchError> {
+    if exec.trim().is_empty() {
+        return Err(LaunchError::EmptyExec);
+    }
+    let args = glib::shell_parse_argv(exec)
+        .map_err(|err| LaunchError::InvalidExec(err.message().to_string()))?;
+
+    let mut argv = Vec::with_capacity(args.len());
+    for arg in args {
+        let arg = arg.to_string_lossy();
+        match arg.as_ref() {
+            "%f" | "%u" => argv.extend(files.first().cloned()),
+            "%F" | "%U" => argv.extend(files.iter().cloned()),
+            "%i" => {
+                if let Some(icon) = entry.icon.as_deref().filter(|icon| !icon.is_empty()) {
+                    argv.push("--icon".to_string());
+                    argv.push(icon.to_string());
+                }
+            }
+            _ => {
+                let expanded = expand_field_codes(&expand_env_vars(&arg), entry);
+                if !expanded.is_empty() || arg.is_empty() {
+                    argv.push(expanded);
+                }
+            }
+        }
+    }
+
+    if argv.is_empty() {
+        return Err(LaunchError::EmptyExec);
+    }
+    Ok(argv)
+}
+
+fn expand_field_codes(arg: &str, entry: &DesktopEntry) -> String {
+    let mut expanded = String::with_capacity(arg.len());
+    let mut chars = arg.chars();
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            expanded.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('%') => expanded.push('%'),
+            Some('c') => expanded.push_str(&entry.name),
+            Some('k') => expanded.push_str(&entry.path.to_string_lossy()),
+            // Deprecated (%d, %D, %n, %N, %v, %m), file codes embedded in a
+            // larger argument, and unknown codes are all dropped.
+            _ => {}
+        }
+    }
+    expanded
+}
+
+/// Builds the command for `entry`, wrapping it in `terminal` when the entry
+/// sets `Terminal=true`.
+pub fn build_command(
+    entry: &DesktopEntry,
+    terminal: Option<&Terminal>,
+) -> Result<LaunchCommand, LaunchError> {
+    build_wrapped_command(entry, terminal, &[])
+}
+
+/// Like [`build_command`], with `wrapper` (e.g. `gamemoderun`) run in front
+/// of the expanded Exec. A terminal still comes first, so the wrapper runs
+/// inside it.
+pub fn build_wrapped_command(
+    entry: &DesktopEntry,
+    terminal: Option<&Terminal>,
+    wrapper: &[String],
+) -> Result<LaunchCommand, LaunchError> {
+    let mut argv = expand_exec(&entry.exec, entry, &[])?;
+    argv.splice(0..0, wrapper.iter().cloned());
+    if entry.terminal {
+        let terminal = terminal.ok_or(LaunchError::NoTerminal)?;
+        let mut wrapped = Vec::with_capacity(argv.len() + terminal.exec_args.len() + 1);
+        wrapped.push(terminal.program.clone());
+        wrapped.extend(terminal.exec_args.iter().cloned());
+        wrapped.append(&mut argv);
+        argv = wrapped;
+    }
+    let working_dir = entry
+        .working_dir
+        .as_deref()
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from);
+    Ok(LaunchCommand { argv, working_dir })
+}
+
+/// Builds the command that opens `terminal` in the directory `entry` runs
+/// from, without starting the app: its `Path` key, else the directory that
+/// holds the desktop file.
+pub fn terminal_here_command(entry: &DesktopEntry, terminal: &Terminal) -> LaunchCommand {
+    let working_dir = entry
+        .working_dir
+        .as_deref()
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| entry.path.parent().map(Path::to_path_buf));
+    LaunchCommand {
+        argv: vec![terminal.program.clone()],
+        working_dir,
+    }
+}
+
+/// Builds the command that opens `terminal` on its own, in the home
+/// directory, unrelated to any app.
+pub fn terminal_command(terminal: &Terminal) -> LaunchCommand {
+    LaunchCommand {
+        argv: vec![terminal.program.clone()],
+        working_dir: env::var_os("HOME")
+            .filter(|home| !home.is_empty())
+            .map(PathBuf::from),
+    }
+}
+
+/// The last-resort command for `entry` after its own launch failed:
+/// `xdg-open` on its desktop file, which the desktop then starts or hands to
+/// its handler for desktop files. `None` without `xdg-open`, and for
+/// AppImages, which are already run directly.
+pub fn xdg_open_fallback(entry: &DesktopEntry) -> Option<LaunchCommand> {
+    if entry.appimage {
+        return None;
+    }
+    find_in_path("xdg-open")?;
+    Some(LaunchCommand {
+        argv: vec![
+            "xdg-open".to_string(),
+            entry.path.to_string_lossy().into_owned(),
+        ],
+        working_dir: None,
+    })
+}
+
+/// How long to wait for the `xdg-open` fallback to exit. One still running
+/// by then is taken to have opened something.
+pub const FALLBACK_EXIT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Told how a spawned process ended; see [`watch_exit`].
+pub type ExitCallback = Box<dyn FnOnce(Result<(), LaunchError>)>;
+
+/// Runs `fallback` with `spawn` after the primary launch failed with
+/// `error`. `spawn` starts the command and reports how it ended; `done`
+/// then gets the details for the error dialog, which name both the
+/// original error and what became of the fallback. Even a fallback that
+/// ran is reported, since the app itself did not start.
+pub fn run_fallback(
+    error: &str,
+    fallback: Option<LaunchCommand>,
+    spawn: impl FnOnce(&LaunchCommand, ExitCallback),
+    done: impl FnOnce(String) + 'static,
+) {
+    let Some(command) = fallback else {
+        done(format!(
+            "{error}\n\nNo fallback was tried: xdg-open is not installed."
+        ));
+        return;
+    };
+    let error = error.to_string();
+    let shown = format_command(&command);
+    spawn(
+        &command,
+        Box::new(move |outcome| {
+            done(match outcome {
+                Ok(()) => format!(
+                    "{error}\n\n{shown} was run instead. It may have opened the desktop \
+                     file rather than the application."
+                ),
+                Err(err) => format!("{error}\n\nThe fallback, {shown}, also failed: {err}"),
+            })
+        }),
+    );
+}
+
+/// Calls `done` from the main loop once `child` exits: `Ok` for status 0,
+/// or [`LaunchError::Exited`]. A child still running after `timeout` counts
+/// as `Ok`, and is left running.
+pub fn watch_exit(
+    child: Child,
+    timeout: Duration,
+    done: impl FnOnce(Result<(), LaunchError>) + 'static,
+) {
+    use std::os::unix::process::ExitStatusExt;
+    let done: Rc<RefCell<Option<ExitCallback>>> = Rc::new(RefCell::new(Some(Box::new(done))));
+    let timer = {
+        let done = Rc::clone(&done);
+        glib::timeout_add_local_once(timeout, move || {
+            if let Some(done) = done.borrow_mut().take() {
+                done(Ok(()));
+            }
+        })
+    };
+    let timer = RefCell::new(Some(timer));
+    // The watch reaps the child, so it is not waited on here.
+    glib::child_watch_add_local(glib::Pid(child.id() as i32), move |_, status| {
+        let Some(done) = done.borrow_mut().take() else {
+            return;
+        };
+        if let Some(timer) = timer.borrow_mut().take() {
+            timer.remove();
+        }
+        let status = ExitStatus::from_raw(status);
+        done(if status.success() {
+            Ok(())
+        } else {
+            Err(LaunchError::Exited(status.code()))
+        });
+    });
+}
+
+/// Reaps `child` from the main loop once it exits. The window runs for a
+/// long time, so every app it spawns directly is handed here or to
+/// [`watch_exit`]; a child left alone stays a zombie until the launcher
+/// quits.
+pub fn reap_child(child: Child) {
+    glib::child_watch_add_local(glib::Pid(child.id() as i32), |_, _| ());
+}
+
+/// Resolves the command that `--launch` would run for `entry`.
+pub fn resolve_command(entry: &DesktopEntry) -> Result<LaunchCommand, LaunchError> {
+    resolve_command_with_args(entry, &[])
+}
+
+/// Like [`resolve_command`], with `extra_args` appended after the expanded Exec.
+pub fn resolve_command_with_args(
+    entry: &DesktopEntry,
+    extra_args: &[String],
+) -> Result<LaunchCommand, LaunchError> {
+    resolve_wrapped_command(entry, &[], extra_args)
+}
+
+/// Like [`resolve_command_with_args`], with `wrapper` run in front of the
+/// expanded Exec as [`build_wrapped_command`] does.
+pub fn resolve_wrapped_command(
+    entry: &DesktopEntry,
+    wrapper: &[String],
+    extra_args: &[String],
+) -> Result<LaunchCommand, LaunchError> {
+    let terminal = if entry.terminal {
+        detect_terminal()
+    } else {
+        None
+    };
+    let mut command = build_wrapped_command(entry, terminal.as_ref(), wrapper)?;
+    command.argv.extend(extra_args.iter().cloned());
+    Ok(command)
+}
+
+/// Splits user-typed arguments the way a shell would, without expanding
+/// anything. Blank input yields no arguments.
+pub fn parse_extra_args(text: &str) -> Result<Vec<String>, LaunchError> {
+    if text.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+    let args = glib::shell_parse_argv(text)
+        .map_err(|err| LaunchError::InvalidArguments(err.message().to_string()))?;
+    Ok(args
+        .into_iter()
+        .map(|arg| arg.to_string_lossy().into_owned())
+        .collect())
+}
+
+/// Variables a GUI app needs to reach the display server and session bus.
+pub const SESSION_ENV_VARS: [&str; 5] = [
+    "DISPLAY",
+    "WAYLAND_DISPLAY",
+    "DBUS_SESSION_BUS_ADDRESS",
+    "XDG_RUNTIME_DIR",
+    "XDG_SESSION_TYPE",
+];
+
+/// Builds the variables to set explicitly on a spawned GUI app.
+///
+/// Each of [`SESSION_ENV_VARS`] that `lookup` finds is passed on. When
+/// neither display variable is known, `display_name` (from GDK) fills in
+/// `DISPLAY` for X11 names like `:0`, else `WAYLAND_DISPLAY`. An
+/// `activation_token` is set as both `XDG_ACTIVATION_TOKEN` (Wayland) and
+/// `DESKTOP_STARTUP_ID` (X11), as GIO does. The child still inherits the
+/// rest of the environment.
+pub fn session_environment(
+    lookup: impl Fn(&str) -> Option<String>,
+    display_name: Option<&str>,
+    activation_token: Option<&str>,
+) -> Vec<(String, String)> {
+    let mut vars: Vec<(String, String)> = SESSION_ENV_VARS
+        .iter()
+        .filter_map(|&key| {
+            lookup(key)
+                .filter(|value| !value.is_empty())
+                .map(|value| (key.to_string(), value))
+        })
+        .collect();
+    let has_display = vars
+        .iter()
+        .any(|(key, _)| key == "DISPLAY" || key == "WAYLAND_DISPLAY");
+    if let Some(name) = display_name.filter(|name| !name.is_empty() && !has_display) {
+        let key = if name.contains(':') {
+            "DISPLAY"
+        } else {
+            "WAYLAND_DISPLAY"
+        };
+        vars.push((key.to_string(), name.to_string()));
+    }
+    if let Some(token) = activation_token.filter(|token| !token.is_empty()) {
+        vars.push(("XDG_ACTIVATION_TOKEN".to_string(), token.to_string()));
+        vars.push(("DESKTOP_STARTUP_ID".to_string(), token.to_string()));
+    }
+    vars
+}
+
+/// Variable that asks Mesa to render on the non-default GPU.
+pub const MESA_OFFLOAD_VARS: [(&str, &str); 1] = [("DRI_PRIME", "1")];
+
+/// Variables that ask the NVIDIA driver to render on its GPU through PRIME
+/// render offload. They make glvnd load NVIDIA's GLX library, so they are
+/// only set when that driver runs the GPU.
+pub const NVIDIA_OFFLOAD_VARS: [(&str, &str); 2] = [
+    ("__NV_PRIME_RENDER_OFFLOAD", "1"),
+    ("__GLX_VENDOR_LIBRARY_NAME", "nvidia"),
+];
+
+/// Kernel drivers whose GPUs Mesa renders on.
+const MESA_DRIVERS: [&str; 5] = ["amdgpu", "radeon", "i915", "xe", "nouveau"];
+
+/// The variables that offload rendering to a GPU run by kernel `driver`,
+/// or `None` for drivers with no known way to do so.
+pub fn offload_vars(driver: &str) -> Option<&'static [(&'static str, &'static str)]> {
+    if driver == "nvidia" {
+        Some(&NVIDIA_OFFLOAD_VARS)
+    } else if MESA_DRIVERS.contains(&driver) {
+        Some(&MESA_OFFLOAD_VARS)
+    } else {
+        None
+    }
+}
+
+/// Adds the `offload` variables to `vars`, replacing any already set there.
+pub fn add_prime_offload(vars: &mut Vec<(String, String)>, offload: &[(&str, &str)]) {
+    vars.retain(|(key, _)| !offload.iter().any(|(prime, _)| prime == key));
+    vars.extend(
+        offload
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string())),
+    );
+}
+
+/// The `cardN` entries of `drm_dir` (normally `/sys/class/drm`), one per
+/// GPU, in card order, leaving out the `cardN-<connector>` outputs.
+fn gpu_cards(drm_dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = std::fs::read_dir(drm_dir) else {
+        return Vec::new();
+    };
+    let mut cards: Vec<(u32, PathBuf)> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let number = entry
+                .file_name()
+                .to_str()?
+                .strip_prefix("card")?
+                .parse()
+                .ok()?;
+            Some((number, entry.path()))
+        })
+        .collect();
+    cards.sort();
+    cards.into_iter().map(|(_, path)| path).collect()
+}
+
+/// Counts the GPUs in `drm_dir` (normally `/sys/class/drm`) by their
+/// `cardN` entries, leaving out the `cardN-<connector>` outputs.
+pub fn gpu_count(drm_dir: &Path) -> usize {
+    gpu_cards(drm_dir).len()
+}
+
+/// The kernel driver of the GPU to offload to, from `drm_dir`: the first
+/// card that is not the boot display device (`device/boot_vga`), or the
+/// second card when none says. `None` with a single GPU.
+pub fn secondary_gpu_driver(drm_dir: &Path) -> Option<String> {
+    let cards = gpu_cards(drm_dir);
+    if cards.len() < 2 {
+        return None;
+    }
+    let is_boot = |card: &PathBuf| {
+        std::fs::read_to_string(card.join("device/boot_vga")).is_ok_and(|value| value.trim() == "1")
+    };
+    let secondary = if cards.iter().any(is_boot) {
+        cards.iter().find(|card| !is_boot(card))?
+    } else {
+        &cards[1]
+    };
+    let driver = std::fs::read_link(secondary.join("device/driver")).ok()?;
+    Some(driver.file_name()?.to_string_lossy().into_owned())
+}
+
+/// The variables that run an app on this machine's discrete GPU, or
+/// `None` when there is no second GPU or its driver is not known.
+pub fn prime_offload() -> Option<&'static [(&'static str, &'static str)]> {
+    offload_vars(&secondary_gpu_driver(Path::new("/sys/class/drm"))?)
+}
+
+/// Whether there is a second GPU to offload rendering to. Without one,
+/// `PrefersNonDefaultGPU` apps launch normally.
+pub fn prime_offload_available() -> bool {
+    prime_offload().is_some()
+}
+
+/// The variables [`spawn`] passes on, plus those of [`prime_offload`] when
+/// `entry` sets `PrefersNonDefaultGPU`.
+pub fn entry_environment(entry: &DesktopEntry) -> Vec<(String, String)> {
+    let mut vars = session_environment(|key| env::var(key).ok(), None, None);
+    if let Some(offload) = prime_offload().filter(|_| entry.prefers_non_default_gpu) {
+        add_prime_offload(&mut vars, offload);
+    }
+    vars
+}
+
+/// Spawns `command` with this process's session variables passed on.
+///
+/// The child is not waited on, which suits the one-shot command line only:
+/// the window spawns with [`spawn_child_with_env`] and [`reap_child`].
+pub fn spawn(command: &LaunchCommand) -> Result<(), LaunchError> {
+    spawn_with_env(
+        command,
+        &session_environment(|key| env::var(key).ok(), None, None),
+    )
+}
+
+/// Spawns `command` with `vars` set on top of the inherited environment.
+/// Like [`spawn`], the child is not waited on.
+pub fn spawn_with_env(
+    command: &LaunchCommand,
+    vars: &[(String, String)],
+) -> Result<(), LaunchError> {
+    spawn_child_with_env(command, vars).map(drop)
+}
+
+/// Like [`spawn_with_env`], but returns the child for [`watch_exit`] or
+/// [`reap_child`].
+pub fn spawn_child_with_env(
+    command: &LaunchCommand,
+    vars: &[(String, String)],
+) -> Result<Child, LaunchError> {
+    let (program, args) = command.argv.split_first().ok_or(LaunchError::EmptyExec)?;
+    let mut process = Command::new(program);
+    process
+        .envs(vars.iter().map(|(key, value)| (key, value)))
+        .args(args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null());
+    if let Some(dir) = &command.working_dir {
+        process.current_dir(dir);
+    }
+    process.spawn().map_err(LaunchError::Spawn)
+}
+
+/// How long the pre-launch hook may run before the launch is aborted. Long
+/// enough for a hook that asks for confirmation.
+pub const PRE_LAUNCH_HOOK_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Runs the pre-launch hook at `hook` before app `id` runs `command_line`.
+///
+/// The hook contract:
+/// - The hook only runs if `hook` is an executable file; otherwise the
+///   launch goes ahead without it.
+/// - It is called as `hook <desktop-id> <command-line>`, where the command
+///   line is the resolved command quoted like `--print-exec` prints it.
+/// - The same two values are written to its stdin, one per line.
+/// - Its stdout and stderr are inherited, so it can log.
+/// - Exit status 0 lets the launch go ahead. Any other status, or being
+///   killed by a signal, aborts the launch.
+/// - A hook still running after `timeout` is killed and the launch aborted.
+///
+/// This blocks until the hook exits, so it is only for the command line;
+/// the window uses [`run_pre_launch_hook_async`].
+pub fn run_pre_launch_hook(
+    hook: &Path,
+    id: &str,
+    command_line: &str,
+    timeout: Duration,
+) -> Result<(), LaunchError> {
+    if !is_executable_file(hook) {
+        return Ok(());
+    }
+    let mut child = Command::new(hook)
+        .arg(id)
+        .arg(command_line)
+        .stdin(Stdio::piped())
+        .spawn()
+        .map_err(LaunchError::HookFailed)?;
+    if let Some(mut stdin) = child.stdin.take() {
+        // A hook that exits without reading closes the pipe; that is fine.
+        let _ = writeln!(stdin, "{id}\n{command_line}");
+    }
+    let started = Instant::now();
+    loop {
+        match child.try_wait() {
+            Ok(Some(status)) if status.success() => return Ok(()),
+            Ok(Some(status)) => return Err(LaunchError::HookRejected(status.code())),
+            Ok(None) if started.elapsed() >= timeout => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(LaunchError::HookTimedOut(timeout));
+            }
+            Ok(None) => std::thread::sleep(Duration::from_millis(10)),
+            Err(err) => return Err(LaunchError::HookFailed(err)),
+        }
+    }
+}
+
+/// Like [`run_pre_launch_hook`], but returns right away and calls `done`
+/// with the outcome from the main loop once the hook exits or times out, so
+/// the window keeps responding while a hook asks for confirmation.
+pub fn run_pre_launch_hook_async(
+    hook: &Path,
+    id: &str,
+    command_line: &str,
+    timeout: Duration,
+    done: impl FnOnce(Result<(), LaunchError>) + 'static,
+) {
+    if !is_executable_file(hook) {
+        done(Ok(()));
+        return;
+    }
+    let argv = [hook.as_os_str(), id.as_ref(), command_line.as_ref()];
+    let process = match gio::Subprocess::newv(&argv, gio::SubprocessFlags::STDIN_PIPE) {
+        Ok(process) => process,
+        Err(err) => {
+            done(Err(LaunchError::HookFailed(io::Error::other(
+                err.message(),
+            ))));
+            return;
+        }
+    };
+    let timed_out = Rc::new(Cell::new(false));
+    let timer = {
+        let process = process.clone();
+        let timed_out = Rc::clone(&timed_out);
+        glib::timeout_add_local_once(timeout, move || {
+            timed_out.set(true);
+            process.force_exit();
+        })
+    };
+    let input = format!("{id}\n{command_line}\n");
+    process
+        .clone()
+        .communicate_utf8_async(Some(input), gio::Cancellable::NONE, move |result| {
+            if timed_out.get() {
+                done(Err(LaunchError::HookTimedOut(timeout)));
+                return;
+            }
+            timer.remove();
+            done(match result {
+                Err(err) => Err(LaunchError::HookFailed(io::Error::other(err.message()))),
+                Ok(_) if process.is_successful() => Ok(()),
+                Ok(_) if process.has_exited() => {
+                    Err(LaunchError::HookRejected(Some(process.exit_status())))
+                }
+                Ok(_) => Err(LaunchError::HookRejected(None)),
+            });
+        });
+}
+
+/// Returns whether `path` is a regular file with an execute bit set.
+pub fn is_executable_file(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    path.metadata()
+        .is_ok_and(|metadata| metadata.is_file() && metadata.permissions().mode() & 0o111 != 0)
+}
+
+/// How long to wait for a D-Bus activated app to answer.
+const DBUS_ACTIVATE_TIMEOUT_MS: i32 = 5000;
+
+/// Returns the bus name to activate `entry` through, if any.
+///
+/// Only `DBusActivatable` entries qualify, and their desktop ID (minus
+/// `.desktop`) must be a valid well-known bus name such as
+/// `org.gnome.Calculator`.
+pub fn dbus_name(entry: &DesktopEntry) -> Option<&str> {
+    if !entry.dbus_activatable {
+        return None;
+    }
+    let name = entry.id.strip_suffix(".desktop")?;
+    let elements: Vec<&str> = name.split('.').collect();
+    let valid = name.len() <= 255
+        && elements.len() >= 2
+        && elements.iter().all(|element| {
+            !element.is_empty()
+                && !element.starts_with(|c: char| c.is_ascii_digit())
+                && element
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+        });
+    valid.then_some(name)
+}
+
+/// Object path the freedesktop Application interface lives at for `name`.
+pub fn dbus_object_path(name: &str) -> String {
+    format!("/{}", name.replace('.', "/").replace('-', "_"))
+}
+
+/// Calls `org.freedesktop.Application.Activate` on `name`, starting the
+/// app through the session bus if it is not running yet.
+pub fn activate_dbus(name: &str) -> Result<(), glib::Error> {
+    let connection = gio::bus_get_sync(gio::BusType::Session, gio::Cancellable::NONE)?;
+    let platform_data = glib::VariantDict::new(None).end();
+    connection.call_sync(
+        Some(name),
+        &dbus_object_path(name),
+        "org.freedesktop.Application",
+        "Activate",
+        Some(&glib::Variant::tuple_from_iter([platform_data])),
+        None,
+        gio::DBusCallFlags::NONE,
+        DBUS_ACTIVATE_TIMEOUT_MS,
+        gio::Cancellable::NONE,
+    )?;
+    Ok(())
+}
+
+/// Repeat activations of the same app within this window are ignored.
+pub const ACTIVATION_DEBOUNCE: Duration = Duration::from_millis(500);
+
+/// Suppresses the duplicate launch a double-click or key repeat would cause.
+///
+/// Only an activation of the same ID inside `window` is rejected, so a
+/// deliberate second launch a moment later still goes through.
+#[derive(Clone, Debug)]
+pub struct ActivationGuard {
+    window: Duration,
+    last: Option<(String, Instant)>,
+}
+
+impl Default for ActivationGuard {
+    fn default() -> Self {
+        Self::new(ACTIVATION_DEBOUNCE)
+    }
+}
+
+impl ActivationGuard {
+    pub fn new(window: Duration) -> Self {
+        Self { window, last: None }
+    }
+
+    /// Returns whether activating `id` at `now` should launch, recording it if so.
+    pub fn allow(&mut self, id: &str, now: Instant) -> bool {
+        if let Some((last_id, last_time)) = &self.last {
+            if last_id == id && now.saturating_duration_since(*last_time) < self.window {
+                return false;
+            }
+        }
+        self.last = Some((id.to_string(), now));
+        true
+    }
+}
+
+/// Finds an entry by desktop ID, with or without the `.desktop` suffix.
+pub fn find_entry<'a>(entries: &'a [DesktopEntry], app_id: &str) -> Option<&'a DesktopEntry> {
+    entries.iter().find(|entry| {
+        entry.id == app_id
+            || entry
+                .id
+                .strip_suffix(".desktop")
+                .is_some_and(|stem| stem == app_id)
+    })
+}
+
+fn shell_quote(arg: &str) -> String {
+    let is_safe = !arg.is_empty()
+        && arg
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "_-./:=@%+,".contains(c));
+    if is_safe {
+        arg.to_string()
+    } else {
+        format!("'{}'", arg.replace('\'', r"'\''"))
+    }
+}
+
+/// Formats a command for display, quoting arguments so it can be pasted
+/// into a shell.
+pub fn format_command(command: &LaunchCommand) -> String {
+    let argv = command
+        .argv
+        .iter()
+        .map(|arg| shell_quote(arg))
+        .collect::<Vec<_>>()
+        .join(" ");
+    match &command.working_dir {
+        Some(dir) => format!("{argv}\nWorking directory: {}", dir.display()),
+        None => argv,
+    }
+}
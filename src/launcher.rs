@@ -0,0 +1,101 @@
+use crate::catalog::Catalog;
+use crate::config::Config;
+use crate::desktop::{collect_desktop_entries, DesktopEntry};
+use crate::launch::{entry_environment, resolve_command, spawn_with_env, LaunchError};
+use crate::search::{search_entries, SearchMode, SearchScope};
+use std::path::PathBuf;
+
+/// Application discovery, search, and launching without the GTK interface,
+/// for other frontends that embed the launcher.
+#[derive(Clone, Debug, Default)]
+pub struct Launcher {
+    catalog: Catalog,
+    search_mode: SearchMode,
+    search_scope: SearchScope,
+}
+
+impl Launcher {
+    /// Wraps an already built catalog. Searches use the default
+    /// [`SearchMode`] and [`SearchScope`]; see [`Launcher::with_search`].
+    pub fn new(catalog: Catalog) -> Self {
+        Self {
+            catalog,
+            ..Default::default()
+        }
+    }
+
+    /// Scans `dirs`, or the XDG application directories when `None`, with
+    /// `config`'s scan settings; see [`collect_desktop_entries`]. Nothing is
+    /// read from the user's configuration, so the result depends only on the
+    /// arguments. To apply a blocklist, name overrides, or category rules,
+    /// build the [`Catalog`] from the scanned entries and use
+    /// [`Launcher::new`].
+    pub fn scan(dirs: Option<&[PathBuf]>, config: &Config) -> Self {
+        Self::new(Catalog::new(collect_desktop_entries(dirs, config)))
+    }
+
+    /// Sets how [`Launcher::search`] matches queries and which fields it
+    /// looks at.
+    pub fn with_search(mut self, mode: SearchMode, scope: SearchScope) -> Self {
+        self.search_mode = mode;
+        self.search_scope = scope;
+        self
+    }
+
+    /// The underlying catalog, for callers that need the category map.
+    pub fn catalog(&self) -> &Catalog {
+        &self.catalog
+    }
+
+    /// Every listed application, sorted by name.
+    pub fn entries(&self) -> &[DesktopEntry] {
+        &self.catalog.entries
+    }
+
+    /// The applications in sidebar category `category`, such as `"Office"`
+    /// (one of [`crate::desktop::CATEGORIES`]), sorted by name. Unknown or
+    /// empty categories give an empty list.
+    pub fn by_category(&self, category: &str) -> Vec<&DesktopEntry> {
+        self.catalog
+            .category_map
+            .get(category)
+            .into_iter()
+            .flatten()
+            .map(|&index| &self.catalog.entries[index])
+            .collect()
+    }
+
+    /// The applications matching `query`, best matches first.
+    pub fn search(&self, query: &str) -> Vec<&DesktopEntry> {
+        search_entries(
+            &self.catalog.entries,
+            query,
+            self.search_mode,
+            self.search_scope,
+        )
+        .into_iter()
+        .map(|index| &self.catalog.entries[index])
+        .collect()
+    }
+
+    /// The application whose windows have class `wm_class`, by its
+    /// `StartupWMClass` key compared ignoring ASCII case, for matching a
+    /// window back to the entry that launched it.
+    pub fn find_by_wm_class(&self, wm_class: &str) -> Option<&DesktopEntry> {
+        self.catalog.entries.iter().find(|entry| {
+            entry
+                .startup_wm_class
+                .as_deref()
+                .is_some_and(|class| class.eq_ignore_ascii_case(wm_class))
+        })
+    }
+
+    /// Starts `entry` by running its resolved `Exec` command, in a terminal
+    /// for `Terminal=true` apps and on the discrete GPU for
+    /// `PrefersNonDefaultGPU` apps when there is one. Wrappers from
+    /// `wrappers.conf`, the pre-launch hook, and D-Bus activation are left
+    /// to the caller.
+    pub fn launch(&self, entry: &DesktopEntry) -> Result<(), LaunchError> {
+        spawn_with_env(&resolve_command(entry)?, &entry_environment(entry))
+    }
+}
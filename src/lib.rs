@@ -1,2 +1,13 @@
+pub mod cache;
+pub mod catalog;
+pub mod category_tree;
+pub mod config;
 pub mod desktop;
+pub mod history;
+pub mod json;
+pub mod launch;
+pub mod launcher;
+pub mod logging;
+pub mod search;
+pub mod speech;
 pub mod ui;
@@ -0,0 +1,113 @@
+//! A tiny stderr logger controlled by the `--verbose` command-line flag.
+//!
+//! Errors are always printed. `--verbose` adds informational messages such
+//! as the directories scanned and launch outcomes; giving it twice, or
+//! `-vv`, adds per-file details such as why a desktop file was skipped.
+
+use std::fmt;
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::time::{Duration, Instant};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Level {
+    Error = 0,
+    Info = 1,
+    Debug = 2,
+}
+
+impl Level {
+    fn label(self) -> &'static str {
+        match self {
+            Self::Error => "error",
+            Self::Info => "info",
+            Self::Debug => "debug",
+        }
+    }
+}
+
+static VERBOSITY: AtomicU8 = AtomicU8::new(0);
+
+pub fn set_verbosity(verbosity: u8) {
+    VERBOSITY.store(verbosity, Ordering::Relaxed);
+}
+
+pub fn verbosity() -> u8 {
+    VERBOSITY.load(Ordering::Relaxed)
+}
+
+pub fn enabled(level: Level) -> bool {
+    level as u8 <= verbosity()
+}
+
+/// Starts timing a step for the `--verbose` timing lines. Returns `None` without
+/// reading the clock when they would not be printed.
+pub fn start_timer() -> Option<Instant> {
+    enabled(Level::Info).then(Instant::now)
+}
+
+/// Formats a step's duration for the timing lines, e.g. `12.3 ms`.
+pub fn format_duration(duration: Duration) -> String {
+    format!("{:.1} ms", duration.as_secs_f64() * 1000.0)
+}
+
+/// Returns how much a verbosity flag adds: `--verbose` is 1, `-vv` is 2,
+/// `-vvv` is 3, and so on. A lone `-v` is not one: it prints the version.
+pub fn verbosity_flag(arg: &str) -> Option<u8> {
+    if arg == "--verbose" {
+        return Some(1);
+    }
+    let count = arg.strip_prefix('-')?;
+    (count.len() > 1 && count.bytes().all(|b| b == b'v'))
+        .then(|| count.len().min(u8::MAX as usize) as u8)
+}
+
+pub fn write(level: Level, args: fmt::Arguments<'_>) {
+    eprintln!("{}: [{}] {args}", env!("CARGO_PKG_NAME"), level.label());
+}
+
+#[macro_export]
+macro_rules! log_error {
+    ($($arg:tt)*) => {
+        $crate::logging::write($crate::logging::Level::Error, format_args!($($arg)*))
+    };
+}
+
+#[macro_export]
+macro_rules! log_info {
+    ($($arg:tt)*) => {
+        if $crate::logging::enabled($crate::logging::Level::Info) {
+            $crate::logging::write($crate::logging::Level::Info, format_args!($($arg)*));
+        }
+    };
+}
+
+#[macro_export]
+macro_rules! log_debug {
+    ($($arg:tt)*) => {
+        if $crate::logging::enabled($crate::logging::Level::Debug) {
+            $crate::logging::write($crate::logging::Level::Debug, format_args!($($arg)*));
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn verbosity_flag_counts_repeated_v() {
+        assert_eq!(verbosity_flag("-v"), None);
+        assert_eq!(verbosity_flag("-vv"), Some(2));
+        assert_eq!(verbosity_flag("-vvv"), Some(3));
+        assert_eq!(verbosity_flag("--verbose"), Some(1));
+        assert_eq!(verbosity_flag("-"), None);
+        assert_eq!(verbosity_flag("-vx"), None);
+        assert_eq!(verbosity_flag("--version"), None);
+    }
+
+    #[test]
+    fn format_duration_uses_tenths_of_milliseconds() {
+        assert_eq!(format_duration(Duration::from_micros(12_345)), "12.3 ms");
+        assert_eq!(format_duration(Duration::ZERO), "0.0 ms");
+    }
+}
@@ -1,118 +1,1901 @@
-use access_launcher::desktop::{build_category_map, collect_desktop_entries};
+use access_launcher::catalog::Catalog;
+use access_launcher::category_tree::build_category_tree;
+use access_launcher::config::{
+    blocklist_path, is_first_run, load_category_rules, load_config, load_keymap,
+    name_overrides_path, pre_launch_hook_path, read_blocklist, read_name_overrides, read_wrappers,
+    save_config_value, wrappers_path, write_blocklist, write_name_overrides, CategoryOrder, Config,
+    Keymap, KeymapAction, ProgramView, RowDensity, KEYMAP_ACTIONS, LAUNCH_ACTION,
+};
+use access_launcher::desktop::{
+    add_broken_entries, apply_name_overrides, collect_desktop_entries,
+    collect_desktop_entries_with_report, dedup_symlinked_entries, desktop_id_owner,
+    entries_to_json, entry_diagnostics, explain_desktop_file, export_desktop_links,
+    filter_terminal_apps, find_desktop_files, format_diagnostics, group_by_raw_category,
+    mark_duplicate_names, mark_recently_installed, merge_duplicate_entries, new_app_indices,
+    parse_current_desktops, parse_desktop_entry_result, parse_entries_dirs, pin_categories,
+    remove_blocklisted, terminal_app_indices, visible_categories, DesktopEntry, DesktopParseError,
+    NEW_APPS_CATEGORY, TERMINAL_APPS_CATEGORY,
+};
+use access_launcher::history::{
+    load_last_category, save_last_category, History, MOST_USED_CATEGORY, RECENT_CATEGORY,
+};
+use access_launcher::launch::{
+    activate_dbus, add_prime_offload, dbus_name, detect_terminal, entry_environment, find_entry,
+    format_command, parse_extra_args, prime_offload, prime_offload_available, reap_child,
+    resolve_command, resolve_wrapped_command, run_fallback, run_pre_launch_hook,
+    run_pre_launch_hook_async, session_environment, spawn_child_with_env, spawn_with_env,
+    terminal_command, terminal_here_command, watch_exit, xdg_open_fallback, ActivationGuard,
+    ExitCallback, LaunchCommand, LaunchError, Terminal, FALLBACK_EXIT_TIMEOUT,
+    PRE_LAUNCH_HOOK_TIMEOUT,
+};
+use access_launcher::launcher::Launcher;
+use access_launcher::logging::{
+    enabled, format_duration, set_verbosity, start_timer, verbosity_flag, Level,
+};
+use access_launcher::search::search_entries;
+use access_launcher::speech;
 use access_launcher::ui::{
-    append_text_row, build_list_box, build_pane, show_error_dialog, update_program_list,
+    accessibility_disabled_reason, add_context_menu_trigger, add_focused_item_shortcut,
+    add_grid_context_menu_trigger, add_icon_search_paths, add_list_navigation,
+    add_multi_select_keys, add_pane_crossing, add_quick_select_badges, announce,
+    apply_accessibility_settings, apply_row_spacing, build_category_tree_toggle,
+    build_category_tree_view, build_compact_mode_toggle, build_launch_context, build_list_box,
+    build_pane, build_program_grid, build_program_grid_toggle, build_quick_launch,
+    build_search_entry, build_search_scope_dropdown, build_status_label, category_rows,
+    clear_list_box, connect_category_tree_keys, describe_batch_failures, describe_focused_program,
+    describe_launch_question, describe_launched, describe_program_count, describe_search_count,
+    describe_selection_count, describe_shortcut, focus_grid, focus_list, format_batch_failures,
+    grid_tile_in_first_column, is_multi_select, populate_category_list, populate_category_tree,
+    quick_select_index, row_alternate_targets, row_launch_target, select_program_row,
+    select_program_tile, selected_launch_targets, selected_search_scope, selected_tree_node,
+    set_exec_subtitles, set_multi_select, set_widget_margin, show_about_dialog,
+    show_arguments_dialog, show_context_menu, show_diagnostics_popover, show_empty_state,
+    show_error_dialog, show_first_run_dialog, show_launch_confirmation, show_loading,
+    show_rename_dialog, show_shortcuts_dialog, track_input_time, update_grouped_program_list,
+    update_program_grid, update_program_list, AccessibilitySetup, Highlight, IdleTimeout,
+    LaunchTarget, ProgramPositions, NO_APPLICATIONS_MESSAGE, QUICK_LAUNCH_LIMIT,
 };
+use access_launcher::{log_debug, log_error, log_info};
 use futures_channel::oneshot;
 use gtk4::prelude::*;
-use gtk4::{self as gtk, gio, glib, Application, ApplicationWindow, Orientation};
+use gtk4::{self as gtk, gdk, gio, glib, Application, ApplicationWindow, Orientation};
+use std::cell::RefCell;
+use std::collections::{HashMap, VecDeque};
 use std::env;
+use std::path::{Path, PathBuf};
+use std::process::Child;
 use std::rc::Rc;
+use std::sync::OnceLock;
 use std::thread;
+use std::time::{Instant, SystemTime};
+
+type SharedConfig = Rc<RefCell<Config>>;
+type SharedCatalog = Rc<RefCell<Catalog>>;
+type SharedHistory = Rc<RefCell<History>>;
+
+const USAGE: &str = "Usage: {name} [OPTIONS] [FILE.desktop]
 
-fn check_args() -> bool {
+Options:
+  -h, --help               Show this help message
+  -v, --version            Show version information
+      --verbose            Log scanned directories and launches to stderr;
+                           repeat it (or use -vv) to also log skipped desktop files
+      --launch <APP-ID>    Launch an application by desktop ID and exit
+      --print-exec <APP-ID>
+                           Print the command --launch would run, without running it
+      --blocklist          Print the desktop IDs hidden with \"Hide this app\"
+      --clear-blocklist    Show all hidden applications again
+      --clear-history      Forget launched apps (the Recent and Most Used lists)
+      --why <PATH-OR-ID>   Explain why a desktop file is or is not listed
+      --export-menu <DIR>  Link every listed application's desktop file into DIR
+      --list               Print each application's desktop ID and name
+      --json               With --list, print the applications as JSON
+      --entries-dir <DIRS> Scan only these comma-separated directories instead
+                           of the standard application directories
+
+Running without options starts the application. Given a FILE.desktop, it
+launches that file without scanning and exits.
+
+Exit status:
+  0  Success
+  1  Other failure, such as a file that cannot be written
+  2  No application or desktop file found (--launch, --print-exec, --why, FILE)
+  3  The launch failed or the pre-launch hook refused it
+  4  Invalid arguments, desktop file, Exec line, or wrapper";
+
+/// Exit codes of the command-line paths, as listed in [`USAGE`].
+const EXIT_SUCCESS: i32 = 0;
+const EXIT_FAILURE: i32 = 1;
+const EXIT_NOT_FOUND: i32 = 2;
+const EXIT_LAUNCH_FAILED: i32 = 3;
+const EXIT_INVALID: i32 = 4;
+
+/// Heading of the program list in compact mode, which lists every app.
+const ALL_APPLICATIONS: &str = "All applications";
+
+/// Directories given with `--entries-dir`, replacing the standard search path.
+static ENTRIES_DIRS: OnceLock<Vec<PathBuf>> = OnceLock::new();
+
+fn entries_dirs() -> Option<&'static [PathBuf]> {
+    ENTRIES_DIRS.get().map(Vec::as_slice)
+}
+
+/// Handles command-line flags, returning an exit code when the GUI should not start.
+fn check_args() -> Option<i32> {
+    let args: Vec<String> = env::args().skip(1).collect();
     let mut version_found = false;
-    for arg in env::args().skip(1) {
-        if arg == "-h" || arg == "--help" {
+    let mut verbosity = 0u8;
+    let mut app_command = None;
+    let mut list = false;
+    let mut json = false;
+    let mut why = None;
+    let mut export_dir = None;
+    let mut desktop_file = None;
+    let mut scan_dirs = Vec::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-h" | "--help" => {
+                println!("{}", USAGE.replace("{name}", env!("CARGO_PKG_NAME")));
+                return Some(EXIT_SUCCESS);
+            }
+            "-v" | "--version" => version_found = true,
+            "--list" => list = true,
+            "--blocklist" => return Some(print_blocklist()),
+            "--clear-blocklist" => return Some(clear_blocklist()),
+            "--clear-history" => return Some(clear_history()),
+            "--json" => json = true,
+            "--export-menu" => {
+                let Some(dir) = args.next() else {
+                    eprintln!("{arg} requires a target directory");
+                    return Some(EXIT_INVALID);
+                };
+                export_dir = Some(PathBuf::from(dir));
+            }
+            "--entries-dir" => {
+                let Some(dirs) = args.next() else {
+                    eprintln!("{arg} requires a directory");
+                    return Some(EXIT_INVALID);
+                };
+                scan_dirs.extend(parse_entries_dirs(dirs));
+            }
+            "--why" => {
+                let Some(target) = args.next() else {
+                    eprintln!("{arg} requires a desktop file path or ID");
+                    return Some(EXIT_INVALID);
+                };
+                why = Some(target.clone());
+            }
+            "--launch" | "--print-exec" => {
+                let Some(app_id) = args.next() else {
+                    eprintln!("{arg} requires an application ID");
+                    return Some(EXIT_INVALID);
+                };
+                app_command.get_or_insert((app_id.clone(), arg == "--print-exec"));
+            }
+            _ => {
+                if let Some(count) = verbosity_flag(arg) {
+                    verbosity = verbosity.saturating_add(count);
+                } else if arg.ends_with(".desktop") && !arg.starts_with('-') {
+                    desktop_file.get_or_insert(arg.clone());
+                }
+            }
+        }
+    }
+    set_verbosity(verbosity);
+    if !scan_dirs.is_empty() {
+        let _ = ENTRIES_DIRS.set(scan_dirs);
+    }
+
+    if version_found {
+        println!("{} {}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION"));
+        return Some(EXIT_SUCCESS);
+    }
+    if let Some(target) = why {
+        return Some(explain_why(&target));
+    }
+    if let Some(dir) = export_dir {
+        return Some(export_menu(&dir));
+    }
+    if list {
+        let launcher = Launcher::new(Catalog::new(scan_user_entries()));
+        if json {
+            print!("{}", entries_to_json(launcher.entries()));
+        } else {
+            for entry in launcher.entries() {
+                println!("{}\t{}", entry.id, entry.name);
+            }
+        }
+        return Some(EXIT_SUCCESS);
+    }
+    if let Some((app_id, print_only)) = app_command {
+        return Some(run_app_command(&app_id, print_only));
+    }
+    if let Some(path) = desktop_file {
+        return Some(run_desktop_file(Path::new(&path)));
+    }
+    None
+}
+
+/// Prints why the desktop file at `target`, or the files providing desktop
+/// ID `target`, would or would not be listed.
+fn explain_why(target: &str) -> i32 {
+    let path = Path::new(target);
+    let paths = if path.is_file() {
+        vec![path.to_path_buf()]
+    } else {
+        find_desktop_files(target, entries_dirs(), &load_config().excluded_subdirs)
+    };
+    if paths.is_empty() {
+        eprintln!("No desktop file found at {target} or with that desktop ID");
+        return EXIT_NOT_FOUND;
+    }
+    let current_lang = env::var("LANG").ok();
+    let current_desktops = env::var("XDG_CURRENT_DESKTOP")
+        .ok()
+        .map(|value| parse_current_desktops(&value));
+    let blocklist = blocklist_path()
+        .map(|path| read_blocklist(&path))
+        .unwrap_or_default();
+    let owner = desktop_id_owner(&paths, current_lang.as_deref(), current_desktops.as_deref());
+    for (index, path) in paths.iter().enumerate() {
+        match owner {
+            _ if index == 0 => {}
+            Some(owner) if owner < index => {
+                println!("\nShadowed by {}, so never listed:", paths[owner].display());
+            }
+            _ => println!("\nRead next, since the Exec of the file above cannot run:"),
+        }
+        let mut fields =
+            explain_desktop_file(path, current_lang.as_deref(), current_desktops.as_deref());
+        let id = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .unwrap_or_default();
+        if blocklist.contains(id) {
+            // The blocklist is applied after parsing, so it overrides a
+            // file that would otherwise be shown.
+            fields.pop();
+            fields.push((
+                "Result",
+                "hidden: on the blocklist (see --blocklist)".to_string(),
+            ));
+        }
+        print!("{}", format_diagnostics(&fields));
+    }
+    if paths.len() > 1 {
+        match owner {
+            Some(owner) => println!("\nThe desktop ID comes from {}", paths[owner].display()),
+            None => println!("\nNo file provides the desktop ID: none has an Exec that can run"),
+        }
+    }
+    EXIT_SUCCESS
+}
+
+/// Drops the apps on the user's blocklist from `entries` and applies the
+/// user's display-name overrides, on top of a library scan.
+fn apply_user_lists(entries: &mut Vec<DesktopEntry>) {
+    if let Some(path) = blocklist_path() {
+        remove_blocklisted(entries, &read_blocklist(&path));
+    }
+    if let Some(path) = name_overrides_path() {
+        apply_name_overrides(entries, &read_name_overrides(&path));
+    }
+}
+
+/// Scans the application directories with the user's settings, blocklist,
+/// and name overrides, as the command-line flags list them.
+fn scan_user_entries() -> Vec<DesktopEntry> {
+    let mut entries = collect_desktop_entries(entries_dirs(), &load_config());
+    apply_user_lists(&mut entries);
+    entries
+}
+
+/// Writes the listed applications to `dir` as symlinks to their desktop files.
+fn export_menu(dir: &Path) -> i32 {
+    let entries = scan_user_entries();
+    match export_desktop_links(&entries, dir) {
+        Ok(skipped) => {
+            for path in &skipped {
+                eprintln!("Skipped {}: a regular file is in the way", path.display());
+            }
+            let exported = entries.iter().filter(|entry| !entry.appimage).count();
             println!(
-                "Usage: {name} [OPTIONS]\n\nOptions:\n  -h, --help     Show this help message\n  -v, --version  Show version information\n\nRunning without options starts the application.",
-                name = env!("CARGO_PKG_NAME")
+                "Linked {} applications into {}",
+                exported - skipped.len(),
+                dir.display()
             );
-            return true;
+            EXIT_SUCCESS
         }
-        if arg == "-v" || arg == "--version" {
-            version_found = true;
+        Err(err) => {
+            eprintln!("Failed to export to {}: {err}", dir.display());
+            EXIT_FAILURE
         }
     }
+}
 
-    if version_found {
-        println!("{} {}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION"));
-        return true;
+fn print_blocklist() -> i32 {
+    if let Some(path) = blocklist_path() {
+        for id in read_blocklist(&path) {
+            println!("{id}");
+        }
     }
-    false
+    EXIT_SUCCESS
 }
 
-fn main() {
-    if check_args() {
+fn clear_blocklist() -> i32 {
+    let Some(path) = blocklist_path() else {
+        return EXIT_SUCCESS;
+    };
+    match write_blocklist(&path, &Default::default()) {
+        Ok(()) => EXIT_SUCCESS,
+        Err(err) => {
+            eprintln!("Failed to clear {}: {err}", path.display());
+            EXIT_FAILURE
+        }
+    }
+}
+
+fn clear_history() -> i32 {
+    match History::default().save() {
+        Ok(()) => EXIT_SUCCESS,
+        Err(err) => {
+            eprintln!("Failed to clear launch history: {err}");
+            EXIT_FAILURE
+        }
+    }
+}
+
+/// Resolves `app_id` and either prints or spawns its command.
+///
+/// Both paths share `resolve_command` so the printed command is exactly what
+/// `--launch` runs. `DBusActivatable` apps are activated over D-Bus first,
+/// and only fall back to that command if activation fails. Apps with a
+/// wrapper in `wrappers.conf` always run the wrapped command.
+fn run_app_command(app_id: &str, print_only: bool) -> i32 {
+    let launcher = Launcher::new(Catalog::new(scan_user_entries()));
+    let Some(entry) = find_entry(launcher.entries(), app_id) else {
+        eprintln!("No application found with ID {app_id}");
+        return EXIT_NOT_FOUND;
+    };
+    run_entry_command(entry, app_id, print_only)
+}
+
+/// Parses the desktop file at `path` and launches it like `--launch`,
+/// without scanning the application directories. A file the scan would
+/// skip is reported on stderr with the reason: a missing or unreadable
+/// file exits with [`EXIT_NOT_FOUND`], any other with [`EXIT_INVALID`].
+fn run_desktop_file(path: &Path) -> i32 {
+    let current_lang = env::var("LANG").ok();
+    let current_desktops = env::var("XDG_CURRENT_DESKTOP")
+        .ok()
+        .map(|value| parse_current_desktops(&value));
+    let mut line_buf = String::new();
+    match parse_desktop_entry_result(
+        path,
+        current_lang.as_deref(),
+        current_desktops.as_deref(),
+        &mut line_buf,
+    ) {
+        Ok(entry) => run_entry_command(&entry, &path.display().to_string(), false),
+        Err(err) => {
+            eprintln!("Cannot launch {}: it {err}", path.display());
+            match err {
+                DesktopParseError::Io(_) => EXIT_NOT_FOUND,
+                _ => EXIT_INVALID,
+            }
+        }
+    }
+}
+
+/// Prints or runs the command for `entry`; `label` names it in messages.
+fn run_entry_command(entry: &DesktopEntry, label: &str, print_only: bool) -> i32 {
+    let wrapper = launch_wrapper(&entry.id);
+    let resolved = match &wrapper {
+        Some(wrapper) => parse_extra_args(wrapper)
+            .and_then(|wrapper| resolve_wrapped_command(entry, &wrapper, &[])),
+        None => resolve_command(entry),
+    };
+    let command = match resolved {
+        Ok(command) => command,
+        Err(err) => {
+            eprintln!("Failed to resolve {label}: {err}");
+            return EXIT_INVALID;
+        }
+    };
+
+    if print_only {
+        println!("{}", format_command(&command));
+        return EXIT_SUCCESS;
+    }
+    if let Err(err) = check_pre_launch_hook(&entry.id, &format_command(&command)) {
+        eprintln!("Failed to launch {label}: {err}");
+        return EXIT_LAUNCH_FAILED;
+    }
+    // D-Bus activation cannot run a wrapper, so wrapped apps skip it.
+    if let Some(name) = dbus_name(entry).filter(|_| wrapper.is_none()) {
+        match activate_dbus(name) {
+            Ok(()) => {
+                log_info!("Activated {label} over D-Bus as {name}");
+                return EXIT_SUCCESS;
+            }
+            Err(err) => log_info!("D-Bus activation of {name} failed, running Exec: {err}"),
+        }
+    }
+    match spawn_with_env(&command, &entry_environment(entry)) {
+        Ok(()) => {
+            log_info!("Launched {label}: {}", format_command(&command));
+            EXIT_SUCCESS
+        }
+        Err(err) => {
+            eprintln!("Failed to launch {label}: {err}");
+            EXIT_LAUNCH_FAILED
+        }
+    }
+}
+
+/// Timeout for asking the session bus where the accessibility bus is.
+const A11Y_BUS_TIMEOUT_MS: i32 = 2000;
+
+/// With `--verbose`, warns when screen readers cannot reach the window: GTK's
+/// accessibility is turned off, or no AT-SPI accessibility bus is running.
+/// The labels and descriptions set on widgets are then lost, but the
+/// launcher works as before. The bus is queried asynchronously, so startup
+/// never waits on it.
+fn check_accessibility_bus() {
+    if !enabled(Level::Info) {
+        return;
+    }
+    if let Some(reason) = accessibility_disabled_reason(|key| env::var(key).ok()) {
+        log_info!("{reason}");
         return;
     }
+    if env::var_os("AT_SPI_BUS_ADDRESS").is_some() {
+        return;
+    }
+    const HINT: &str = "screen readers will not hear the launcher. Start at-spi-bus-launcher \
+                        (usually done by the desktop session), or turn on accessibility in \
+                        the desktop settings";
+    gio::bus_get(
+        gio::BusType::Session,
+        gio::Cancellable::NONE,
+        |connection| {
+            let connection = match connection {
+                Ok(connection) => connection,
+                Err(err) => {
+                    log_info!("No D-Bus session bus ({err}), so {HINT}");
+                    return;
+                }
+            };
+            // Asking for the address starts the bus if it is D-Bus activatable,
+            // as GTK itself would.
+            connection.call(
+                Some("org.a11y.Bus"),
+                "/org/a11y/bus",
+                "org.a11y.Bus",
+                "GetAddress",
+                None,
+                Some(glib::VariantTy::new("(s)").expect("valid type string")),
+                gio::DBusCallFlags::NONE,
+                A11Y_BUS_TIMEOUT_MS,
+                gio::Cancellable::NONE,
+                |reply| match reply {
+                    Ok(_) => log_debug!("The AT-SPI accessibility bus is available"),
+                    Err(err) => log_info!("No AT-SPI accessibility bus ({err}), so {HINT}"),
+                },
+            );
+        },
+    );
+}
+
+/// Why a launch failed, as shown in an error dialog.
+struct LaunchFailure {
+    /// App name, or the desktop file path when it could not be read.
+    name: String,
+    title: String,
+    details: String,
+}
+
+/// Launches `target` via GIO and records it in the launch history, then
+/// calls `done` with whether it started; failures are shown in a dialog.
+fn launch_target(
+    window: &ApplicationWindow,
+    target: &LaunchTarget,
+    event_time: u32,
+    catalog: &SharedCatalog,
+    history: &SharedHistory,
+    done: impl FnOnce(bool) + 'static,
+) {
+    let parent = window.clone();
+    start_target(
+        window,
+        target,
+        event_time,
+        catalog,
+        history,
+        move |result| {
+            let launched = match result {
+                Ok(name) => {
+                    speech::speak(&describe_launched(&[name]));
+                    true
+                }
+                Err(failure) => {
+                    show_error_dialog(&parent, &failure.title, &failure.details);
+                    false
+                }
+            };
+            done(launched);
+        },
+    );
+}
+
+/// Launches every target in order, then reports all failures in a single
+/// dialog and calls `done` with whether every launch succeeded.
+fn launch_targets(
+    window: &ApplicationWindow,
+    targets: &[LaunchTarget],
+    event_time: u32,
+    catalog: &SharedCatalog,
+    history: &SharedHistory,
+    done: impl FnOnce(bool) + 'static,
+) {
+    let total = targets.len();
+    let parent = window.clone();
+    start_targets(
+        window,
+        targets.iter().cloned().collect(),
+        event_time,
+        catalog,
+        history,
+        (Vec::new(), Vec::new()),
+        move |(launched, failures)| {
+            speech::speak(&describe_launched(&launched));
+            if !failures.is_empty() {
+                show_error_dialog(
+                    &parent,
+                    &describe_batch_failures(failures.len(), total),
+                    &format_batch_failures(&failures),
+                );
+            }
+            done(failures.is_empty());
+        },
+    );
+}
+
+/// Names launched so far in a batch, and `(name, details)` for each failure.
+type BatchResults = (Vec<String>, Vec<(String, String)>);
+
+/// Starts the `pending` targets one at a time, each once the one before has
+/// got past the pre-launch hook, then calls `done` with the `results`.
+fn start_targets(
+    window: &ApplicationWindow,
+    mut pending: VecDeque<LaunchTarget>,
+    event_time: u32,
+    catalog: &SharedCatalog,
+    history: &SharedHistory,
+    mut results: BatchResults,
+    done: impl FnOnce(BatchResults) + 'static,
+) {
+    let Some(target) = pending.pop_front() else {
+        done(results);
+        return;
+    };
+    let next_window = window.clone();
+    let next_catalog = Rc::clone(catalog);
+    let next_history = Rc::clone(history);
+    start_target(
+        window,
+        &target,
+        event_time,
+        catalog,
+        history,
+        move |result| {
+            match result {
+                Ok(name) => results.0.push(name),
+                Err(failure) => results.1.push((failure.name, failure.details)),
+            }
+            start_targets(
+                &next_window,
+                pending,
+                event_time,
+                &next_catalog,
+                &next_history,
+                results,
+                done,
+            );
+        },
+    );
+}
+
+/// Like [`launch_target`], but hands the outcome to `done` instead of
+/// showing it: the app's name on success, or the failure.
+///
+/// GIO already activates `DBusActivatable` apps over D-Bus, so only the
+/// `--launch` path needs [`activate_dbus`]. Apps with a wrapper in
+/// `wrappers.conf` are spawned directly instead, since GIO cannot add one,
+/// and so are apps run on the discrete GPU, which need PRIME variables.
+fn start_target(
+    window: &ApplicationWindow,
+    target: &LaunchTarget,
+    event_time: u32,
+    catalog: &SharedCatalog,
+    history: &SharedHistory,
+    done: impl FnOnce(Result<String, LaunchFailure>) + 'static,
+) {
+    let discrete_gpu = target.discrete_gpu && prime_offload_available();
+    if target.discrete_gpu && !discrete_gpu {
+        log_info!("No second GPU found; launching {} normally", target.id);
+    }
+    // An AppImage has no desktop file for GIO to launch, so it is spawned
+    // directly, like a wrapped app with an empty wrapper.
+    let wrapper = launch_wrapper(&target.id).or_else(|| {
+        (discrete_gpu
+            || target_entry(&catalog.borrow().entries, target).is_some_and(|entry| entry.appimage))
+        .then(String::new)
+    });
+    if let Some(wrapper) = wrapper {
+        let target = LaunchTarget {
+            discrete_gpu,
+            ..target.clone()
+        };
+        launch_wrapped(
+            window, &target, &wrapper, event_time, catalog, history, done,
+        );
+        return;
+    }
+    let path = target.path.clone();
+    let Some(app_info) = gio::DesktopAppInfo::from_filename(&path) else {
+        log_error!("Failed to load desktop entry: {path}");
+        done(Err(LaunchFailure {
+            name: path.clone(),
+            title: "Failed to load application".to_string(),
+            details: format!("Could not read desktop entry at {path}"),
+        }));
+        return;
+    };
+
+    let command_line = target_entry(&catalog.borrow().entries, target)
+        .and_then(|entry| resolve_command(&entry).ok())
+        .map(|command| format_command(&command))
+        .or_else(|| Some(app_info.commandline()?.display().to_string()))
+        .unwrap_or_default();
+    let window = window.clone();
+    let target = target.clone();
+    let catalog = Rc::clone(catalog);
+    let history = Rc::clone(history);
+    let id = target.id.clone();
+    after_pre_launch_hook(&id, &command_line, move |allowed| {
+        let app_name = app_info.name().to_string();
+        if let Err(err) = allowed {
+            log_info!("Not launching {path}: {err}");
+            done(Err(LaunchFailure {
+                title: format!("Did not launch {app_name}"),
+                name: app_name,
+                details: err.to_string(),
+            }));
+            return;
+        }
+        let files: Vec<gio::File> = Vec::new();
+        let launch_context = build_launch_context(&window, target.startup_notify, event_time);
+        match app_info.launch(&files, Some(&launch_context)) {
+            Ok(()) => {
+                log_info!("Launched {path}");
+                record_launch(&history, &target.id);
+                done(Ok(app_name));
+            }
+            Err(err) => {
+                log_error!("Failed to launch {path}: {err}");
+                let entry = target_entry(&catalog.borrow().entries, &target);
+                launch_fallback(
+                    &window,
+                    &target,
+                    entry.as_ref(),
+                    event_time,
+                    err.message(),
+                    done,
+                );
+            }
+        }
+    });
+}
+
+/// Runs the user's pre-launch hook, if one is installed, for app `id`
+/// about to run `command_line`. See [`run_pre_launch_hook`]. This blocks,
+/// so only the command line uses it.
+fn check_pre_launch_hook(id: &str, command_line: &str) -> Result<(), LaunchError> {
+    match pre_launch_hook_path() {
+        Some(hook) => run_pre_launch_hook(&hook, id, command_line, PRE_LAUNCH_HOOK_TIMEOUT),
+        None => Ok(()),
+    }
+}
+
+/// Like [`check_pre_launch_hook`], but calls `then` with the verdict once
+/// the hook exits, keeping the window responsive meanwhile. See
+/// [`run_pre_launch_hook_async`].
+fn after_pre_launch_hook(
+    id: &str,
+    command_line: &str,
+    then: impl FnOnce(Result<(), LaunchError>) + 'static,
+) {
+    match pre_launch_hook_path() {
+        Some(hook) => {
+            run_pre_launch_hook_async(&hook, id, command_line, PRE_LAUNCH_HOOK_TIMEOUT, then)
+        }
+        None => then(Ok(())),
+    }
+}
+
+fn record_launch(history: &SharedHistory, id: &str) {
+    let mut history = history.borrow_mut();
+    history.record_launch(id);
+    if let Err(err) = history.save() {
+        log_error!("Failed to save launch history: {err}");
+    }
+}
+
+/// Empties and saves the launch history, updating "Recent" or "Most Used"
+/// right away when one is shown.
+fn clear_launch_history(
+    config: &SharedConfig,
+    ui: &Ui,
+    catalog: &SharedCatalog,
+    history: &SharedHistory,
+) {
+    history.borrow_mut().clear();
+    let saved = history.borrow().save();
+    refresh_program_view(&config.borrow(), ui, catalog, history);
+    match saved {
+        Ok(()) => announce(&ui.status, "History cleared"),
+        Err(err) => {
+            log_error!("Failed to save launch history: {err}");
+            announce(&ui.status, &format!("Failed to clear history: {err}"));
+        }
+    }
+}
+
+/// The wrapper command configured for `id` in `wrappers.conf`, if any. The
+/// file is read on each launch so edits apply right away.
+fn launch_wrapper(id: &str) -> Option<String> {
+    read_wrappers(&wrappers_path()?).remove(id)
+}
+
+/// Spawns `target` with `wrapper` in front of its expanded Exec, keeping
+/// its `Terminal` and `Path` settings, on the discrete GPU when its
+/// `discrete_gpu` is set, then calls `done` like [`start_target`].
+fn launch_wrapped(
+    window: &ApplicationWindow,
+    target: &LaunchTarget,
+    wrapper: &str,
+    event_time: u32,
+    catalog: &SharedCatalog,
+    history: &SharedHistory,
+    done: impl FnOnce(Result<String, LaunchFailure>) + 'static,
+) {
+    let Some(entry) = target_entry(&catalog.borrow().entries, target) else {
+        done(Err(LaunchFailure {
+            name: target.id.clone(),
+            title: format!("Failed to launch {}", target.id),
+            details: "The application is no longer listed; reload and try again.".to_string(),
+        }));
+        return;
+    };
+    let resolved = parse_extra_args(wrapper)
+        .and_then(|wrapper| resolve_wrapped_command(&entry, &wrapper, &[]));
+    let spawn_window = window.clone();
+    let app_path = entry.path.clone();
+    let id = entry.id.clone();
+    let (startup_notify, discrete_gpu) = (target.startup_notify, target.discrete_gpu);
+    let window = window.clone();
+    let target = target.clone();
+    let history = Rc::clone(history);
+    let finish = move |result: Result<LaunchCommand, LaunchError>| match result {
+        Ok(command) => {
+            log_info!("Launched {}: {}", target.id, format_command(&command));
+            record_launch(&history, &target.id);
+            done(Ok(entry.name));
+        }
+        Err(err) => {
+            log_error!("Failed to launch {}: {err}", target.id);
+            // A launch the pre-launch hook stopped stays stopped.
+            if err.is_from_hook() {
+                done(Err(LaunchFailure {
+                    title: format!("Failed to launch {}", entry.name),
+                    name: entry.name,
+                    details: err.to_string(),
+                }));
+            } else {
+                let error = err.to_string();
+                launch_fallback(&window, &target, Some(&entry), event_time, &error, done);
+            }
+        }
+    };
+    let command = match resolved {
+        Ok(command) => command,
+        Err(err) => return finish(Err(err)),
+    };
+    after_pre_launch_hook(&id, &format_command(&command), move |allowed| {
+        let spawned = allowed.and_then(|()| {
+            spawn_child_in_session(
+                &spawn_window,
+                &app_path,
+                startup_notify,
+                event_time,
+                discrete_gpu,
+                &command,
+            )
+        });
+        finish(spawned.map(|child| {
+            reap_child(child);
+            command
+        }));
+    });
+}
+
+/// Opens `entry` with [`xdg_open_fallback`] once the launch of `target`
+/// failed with `error`, then calls `done` with the failure to show: the
+/// app did not start either way, so nothing is recorded as launched, and
+/// the details say what became of the fallback.
+fn launch_fallback(
+    window: &ApplicationWindow,
+    target: &LaunchTarget,
+    entry: Option<&DesktopEntry>,
+    event_time: u32,
+    error: &str,
+    done: impl FnOnce(Result<String, LaunchFailure>) + 'static,
+) {
+    let name = entry.map_or_else(|| target.id.clone(), |entry| entry.name.clone());
+    let id = target.id.clone();
+    let spawn = |command: &LaunchCommand, exited: ExitCallback| {
+        let spawned = spawn_child_in_session(
+            window,
+            Path::new(&target.path),
+            target.startup_notify,
+            event_time,
+            false,
+            command,
+        );
+        match spawned {
+            Ok(child) => watch_exit(child, FALLBACK_EXIT_TIMEOUT, exited),
+            Err(err) => exited(Err(err)),
+        }
+    };
+    run_fallback(
+        error,
+        entry.and_then(xdg_open_fallback),
+        spawn,
+        move |details| {
+            log_info!("Fallback for {id}: {details}");
+            done(Err(LaunchFailure {
+                title: format!("Failed to launch {name}"),
+                name,
+                details,
+            }));
+        },
+    );
+}
+
+/// Spawns `command` for the app at `app_path` with the window's session
+/// variables and an activation token from its launch context, so the app
+/// shows up on this display and can take focus like GIO launches do. With
+/// `discrete_gpu`, the PRIME offload variables are set too. The child goes
+/// to [`watch_exit`] or [`reap_child`], so it does not linger as a zombie.
+fn spawn_child_in_session(
+    window: &ApplicationWindow,
+    app_path: &Path,
+    startup_notify: bool,
+    event_time: u32,
+    discrete_gpu: bool,
+    command: &LaunchCommand,
+) -> Result<Child, LaunchError> {
+    let launch_context = build_launch_context(window, startup_notify, event_time);
+    let context_env: HashMap<String, String> = launch_context
+        .environment()
+        .iter()
+        .filter_map(|var| var.to_str()?.split_once('='))
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .collect();
+    let token = gio::DesktopAppInfo::from_filename(app_path)
+        .and_then(|info| launch_context.startup_notify_id(&info, &[]));
+    let display_name = WidgetExt::display(window).name();
+    let mut vars = session_environment(
+        |key| context_env.get(key).cloned().or_else(|| env::var(key).ok()),
+        Some(display_name.as_str()),
+        token.as_deref(),
+    );
+    if let Some(offload) = prime_offload().filter(|_| discrete_gpu) {
+        add_prime_offload(&mut vars, offload);
+    }
+    let result = spawn_child_with_env(command, &vars);
+    if let (Err(_), Some(token)) = (&result, &token) {
+        launch_context.launch_failed(token);
+    }
+    result
+}
+
+/// Finds the entry behind `target`, including merged alternates.
+fn target_entry(entries: &[DesktopEntry], target: &LaunchTarget) -> Option<DesktopEntry> {
+    entries
+        .iter()
+        .flat_map(|entry| std::iter::once(entry).chain(&entry.alternates))
+        .find(|entry| entry.path.to_string_lossy() == target.path)
+        .cloned()
+}
+
+/// Asks for extra arguments, then spawns `target`'s expanded Exec with them
+/// appended. A parse error keeps the dialog open; a launch error is shown
+/// in a dialog like any other failed launch.
+fn launch_target_with_arguments(
+    window: &ApplicationWindow,
+    target: &LaunchTarget,
+    catalog: &SharedCatalog,
+    history: &SharedHistory,
+) {
+    let Some(entry) = target_entry(&catalog.borrow().entries, target) else {
+        return;
+    };
+    let parent = window.clone();
+    let history = Rc::clone(history);
+    let id = target.id.clone();
+    let name = entry.name.clone();
+    let discrete_gpu = target.discrete_gpu && prime_offload_available();
+    show_arguments_dialog(window, &name, move |text| {
+        let extra_args =
+            parse_extra_args(text).map_err(|err| format!("Invalid arguments: {err}"))?;
+        let wrapper = launch_wrapper(&id).unwrap_or_default();
+        let resolved = parse_extra_args(&wrapper)
+            .and_then(|wrapper| resolve_wrapped_command(&entry, &wrapper, &extra_args));
+        let spawn_window = parent.clone();
+        let (app_path, startup_notify) = (entry.path.clone(), entry.startup_notify);
+        let entry_id = entry.id.clone();
+        let parent = parent.clone();
+        let history = Rc::clone(&history);
+        let id = id.clone();
+        let name = entry.name.clone();
+        let finish = move |result: Result<LaunchCommand, LaunchError>| match result {
+            Ok(command) => {
+                log_info!("Launched {id}: {}", format_command(&command));
+                record_launch(&history, &id);
+                speech::speak(&describe_launched(std::slice::from_ref(&name)));
+            }
+            Err(err) => {
+                log_error!("Failed to launch {id}: {err}");
+                show_error_dialog(
+                    &parent,
+                    &format!("Failed to launch {name}"),
+                    &err.to_string(),
+                );
+            }
+        };
+        let command = match resolved {
+            Ok(command) => command,
+            Err(err) => {
+                finish(Err(err));
+                return Ok(());
+            }
+        };
+        after_pre_launch_hook(&entry_id, &format_command(&command), move |allowed| {
+            let spawned = allowed.and_then(|()| {
+                spawn_child_in_session(
+                    &spawn_window,
+                    &app_path,
+                    startup_notify,
+                    0,
+                    discrete_gpu,
+                    &command,
+                )
+            });
+            finish(spawned.map(|child| {
+                reap_child(child);
+                command
+            }));
+        });
+        Ok(())
+    });
+}
+
+/// Shows the diagnostics popover for a program row or grid tile.
+fn show_diagnostics(row: &gtk::Widget, catalog: &SharedCatalog) {
+    let Some(target) = row_launch_target(row) else {
+        return;
+    };
+    let Some(entry) = target_entry(&catalog.borrow().entries, &target) else {
+        return;
+    };
+    let fields = entry_diagnostics(&entry, &catalog.borrow().category_rules);
+    show_diagnostics_popover(row, &fields, format_diagnostics(&fields));
+}
+
+/// Opens a terminal in the directory `target` runs from, for troubleshooting.
+fn open_terminal_here(
+    window: &ApplicationWindow,
+    target: &LaunchTarget,
+    terminal: &Terminal,
+    catalog: &SharedCatalog,
+) {
+    let Some(entry) = target_entry(&catalog.borrow().entries, target) else {
+        return;
+    };
+    let command = terminal_here_command(&entry, terminal);
+    match spawn_child_in_session(window, &entry.path, true, 0, false, &command) {
+        Ok(child) => {
+            reap_child(child);
+            log_info!("Opened terminal: {}", format_command(&command));
+        }
+        Err(err) => {
+            log_error!("Failed to open {}: {err}", terminal.program);
+            show_error_dialog(window, "Failed to open terminal", &err.to_string());
+        }
+    }
+}
+
+/// Opens the detected terminal emulator in the home directory, independent
+/// of the selected app, in the window's session like app launches. The
+/// terminal's own entry in `catalog`, when listed, provides the activation
+/// token. Without a terminal, says so in an error dialog.
+fn open_terminal(window: &ApplicationWindow, catalog: &SharedCatalog) {
+    let result = detect_terminal()
+        .ok_or(LaunchError::NoTerminal)
+        .and_then(|terminal| {
+            let command = terminal_command(&terminal);
+            let app_path = catalog
+                .borrow()
+                .find_by_program(&terminal.program)
+                .map(|entry| entry.path.clone())
+                .unwrap_or_default();
+            let child = spawn_child_in_session(window, &app_path, true, 0, false, &command)?;
+            reap_child(child);
+            Ok(command)
+        });
+    match result {
+        Ok(command) => log_info!("Opened terminal: {}", format_command(&command)),
+        Err(err) => {
+            log_error!("Failed to open a terminal: {err}");
+            show_error_dialog(window, "Failed to open terminal", &err.to_string());
+        }
+    }
+}
+
+/// Asks for a display name for `target` and saves it as an override, then
+/// rescans so sorting, search, and both category views pick it up.
+fn rename_target(
+    window: &ApplicationWindow,
+    target: &LaunchTarget,
+    config: &SharedConfig,
+    ui: &Ui,
+    catalog: &SharedCatalog,
+    history: &SharedHistory,
+) {
+    let Some(entry) = target_entry(&catalog.borrow().entries, target) else {
+        return;
+    };
+    let original = entry.original_name.clone().unwrap_or(entry.name.clone());
+    let id = target.id.clone();
+    let config = Rc::clone(config);
+    let ui = ui.clone();
+    let catalog = Rc::clone(catalog);
+    let history = Rc::clone(history);
+    show_rename_dialog(window, &entry.name, &original.clone(), move |name| {
+        let path = name_overrides_path()
+            .ok_or_else(|| "No configuration directory is available.".to_string())?;
+        let mut overrides = read_name_overrides(&path);
+        if name.is_empty() || name == original {
+            overrides.remove(&id);
+        } else {
+            overrides.insert(id.clone(), name.to_string());
+        }
+        write_name_overrides(&path, &overrides).map_err(|err| {
+            log_error!("Failed to save name overrides: {err}");
+            format!("Could not save the name: {err}")
+        })?;
+        scan_applications(&config, &ui, &catalog, &history);
+        Ok(())
+    });
+}
+
+/// Adds `ids` to the blocklist and rescans so the row disappears.
+fn hide_targets(
+    window: &ApplicationWindow,
+    ids: &[String],
+    config: &SharedConfig,
+    ui: &Ui,
+    catalog: &SharedCatalog,
+    history: &SharedHistory,
+) {
+    let Some(path) = blocklist_path() else {
+        return;
+    };
+    let mut blocklist = read_blocklist(&path);
+    blocklist.extend(ids.iter().cloned());
+    if let Err(err) = write_blocklist(&path, &blocklist) {
+        log_error!("Failed to save blocklist: {err}");
+        show_error_dialog(window, "Failed to hide application", &err.to_string());
+        return;
+    }
+    scan_applications(config, ui, catalog, history);
+}
+
+/// Opens the Ctrl+Space quick-launch popup over `window`.
+///
+/// Results use the same ranking as the main search box; Enter launches the
+/// top result and activating a result launches that one. Launches go
+/// through `activate`, as from the programs pane, so duplicate activations
+/// are dropped and `confirm_launch` asks first.
+fn show_quick_launch(
+    window: &ApplicationWindow,
+    config: &SharedConfig,
+    catalog: &SharedCatalog,
+    activate: &Rc<dyn Fn(Vec<LaunchTarget>)>,
+) {
+    let quick = build_quick_launch(window);
+
+    {
+        let config = Rc::clone(config);
+        let catalog = Rc::clone(catalog);
+        let results = quick.results.clone();
+        let status = quick.status.clone();
+        quick.entry.connect_search_changed(move |entry| {
+            let entries = &catalog.borrow().entries;
+            let query = entry.text();
+            if query.trim().is_empty() {
+                clear_list_box(&results);
+                announce(&status, "");
+                return;
+            }
+            let search_mode = config.borrow().search_mode;
+            let search_scope = config.borrow().search_scope;
+            let mut matches = search_entries(entries, &query, search_mode, search_scope);
+            matches.truncate(QUICK_LAUNCH_LIMIT);
+            let highlight = Highlight {
+                query: &query,
+                mode: search_mode,
+            };
+            update_program_list(&results, entries, &matches, Some(&highlight), 0);
+            let shown: Vec<&DesktopEntry> = matches
+                .iter()
+                .filter_map(|&index| entries.get(index))
+                .collect();
+            add_quick_select_badges(&results, &shown);
+            announce(
+                &status,
+                &describe_search_count("Quick launch", &query, matches.len()),
+            );
+        });
+    }
+
+    let launch = {
+        let popup = quick.window.clone();
+        let activate = Rc::clone(activate);
+        Rc::new(move |target: &LaunchTarget| {
+            popup.close();
+            activate(vec![target.clone()]);
+        })
+    };
+    {
+        let launch = Rc::clone(&launch);
+        let results = quick.results.clone();
+        quick.entry.connect_activate(move |_| {
+            if let Some(target) = results.row_at_index(0).as_ref().and_then(row_launch_target) {
+                launch(&target);
+            }
+        });
+    }
+    // Number keys launch the matching badge. From the search entry they
+    // need Alt so typing digits still searches; in the results list a bare
+    // number is enough.
+    {
+        let launch = Rc::clone(&launch);
+        let results = quick.results.clone();
+        let keys = gtk::EventControllerKey::new();
+        keys.set_propagation_phase(gtk::PropagationPhase::Capture);
+        keys.connect_key_pressed(move |_, key, _, modifiers| {
+            let in_results = results.focus_child().is_some();
+            let armed =
+                modifiers == gdk::ModifierType::ALT_MASK || (in_results && modifiers.is_empty());
+            let target = quick_select_index(key)
+                .filter(|_| armed)
+                .and_then(|index| results.row_at_index(index as i32))
+                .as_ref()
+                .and_then(row_launch_target);
+            match target {
+                Some(target) => {
+                    launch(&target);
+                    glib::Propagation::Stop
+                }
+                None => glib::Propagation::Proceed,
+            }
+        });
+        quick.window.add_controller(keys);
+    }
+    quick.results.connect_row_activated(move |_, row| {
+        if let Some(target) = row_launch_target(row) {
+            launch(&target);
+        }
+    });
+
+    quick.window.present();
+    quick.entry.grab_focus();
+}
+
+/// Widgets shared between the scan, search, and selection handlers.
+#[derive(Clone)]
+struct Ui {
+    /// The whole categories pane, hidden in compact mode.
+    categories_pane: gtk::Box,
+    categories_list: gtk::ListBox,
+    category_tree: gtk::ListView,
+    category_tree_toggle: gtk::CheckButton,
+    sidebar: gtk::Stack,
+    programs_list: gtk::ListBox,
+    programs_grid: gtk::FlowBox,
+    /// Holds the "list" and "grid" layouts of the programs pane.
+    programs_stack: gtk::Stack,
+    program_grid_toggle: gtk::CheckButton,
+    compact_toggle: gtk::CheckButton,
+    search_entry: gtk::SearchEntry,
+    search_scope: gtk::DropDown,
+    status: gtk::Label,
+    /// Where each category was left, for `remember_program_position`.
+    program_positions: Rc<RefCell<ProgramPositions>>,
+}
+
+/// The apps activating `row` launches: the selected rows of `list` in
+/// multi-select mode, otherwise just `row`'s app.
+fn activation_targets(list: &gtk::ListBox, row: &gtk::Widget) -> Vec<LaunchTarget> {
+    if is_multi_select(list) && row.is_ancestor(list) {
+        let selected = selected_launch_targets(list);
+        if !selected.is_empty() {
+            return selected;
+        }
+    }
+    row_launch_target(row).into_iter().collect()
+}
+
+/// Resolves the accelerators of every keymap action, dropping (and logging)
+/// any GTK cannot parse. Launch falls back to `activate_shortcut` when the
+/// keymap does not map it.
+fn active_shortcuts(keymap: &Keymap, activate_shortcut: &str) -> Vec<(KeymapAction, Vec<String>)> {
+    KEYMAP_ACTIONS
+        .iter()
+        .map(|action| {
+            let accels =
+                if action.name == LAUNCH_ACTION && !keymap.bindings.contains_key(LAUNCH_ACTION) {
+                    vec![activate_shortcut.to_string()]
+                } else {
+                    keymap.accels(action)
+                };
+            let accels = accels
+                .into_iter()
+                .filter(|accel| {
+                    let valid = gtk::accelerator_parse(accel).is_some();
+                    if !valid {
+                        log_error!("Ignoring invalid shortcut {accel} for {}", action.name);
+                    }
+                    valid
+                })
+                .collect();
+            (*action, accels)
+        })
+        .collect()
+}
+
+/// Lines for the shortcuts dialog, with keys as GTK labels them.
+fn shortcut_lines(shortcuts: &[(KeymapAction, Vec<String>)]) -> Vec<String> {
+    shortcuts
+        .iter()
+        .map(|(action, accels)| {
+            let mut labels: Vec<String> = accels
+                .iter()
+                .filter_map(gtk::accelerator_parse)
+                .map(|(key, modifiers)| gtk::accelerator_get_label(key, modifiers).to_string())
+                .collect();
+            if action.name == LAUNCH_ACTION {
+                labels.insert(0, "Enter".to_string());
+            }
+            describe_shortcut(action.description, &labels)
+        })
+        .collect()
+}
+
+/// Speaks the program whose row or grid tile has keyboard focus.
+fn speak_focused_program(window: &ApplicationWindow, catalog: &SharedCatalog) {
+    let Some(focus) = GtkWindowExt::focus(window) else {
+        return;
+    };
+    let Some(target) = std::iter::successors(Some(focus), |widget| widget.parent())
+        .find_map(|widget| row_launch_target(&widget))
+    else {
+        return;
+    };
+    if let Some(entry) = target_entry(&catalog.borrow().entries, &target) {
+        speech::speak(&describe_focused_program(&entry));
+    }
+}
+
+/// Turns multi-select mode of the programs list on or off and announces it.
+fn toggle_multi_select(ui: &Ui) {
+    if ui.program_grid_toggle.is_active() {
+        announce(
+            &ui.status,
+            "Multi-select is only available in the list view",
+        );
+        return;
+    }
+    let on = !is_multi_select(&ui.programs_list);
+    set_multi_select(&ui.programs_list, on);
+    announce(
+        &ui.status,
+        if on {
+            "Multi-select on: press Space to select applications and Enter to launch them all"
+        } else {
+            "Multi-select off"
+        },
+    );
+}
+
+/// Moves focus to the categories pane, onto its selected entry. Returns
+/// false in compact mode, which hides the pane.
+fn focus_categories(ui: &Ui) -> bool {
+    if ui.compact_toggle.is_active() {
+        false
+    } else if ui.category_tree_toggle.is_active() {
+        ui.category_tree.grab_focus()
+    } else {
+        focus_list(&ui.categories_list)
+    }
+}
+
+/// Moves focus to the programs pane, onto its selected (or first) program.
+fn focus_programs(ui: &Ui) -> bool {
+    if ui.programs_stack.visible_child_name().as_deref() == Some("grid") {
+        focus_grid(&ui.programs_grid)
+    } else {
+        focus_list(&ui.programs_list)
+    }
+}
+
+/// The label of the category selected in whichever sidebar view is shown.
+fn selected_category_label(ui: &Ui) -> Option<String> {
+    if ui.category_tree_toggle.is_active() {
+        selected_tree_node(&ui.category_tree).map(|node| node.label)
+    } else {
+        selected_category(&ui.categories_list)
+    }
+}
+
+/// The category whose program position is remembered: the selected one,
+/// unless search results or compact mode's full list are shown instead.
+fn program_position_key(ui: &Ui) -> Option<String> {
+    if !ui.search_entry.text().trim().is_empty() || ui.compact_toggle.is_active() {
+        return None;
+    }
+    selected_category_label(ui)
+}
+
+/// Remembers `target` as the program selected in the current category.
+fn record_program_position(ui: &Ui, target: Option<LaunchTarget>) {
+    if let (Some(category), Some(target)) = (program_position_key(ui), target) {
+        ui.program_positions
+            .borrow_mut()
+            .remember(&category, &target.id);
+    }
+}
+
+/// Selects the program last selected in the current category, if it is
+/// still listed, so moving into the programs pane returns to it.
+fn restore_program_position(config: &Config, ui: &Ui) {
+    if !config.remember_program_position {
+        return;
+    }
+    let Some(category) = program_position_key(ui) else {
+        return;
+    };
+    let Some(id) = ui
+        .program_positions
+        .borrow()
+        .get(&category)
+        .map(str::to_string)
+    else {
+        return;
+    };
+    if ui.programs_stack.visible_child_name().as_deref() == Some("grid") {
+        select_program_tile(&ui.programs_grid, &id);
+    } else {
+        select_program_row(&ui.programs_list, &id);
+    }
+}
+
+/// The program a launch from the categories pane starts: the one selected
+/// in the programs pane, or else its first program.
+fn highlighted_program(ui: &Ui) -> Option<LaunchTarget> {
+    if ui.programs_stack.visible_child_name().as_deref() == Some("grid") {
+        let grid = &ui.programs_grid;
+        grid.selected_children()
+            .first()
+            .and_then(row_launch_target)
+            .or_else(|| {
+                (0..)
+                    .map_while(|index| grid.child_at_index(index))
+                    .find_map(|tile| row_launch_target(&tile))
+            })
+    } else {
+        let list = &ui.programs_list;
+        list.selected_row()
+            .as_ref()
+            .and_then(row_launch_target)
+            .or_else(|| {
+                // Group headings and placeholders carry no launch target.
+                (0..)
+                    .map_while(|index| list.row_at_index(index))
+                    .find_map(|row| row_launch_target(&row))
+            })
+    }
+}
+
+fn selected_category(categories_list: &gtk::ListBox) -> Option<String> {
+    let row = categories_list.selected_row()?;
+    let category = unsafe { row.data::<String>("category") }?;
+    Some(unsafe { category.as_ref() }.clone())
+}
+
+/// Returns the entry indices listed under `category`, including pseudo-categories.
+fn category_programs(
+    config: &Config,
+    category: &str,
+    catalog: &Catalog,
+    history: &History,
+) -> Vec<usize> {
+    match category {
+        RECENT_CATEGORY => history.recent_indices(&catalog.entries),
+        MOST_USED_CATEGORY => history.most_used_indices(&catalog.entries, config.most_used_limit),
+        NEW_APPS_CATEGORY => new_app_indices(&catalog.entries),
+        TERMINAL_APPS_CATEGORY => terminal_app_indices(&catalog.entries),
+        _ => catalog
+            .category_map
+            .get(category)
+            .cloned()
+            .unwrap_or_default(),
+    }
+}
+
+/// Shows `programs` in whichever layout the grid toggle selects.
+fn show_programs(
+    config: &Config,
+    ui: &Ui,
+    entries: &[DesktopEntry],
+    programs: &[usize],
+    highlight: Option<&Highlight>,
+) {
+    if ui.program_grid_toggle.is_active() {
+        ui.programs_stack.set_visible_child_name("grid");
+        update_program_grid(&ui.programs_grid, entries, programs, highlight);
+    } else {
+        ui.programs_stack.set_visible_child_name("list");
+        update_program_list(
+            &ui.programs_list,
+            entries,
+            programs,
+            highlight,
+            config.max_visible_results,
+        );
+    }
+}
+
+/// Shows or hides the categories pane. Compact mode lists every app in the
+/// programs pane and moves focus to the search box, which drives it.
+fn set_compact_mode(
+    config: &Config,
+    ui: &Ui,
+    catalog: &SharedCatalog,
+    history: &SharedHistory,
+    compact: bool,
+) {
+    ui.categories_pane.set_visible(!compact);
+    refresh_program_view(config, ui, catalog, history);
+    if compact {
+        ui.search_entry.grab_focus();
+    }
+}
+
+/// Shows search results while a query is active, otherwise the selected
+/// category, or every app in compact mode.
+fn refresh_program_view(
+    config: &Config,
+    ui: &Ui,
+    catalog: &SharedCatalog,
+    history: &SharedHistory,
+) {
+    let catalog = catalog.borrow();
+    if catalog.is_empty() {
+        return;
+    }
+    let entries = &catalog.entries;
+
+    let query = ui.search_entry.text();
+    if !query.trim().is_empty() {
+        let results = search_entries(entries, &query, config.search_mode, config.search_scope);
+        let highlight = Highlight {
+            query: &query,
+            mode: config.search_mode,
+        };
+        show_programs(config, ui, entries, &results, Some(&highlight));
+        announce(
+            &ui.status,
+            &describe_search_count("Search results", &query, results.len()),
+        );
+        return;
+    }
+
+    if ui.compact_toggle.is_active() {
+        let programs: Vec<usize> = (0..entries.len()).collect();
+        show_programs(config, ui, entries, &programs, None);
+        announce(
+            &ui.status,
+            &describe_program_count(ALL_APPLICATIONS, programs.len()),
+        );
+        return;
+    }
+
+    if ui.category_tree_toggle.is_active() {
+        if let Some(node) = selected_tree_node(&ui.category_tree) {
+            show_programs(config, ui, entries, &node.entries, None);
+            restore_program_position(config, ui);
+            announce(
+                &ui.status,
+                &describe_program_count(&node.label, node.entries.len()),
+            );
+        }
+        return;
+    }
+
+    if let Some(category) = selected_category(&ui.categories_list) {
+        let programs = category_programs(config, &category, &catalog, &history.borrow());
+        // Group headings only exist in the list layout.
+        if config.group_other && category == "Other" && !ui.program_grid_toggle.is_active() {
+            ui.programs_stack.set_visible_child_name("list");
+            let groups = group_by_raw_category(entries, &programs);
+            update_grouped_program_list(&ui.programs_list, entries, &groups);
+        } else {
+            show_programs(config, ui, entries, &programs, None);
+        }
+        restore_program_position(config, ui);
+        announce(
+            &ui.status,
+            &describe_program_count(&category, programs.len()),
+        );
+    }
+}
+
+fn scan_applications(
+    config: &SharedConfig,
+    ui: &Ui,
+    catalog: &SharedCatalog,
+    history: &SharedHistory,
+) {
+    scan_applications_then(config, ui, catalog, history, || {});
+}
+
+/// Like [`scan_applications`], calling `on_loaded` once the lists are rebuilt.
+fn scan_applications_then(
+    config: &SharedConfig,
+    ui: &Ui,
+    catalog: &SharedCatalog,
+    history: &SharedHistory,
+    on_loaded: impl FnOnce() + 'static,
+) {
+    // Loading and empty-state messages live in the list layout.
+    ui.programs_stack.set_visible_child_name("list");
+    show_loading(&ui.programs_list);
+
+    let (sender, receiver) = oneshot::channel();
+    let merge_duplicates = config.borrow().merge_duplicates;
+    let dedup_symlinks = config.borrow().dedup_symlinks;
+    let hide_terminal_apps = config.borrow().hide_terminal_apps;
+    let show_broken_apps = config.borrow().show_broken_apps;
+    let new_app_days = config.borrow().new_app_days;
+    let settings = config.borrow().clone();
+    thread::spawn(move || {
+        let (mut entries, report) = collect_desktop_entries_with_report(entries_dirs(), &settings);
+        if show_broken_apps {
+            add_broken_entries(&mut entries, &report.broken_exec);
+        }
+        apply_user_lists(&mut entries);
+        let mut entries = filter_terminal_apps(entries, hide_terminal_apps);
+        if dedup_symlinks {
+            entries = dedup_symlinked_entries(entries);
+        }
+        if merge_duplicates {
+            entries = merge_duplicate_entries(entries);
+        }
+        for name in mark_duplicate_names(&mut entries) {
+            log_info!("Several apps are named {name}; their rows name where each comes from");
+        }
+        mark_recently_installed(&mut entries, SystemTime::now(), new_app_days);
+        let timer = start_timer();
+        let catalog = Catalog::with_rules(entries, load_category_rules());
+        let category_tree = build_category_tree(&catalog.entries);
+        if let Some(timer) = timer {
+            log_info!(
+                "Timing: built the category map and tree in {}",
+                format_duration(timer.elapsed())
+            );
+        }
+        let _ = sender.send((catalog, category_tree));
+    });
+
+    let config = Rc::clone(config);
+    let ui = ui.clone();
+    let catalog = Rc::clone(catalog);
+    let history = Rc::clone(history);
+    glib::MainContext::default().spawn_local(async move {
+        let Ok((new_catalog, category_tree)) = receiver.await else {
+            return;
+        };
+        *catalog.borrow_mut() = new_catalog;
+
+        if catalog.borrow().is_empty() {
+            announce(&ui.status, NO_APPLICATIONS_MESSAGE);
+            let programs_list = ui.programs_list.clone();
+            show_empty_state(&programs_list, move || {
+                scan_applications(&config, &ui, &catalog, &history);
+            });
+            on_loaded();
+            return;
+        }
+
+        // Pseudo-categories stay pinned above the regular buckets, but a
+        // fresh window opens on the configured or remembered category, or
+        // else the first regular bucket.
+        let config = config.borrow();
+        let mut buckets =
+            visible_categories(&catalog.borrow().category_map, config.show_empty_categories);
+        match config.category_order {
+            CategoryOrder::Fixed => {}
+            CategoryOrder::Alphabetical => buckets.sort_unstable(),
+            CategoryOrder::Frequency => {
+                let catalog = catalog.borrow();
+                history.borrow().sort_categories_by_use(
+                    &mut buckets,
+                    &catalog.entries,
+                    &catalog.category_map,
+                );
+            }
+        }
+        pin_categories(&mut buckets, &config.pinned_categories);
+        let mut categories = vec![RECENT_CATEGORY, MOST_USED_CATEGORY];
+        if config.new_apps_category
+            && catalog
+                .borrow()
+                .entries
+                .iter()
+                .any(|entry| entry.recently_installed)
+        {
+            categories.push(NEW_APPS_CATEGORY);
+        }
+        if config.terminal_apps_category
+            && catalog.borrow().entries.iter().any(|entry| entry.terminal)
+        {
+            categories.push(TERMINAL_APPS_CATEGORY);
+        }
+        let special = categories.len();
+        categories.extend(buckets.iter().copied());
+        let selected = selected_category(&ui.categories_list).or_else(|| {
+            let last = load_last_category();
+            config
+                .initial_category(last.as_deref(), &categories)
+                .or_else(|| buckets.first().copied())
+                .map(str::to_string)
+        });
+        let rows = category_rows(&categories, special, config.category_headings);
+        populate_category_list(&ui.categories_list, &rows, selected.as_deref());
+
+        let selected_node = selected_tree_node(&ui.category_tree).map(|node| node.id);
+        populate_category_tree(&ui.category_tree, &category_tree, selected_node.as_deref());
+        on_loaded();
+    });
+}
+
+/// Re-reads the config files, re-applies high contrast and the font scale,
+/// and rescans so the lists follow the new settings. The search text and
+/// selected category are kept. Settings wired up when the window opens,
+/// such as shortcuts and the idle timeout, still need a restart.
+fn reload_config(config: &SharedConfig, ui: &Ui, catalog: &SharedCatalog, history: &SharedHistory) {
+    let reloaded = load_config();
+    apply_accessibility_settings(AccessibilitySetup {
+        high_contrast: reloaded.high_contrast,
+        font_scale: reloaded.font_scale,
+    });
+    let (padding, min_height) = reloaded.row_spacing();
+    apply_row_spacing(padding, min_height);
+    set_widget_margin(reloaded.widget_margin());
+    set_exec_subtitles(&ui.programs_list, reloaded.show_exec_subtitle);
+    *config.borrow_mut() = reloaded;
+    log_info!("Reloaded configuration");
+    let status = ui.status.clone();
+    scan_applications_then(config, ui, catalog, history, move || {
+        announce(&status, "Configuration reloaded");
+    });
+}
+
+fn main() {
+    if let Some(code) = check_args() {
+        std::process::exit(code);
+    }
 
     let app = Application::builder()
         .application_id("com.example.AccessLauncher")
         .build();
 
     app.connect_activate(|app| {
-        let categories = [
-            "Accessories",
-            "Audio/Video",
-            "Development",
-            "Games",
-            "Graphics",
-            "Text Editors",
-            "Internet",
-            "Office",
-            "System",
-            "Terminal Emulator",
-            "Utilities",
-            "Other",
-        ];
-
-        let categories_list = build_list_box("Categories list");
-        for category in categories {
-            append_text_row(&categories_list, category, Some("category"));
-        }
-
-        let programs_list = build_list_box("Programs list");
-        append_text_row(&programs_list, "Loading...", None);
-
-        let programs_list_clone = programs_list.clone();
-        let categories_list_clone = categories_list.clone();
-
-        let (sender, receiver) = oneshot::channel();
-
-        thread::spawn(move || {
-            let entries = collect_desktop_entries();
-            let category_map = build_category_map(&entries);
-            let _ = sender.send((entries, category_map));
+        let config: SharedConfig = Rc::new(RefCell::new(load_config()));
+        let settings = config.borrow().clone();
+        if settings.speak && !speech::enable() {
+            log_info!("speak is set, but spd-say was not found; staying silent");
+        }
+        check_accessibility_bus();
+        apply_accessibility_settings(AccessibilitySetup {
+            high_contrast: settings.high_contrast,
+            font_scale: settings.font_scale,
         });
+        let (padding, min_height) = settings.row_spacing();
+        apply_row_spacing(padding, min_height);
+        set_widget_margin(settings.widget_margin());
+        if let Some(display) = gdk::Display::default() {
+            add_icon_search_paths(&display);
+        }
 
-        let ctx = glib::MainContext::default();
-        ctx.spawn_local(async move {
-            if let Ok((entries, category_map)) = receiver.await {
-                let entries = Rc::new(entries);
-                let category_map = Rc::new(category_map);
+        let sidebar = gtk::Stack::new();
+        let category_tree_toggle = build_category_tree_toggle(settings.category_tree);
+        let ui = Ui {
+            categories_pane: build_pane(
+                "Categories",
+                &sidebar,
+                Some(category_tree_toggle.upcast_ref()),
+            ),
+            categories_list: build_list_box("Categories list"),
+            category_tree: build_category_tree_view("Category tree"),
+            category_tree_toggle,
+            sidebar,
+            programs_list: build_list_box("Programs list"),
+            programs_grid: build_program_grid("Programs grid"),
+            programs_stack: gtk::Stack::new(),
+            program_grid_toggle: build_program_grid_toggle(
+                settings.program_view == ProgramView::Grid,
+            ),
+            compact_toggle: build_compact_mode_toggle(settings.compact_mode),
+            search_entry: build_search_entry(),
+            search_scope: build_search_scope_dropdown(settings.search_scope),
+            status: build_status_label(),
+            program_positions: Rc::default(),
+        };
+        let categories_list = ui.categories_list.clone();
+        let programs_list = ui.programs_list.clone();
+        let programs_grid = ui.programs_grid.clone();
+        add_list_navigation(&categories_list, settings.wrap_navigation);
+        add_list_navigation(&programs_list, settings.wrap_navigation);
+        set_exec_subtitles(&programs_list, settings.show_exec_subtitle);
 
-                update_program_list(&programs_list_clone, &entries, &category_map, "Internet");
+        let catalog: SharedCatalog = Rc::new(RefCell::new(Catalog::default()));
+        let history: SharedHistory = Rc::new(RefCell::new(History::load()));
 
-                {
-                    let entries = Rc::clone(&entries);
-                    let category_map = Rc::clone(&category_map);
-                    let programs_list = programs_list_clone.clone();
-                    categories_list_clone.connect_row_selected(move |_, row| {
-                        if let Some(row) = row {
-                            if let Some(category) = unsafe { row.data::<String>("category") } {
-                                let category = unsafe { category.as_ref() };
-                                update_program_list(
-                                    &programs_list,
-                                    &entries,
-                                    &category_map,
-                                    category,
-                                );
-                            }
-                        }
-                    });
+        {
+            let config = Rc::clone(&config);
+            let ui_for_handler = ui.clone();
+            let catalog = Rc::clone(&catalog);
+            let history = Rc::clone(&history);
+            categories_list.connect_row_selected(move |_, row| {
+                if row.is_some() {
+                    refresh_program_view(&config.borrow(), &ui_for_handler, &catalog, &history);
                 }
+            });
+        }
+
+        if let Some(selection) = ui.category_tree.model() {
+            let config = Rc::clone(&config);
+            let ui_for_handler = ui.clone();
+            let catalog = Rc::clone(&catalog);
+            let history = Rc::clone(&history);
+            selection.connect_selection_changed(move |_, _, _| {
+                refresh_program_view(&config.borrow(), &ui_for_handler, &catalog, &history);
+            });
+        }
 
-                if let Some(row) = categories_list_clone.row_at_index(0) {
-                    categories_list_clone.select_row(Some(&row));
+        ui.sidebar.add_named(&categories_list, Some("flat"));
+        connect_category_tree_keys(&ui.category_tree, &ui.status);
+        ui.sidebar.add_named(&ui.category_tree, Some("tree"));
+        {
+            let config = Rc::clone(&config);
+            let ui_for_handler = ui.clone();
+            let catalog = Rc::clone(&catalog);
+            let history = Rc::clone(&history);
+            let show_view = move |tree: bool| {
+                ui_for_handler
+                    .sidebar
+                    .set_visible_child_name(if tree { "tree" } else { "flat" });
+                refresh_program_view(&config.borrow(), &ui_for_handler, &catalog, &history);
+            };
+            show_view(ui.category_tree_toggle.is_active());
+            ui.category_tree_toggle
+                .connect_toggled(move |toggle| show_view(toggle.is_active()));
+        }
+
+        {
+            let config = Rc::clone(&config);
+            let ui_for_handler = ui.clone();
+            let catalog = Rc::clone(&catalog);
+            let history = Rc::clone(&history);
+            ui.search_entry.connect_search_changed(move |_| {
+                refresh_program_view(&config.borrow(), &ui_for_handler, &catalog, &history);
+            });
+        }
+
+        {
+            let config = Rc::clone(&config);
+            let ui_for_handler = ui.clone();
+            let catalog = Rc::clone(&catalog);
+            let history = Rc::clone(&history);
+            ui.search_scope.connect_selected_notify(move |dropdown| {
+                let scope = selected_search_scope(dropdown);
+                config.borrow_mut().search_scope = scope;
+                refresh_program_view(&config.borrow(), &ui_for_handler, &catalog, &history);
+                if let Err(err) = save_config_value("search_scope", scope.as_str()) {
+                    log_error!("Failed to save search scope: {err}");
                 }
-            }
-        });
+            });
+        }
+
+        scan_applications(&config, &ui, &catalog, &history);
 
-        let left_pane = build_pane("Categories", &categories_list);
-        let right_pane = build_pane("Programs", &programs_list);
+        ui.programs_stack.add_named(&programs_list, Some("list"));
+        ui.programs_stack.add_named(&ui.programs_grid, Some("grid"));
+        {
+            let config = Rc::clone(&config);
+            let ui_for_handler = ui.clone();
+            let catalog = Rc::clone(&catalog);
+            let history = Rc::clone(&history);
+            ui.program_grid_toggle.connect_toggled(move |toggle| {
+                refresh_program_view(&config.borrow(), &ui_for_handler, &catalog, &history);
+                let view = if toggle.is_active() {
+                    ProgramView::Grid
+                } else {
+                    ProgramView::List
+                };
+                if let Err(err) = save_config_value("program_view", view.as_str()) {
+                    log_error!("Failed to save program view: {err}");
+                }
+            });
+        }
+        {
+            let config = Rc::clone(&config);
+            let ui_for_handler = ui.clone();
+            let catalog = Rc::clone(&catalog);
+            let history = Rc::clone(&history);
+            ui.compact_toggle.connect_toggled(move |toggle| {
+                let compact = toggle.is_active();
+                set_compact_mode(
+                    &config.borrow(),
+                    &ui_for_handler,
+                    &catalog,
+                    &history,
+                    compact,
+                );
+                if let Err(err) = save_config_value("compact_mode", &compact.to_string()) {
+                    log_error!("Failed to save compact mode: {err}");
+                }
+            });
+        }
+        ui.categories_pane.set_visible(!settings.compact_mode);
+        let programs_header = gtk::Box::new(Orientation::Vertical, 6);
+        programs_header.append(&ui.compact_toggle);
+        programs_header.append(&ui.program_grid_toggle);
+        programs_header.append(&ui.status);
+        let right_pane = build_pane(
+            "Programs",
+            &ui.programs_stack,
+            Some(programs_header.upcast_ref()),
+        );
 
         let paned = gtk::Paned::new(Orientation::Horizontal);
-        paned.set_start_child(Some(&left_pane));
+        paned.set_start_child(Some(&ui.categories_pane));
         paned.set_end_child(Some(&right_pane));
         paned.set_resize_start_child(true);
         paned.set_resize_end_child(true);
@@ -120,45 +1903,560 @@ fn main() {
         paned.set_shrink_end_child(false);
         paned.set_wide_handle(true);
 
+        let content = gtk::Box::new(Orientation::Vertical, 0);
+        let search_row = gtk::Box::new(Orientation::Horizontal, 0);
+        search_row.append(&ui.search_entry);
+        search_row.append(&ui.search_scope);
+        content.append(&search_row);
+        content.append(&paned);
+
         let window = ApplicationWindow::builder()
             .application(app)
             .title("Access Launcher")
             .default_width(900)
             .default_height(600)
-            .child(&paned)
+            .child(&content)
             .build();
-        window.maximize();
-
-        let window_for_dialog = window.clone();
-        programs_list.connect_row_activated(move |_, row| {
-            if let Some(path) = unsafe { row.data::<String>("desktop-path") } {
-                let path = unsafe { path.as_ref() };
-                if let Some(app_info) = gio::DesktopAppInfo::from_filename(path) {
-                    let files: Vec<gio::File> = Vec::new();
-                    let launch_context =
-                        gtk::prelude::WidgetExt::display(&window_for_dialog).app_launch_context();
-                    if let Err(err) = app_info.launch(&files, Some(&launch_context)) {
-                        eprintln!("Failed to launch {path}: {err}");
-                        let app_name = app_info.name();
-                        show_error_dialog(
-                            &window_for_dialog,
-                            &format!("Failed to launch {app_name}"),
-                            err.message(),
-                        );
+        if settings.start_fullscreen {
+            window.fullscreen();
+        } else if settings.start_maximized {
+            window.maximize();
+        }
+        if speech::enabled() {
+            let catalog = Rc::clone(&catalog);
+            window.connect_focus_widget_notify(move |window| {
+                speak_focused_program(window, &catalog);
+            });
+        }
+        if settings.remember_last_category {
+            let categories_list = categories_list.clone();
+            window.connect_close_request(move |_| {
+                if let Some(category) = selected_category(&categories_list) {
+                    if let Err(err) = save_last_category(&category) {
+                        log_error!("Failed to save last category: {err}");
+                    }
+                }
+                glib::Propagation::Proceed
+            });
+        }
+        ui.search_entry.set_key_capture_widget(Some(&window));
+
+        let about_action = gio::SimpleAction::new("about", None);
+        {
+            let window = window.clone();
+            about_action.connect_activate(move |_, _| show_about_dialog(&window));
+        }
+        app.add_action(&about_action);
+
+        let shortcuts = Rc::new(active_shortcuts(
+            &load_keymap(),
+            &settings.activate_shortcut,
+        ));
+        let shortcuts_action = gio::SimpleAction::new("shortcuts", None);
+        {
+            let window = window.clone();
+            let shortcuts = Rc::clone(&shortcuts);
+            shortcuts_action.connect_activate(move |_, _| {
+                show_shortcuts_dialog(&window, &shortcut_lines(&shortcuts));
+            });
+        }
+        app.add_action(&shortcuts_action);
+
+        let focus_search_action = gio::SimpleAction::new("focus-search", None);
+        {
+            let search_entry = ui.search_entry.clone();
+            focus_search_action.connect_activate(move |_, _| {
+                search_entry.grab_focus();
+            });
+        }
+        app.add_action(&focus_search_action);
+
+        let refresh_action = gio::SimpleAction::new("refresh", None);
+        {
+            let config = Rc::clone(&config);
+            let ui = ui.clone();
+            let catalog = Rc::clone(&catalog);
+            let history = Rc::clone(&history);
+            refresh_action.connect_activate(move |_, _| {
+                let status = ui.status.clone();
+                scan_applications_then(&config, &ui, &catalog, &history, move || {
+                    announce(&status, "Applications rescanned");
+                });
+            });
+        }
+        app.add_action(&refresh_action);
+
+        let row_density_action = gio::SimpleAction::new("toggle-row-density", None);
+        {
+            let config = Rc::clone(&config);
+            let status = ui.status.clone();
+            row_density_action.connect_activate(move |_, _| {
+                let mut config = config.borrow_mut();
+                config.row_density = config.row_density.toggled();
+                let (padding, min_height) = config.row_spacing();
+                apply_row_spacing(padding, min_height);
+                set_widget_margin(config.widget_margin());
+                if let Err(err) = save_config_value("row_density", config.row_density.as_str()) {
+                    log_error!("Failed to save row density: {err}");
+                }
+                let message = match config.row_density {
+                    RowDensity::Compact => "Compact rows",
+                    RowDensity::Comfortable => "Comfortable rows",
+                };
+                announce(&status, message);
+            });
+        }
+        app.add_action(&row_density_action);
+
+        let clear_history_action = gio::SimpleAction::new("clear-history", None);
+        {
+            let config = Rc::clone(&config);
+            let ui = ui.clone();
+            let catalog = Rc::clone(&catalog);
+            let history = Rc::clone(&history);
+            clear_history_action.connect_activate(move |_, _| {
+                clear_launch_history(&config, &ui, &catalog, &history);
+            });
+        }
+        app.add_action(&clear_history_action);
+
+        let open_terminal_action = gio::SimpleAction::new("open-terminal", None);
+        {
+            let window = window.clone();
+            let catalog = Rc::clone(&catalog);
+            open_terminal_action.connect_activate(move |_, _| open_terminal(&window, &catalog));
+        }
+        app.add_action(&open_terminal_action);
+
+        let compact_action = gio::SimpleAction::new("toggle-compact-mode", None);
+        {
+            let toggle = ui.compact_toggle.clone();
+            compact_action.connect_activate(move |_, _| {
+                toggle.set_active(!toggle.is_active());
+            });
+        }
+        app.add_action(&compact_action);
+
+        let quit_action = gio::SimpleAction::new("quit", None);
+        {
+            let window = window.clone();
+            quit_action.connect_activate(move |_, _| window.close());
+        }
+        app.add_action(&quit_action);
+
+        // With only two panes, focus-other-pane moves to whichever pane
+        // does not have focus.
+        let other_pane_action = gio::SimpleAction::new("focus-other-pane", None);
+        {
+            let window = window.clone();
+            let ui = ui.clone();
+            other_pane_action.connect_activate(move |_, _| {
+                let in_programs = GtkWindowExt::focus(&window)
+                    .is_some_and(|widget| widget.is_ancestor(&ui.programs_stack));
+                if in_programs {
+                    if !focus_categories(&ui) {
+                        ui.search_entry.grab_focus();
                     }
                 } else {
-                    eprintln!("Failed to load desktop entry: {path}");
-                    show_error_dialog(
-                        &window_for_dialog,
-                        "Failed to load application",
-                        &format!("Could not read desktop entry at {path}"),
+                    focus_programs(&ui);
+                }
+            });
+        }
+        app.add_action(&other_pane_action);
+        {
+            let ui_for_handler = ui.clone();
+            add_pane_crossing(&categories_list, gdk::Key::Right, move || {
+                focus_programs(&ui_for_handler)
+            });
+        }
+        {
+            let ui_for_handler = ui.clone();
+            add_pane_crossing(&programs_list, gdk::Key::Left, move || {
+                focus_categories(&ui_for_handler)
+            });
+        }
+        {
+            let ui_for_handler = ui.clone();
+            let grid = programs_grid.clone();
+            add_pane_crossing(&programs_grid, gdk::Key::Left, move || {
+                let at_edge = grid
+                    .focus_child()
+                    .and_downcast::<gtk::FlowBoxChild>()
+                    .is_none_or(|tile| grid_tile_in_first_column(&grid, &tile));
+                at_edge && focus_categories(&ui_for_handler)
+            });
+        }
+
+        let input_time = track_input_time(&ui.programs_stack);
+        let idle_timeout = (settings.idle_quit_seconds > 0).then(|| {
+            let window_for_idle = window.clone();
+            IdleTimeout::attach(&window, settings.idle_quit_seconds, move || {
+                log_info!("Quitting after the idle timeout");
+                window_for_idle.close();
+            })
+        });
+
+        let reload_action = gio::SimpleAction::new("reload-config", None);
+        {
+            let config = Rc::clone(&config);
+            let ui = ui.clone();
+            let catalog = Rc::clone(&catalog);
+            let history = Rc::clone(&history);
+            reload_action.connect_activate(move |_, _| {
+                reload_config(&config, &ui, &catalog, &history);
+            });
+        }
+        app.add_action(&reload_action);
+
+        let multi_select_action = gio::SimpleAction::new("toggle-multi-select", None);
+        {
+            let ui = ui.clone();
+            multi_select_action.connect_activate(move |_, _| toggle_multi_select(&ui));
+        }
+        app.add_action(&multi_select_action);
+        for (action, accels) in shortcuts.iter() {
+            if action.name != LAUNCH_ACTION {
+                let accels: Vec<&str> = accels.iter().map(String::as_str).collect();
+                app.set_accels_for_action(&format!("app.{}", action.name), &accels);
+            }
+        }
+        add_multi_select_keys(&programs_list);
+        {
+            let ui = ui.clone();
+            programs_list.connect_selected_rows_changed(move |list| {
+                // Rebuilding the list also changes the selection; only
+                // track changes the user makes inside the list.
+                if list.focus_child().is_none() {
+                    return;
+                }
+                if is_multi_select(list) {
+                    announce(
+                        &ui.status,
+                        &describe_selection_count(list.selected_rows().len()),
                     );
                 }
+                record_program_position(
+                    &ui,
+                    list.selected_row().as_ref().and_then(row_launch_target),
+                );
+            });
+        }
+        {
+            let ui_for_handler = ui.clone();
+            ui.programs_grid
+                .connect_selected_children_changed(move |grid| {
+                    if grid.focus_child().is_some() {
+                        let target = grid.selected_children().first().and_then(row_launch_target);
+                        record_program_position(&ui_for_handler, target);
+                    }
+                });
+        }
+
+        // Every launch the user starts goes through `activate`: the
+        // duplicate guard, `confirm_launch`, and `quit_on_launch`.
+        let activate = {
+            let window = window.clone();
+            let catalog = Rc::clone(&catalog);
+            let history = Rc::clone(&history);
+            let input_time = Rc::clone(&input_time);
+            let quit_on_launch = settings.quit_on_launch;
+            let confirm_launch = settings.confirm_launch;
+            let idle_timeout = idle_timeout.clone();
+            let activation_guard = RefCell::new(ActivationGuard::default());
+            let launch = {
+                let window = window.clone();
+                let catalog = Rc::clone(&catalog);
+                Rc::new(move |targets: &[LaunchTarget]| {
+                    let done = {
+                        let window = window.clone();
+                        let idle_timeout = idle_timeout.clone();
+                        move |launched: bool| {
+                            if let Some(idle_timeout) = &idle_timeout {
+                                idle_timeout.reset();
+                            }
+                            if launched && quit_on_launch {
+                                // A failed launch leaves the window open, so the
+                                // error dialog keeps its parent window.
+                                window.close();
+                            }
+                        }
+                    };
+                    let event_time = input_time.get();
+                    match targets {
+                        [target] => {
+                            launch_target(&window, target, event_time, &catalog, &history, done)
+                        }
+                        targets => {
+                            launch_targets(&window, targets, event_time, &catalog, &history, done)
+                        }
+                    }
+                })
+            };
+            let activate: Rc<dyn Fn(Vec<LaunchTarget>)> = {
+                let window = window.clone();
+                let catalog = Rc::clone(&catalog);
+                Rc::new(move |targets| {
+                    // The guard is checked once, before asking: a duplicate
+                    // activation then cannot open a second prompt, and a quick
+                    // Yes is not mistaken for a duplicate of the first one.
+                    let paths: Vec<&str> =
+                        targets.iter().map(|target| target.path.as_str()).collect();
+                    if !activation_guard
+                        .borrow_mut()
+                        .allow(&paths.join("\n"), Instant::now())
+                    {
+                        return;
+                    }
+                    if !confirm_launch {
+                        launch(&targets);
+                        return;
+                    }
+                    let names: Vec<String> = {
+                        let catalog = catalog.borrow();
+                        targets
+                            .iter()
+                            .map(|target| {
+                                target_entry(&catalog.entries, target)
+                                    .map_or_else(|| target.id.clone(), |entry| entry.name)
+                            })
+                            .collect()
+                    };
+                    let launch = Rc::clone(&launch);
+                    show_launch_confirmation(
+                        &window,
+                        &describe_launch_question(&names),
+                        move || launch(&targets),
+                    );
+                })
+            };
+            {
+                let activate = Rc::clone(&activate);
+                programs_list.connect_row_activated(move |list, row| {
+                    let targets = activation_targets(list, row.upcast_ref());
+                    if !targets.is_empty() {
+                        activate(targets);
+                    }
+                });
             }
-        });
+            // Explicit bindings as well as Enter, which some screen
+            // readers intercept.
+            let launch_accels = shortcuts
+                .iter()
+                .find(|(action, _)| action.name == LAUNCH_ACTION)
+                .map(|(_, accels)| accels.as_slice())
+                .unwrap_or_default();
+            for accel in launch_accels {
+                if let Some((key, modifiers)) = gtk::accelerator_parse(accel) {
+                    for container in [
+                        programs_list.upcast_ref::<gtk::Widget>(),
+                        programs_grid.upcast_ref(),
+                    ] {
+                        let activate = Rc::clone(&activate);
+                        let list = programs_list.clone();
+                        add_focused_item_shortcut(container, key, modifiers, move |row| {
+                            let targets = activation_targets(&list, row);
+                            if !targets.is_empty() {
+                                activate(targets);
+                            }
+                        });
+                    }
+                    // From the categories pane, the same key launches the
+                    // program highlighted in the programs pane.
+                    for container in [
+                        categories_list.upcast_ref::<gtk::Widget>(),
+                        ui.category_tree.upcast_ref(),
+                    ] {
+                        let activate = Rc::clone(&activate);
+                        let ui = ui.clone();
+                        add_focused_item_shortcut(container, key, modifiers, move |_| {
+                            match highlighted_program(&ui) {
+                                Some(target) => activate(vec![target]),
+                                None => {
+                                    let category = selected_category_label(&ui).unwrap_or_default();
+                                    announce(&ui.status, &format!("No applications in {category}"));
+                                }
+                            }
+                        });
+                    }
+                }
+            }
+            let quick_launch_action = gio::SimpleAction::new("quick-launch", None);
+            {
+                let window = window.clone();
+                let config = Rc::clone(&config);
+                let catalog = Rc::clone(&catalog);
+                let activate = Rc::clone(&activate);
+                quick_launch_action.connect_activate(move |_, _| {
+                    show_quick_launch(&window, &config, &catalog, &activate);
+                });
+            }
+            app.add_action(&quick_launch_action);
+            {
+                let activate = Rc::clone(&activate);
+                ui.programs_grid.connect_child_activated(move |_, tile| {
+                    if let Some(target) = row_launch_target(tile) {
+                        activate(vec![target]);
+                    }
+                });
+            }
+            activate
+        };
+
+        {
+            let window = window.clone();
+            let config = Rc::clone(&config);
+            let ui = ui.clone();
+            let catalog = Rc::clone(&catalog);
+            let history = Rc::clone(&history);
+            let on_menu = Rc::new(move |row: &gtk::Widget| {
+                let Some(primary) = row_launch_target(row) else {
+                    return;
+                };
+                let alternates = row_alternate_targets(row);
+                let merged = !alternates.is_empty();
+                let mut targets = vec![primary];
+                targets.extend(alternates);
+
+                let mut items: Vec<(String, Box<dyn Fn()>)> = targets
+                    .iter()
+                    .cloned()
+                    .map(|target| {
+                        let label = if merged {
+                            format!("Launch {} version", target.variant)
+                        } else {
+                            "Launch".to_string()
+                        };
+                        let activate = Rc::clone(&activate);
+                        let action: Box<dyn Fn()> =
+                            Box::new(move || activate(vec![target.clone()]));
+                        (label, action)
+                    })
+                    .collect();
+                let ids: Vec<String> = targets.iter().map(|target| target.id.clone()).collect();
+                let primary = targets.swap_remove(0);
+                {
+                    let target = primary.clone();
+                    let window = window.clone();
+                    let catalog = Rc::clone(&catalog);
+                    let history = Rc::clone(&history);
+                    items.push((
+                        "Launch with arguments…".to_string(),
+                        Box::new(move || {
+                            launch_target_with_arguments(&window, &target, &catalog, &history);
+                        }),
+                    ));
+                }
+                // Only offered on hybrid-graphics systems; apps that set
+                // PrefersNonDefaultGPU already launch there.
+                if !primary.discrete_gpu && prime_offload_available() {
+                    let target = LaunchTarget {
+                        discrete_gpu: true,
+                        ..primary.clone()
+                    };
+                    let activate = Rc::clone(&activate);
+                    items.push((
+                        "Run on discrete GPU".to_string(),
+                        Box::new(move || activate(vec![target.clone()])),
+                    ));
+                }
+                {
+                    let target = primary.clone();
+                    let window = window.clone();
+                    let config = Rc::clone(&config);
+                    let ui = ui.clone();
+                    let catalog = Rc::clone(&catalog);
+                    let history = Rc::clone(&history);
+                    items.push((
+                        "Rename…".to_string(),
+                        Box::new(move || {
+                            rename_target(&window, &target, &config, &ui, &catalog, &history);
+                        }),
+                    ));
+                }
+                {
+                    let window = window.clone();
+                    let config = Rc::clone(&config);
+                    let ui = ui.clone();
+                    let catalog = Rc::clone(&catalog);
+                    let history = Rc::clone(&history);
+                    items.push((
+                        "Hide this app".to_string(),
+                        Box::new(move || {
+                            hide_targets(&window, &ids, &config, &ui, &catalog, &history);
+                        }),
+                    ));
+                }
+                let showing_history = selected_category_label(&ui)
+                    .is_some_and(|label| label == RECENT_CATEGORY || label == MOST_USED_CATEGORY);
+                if showing_history {
+                    let config = Rc::clone(&config);
+                    let ui = ui.clone();
+                    let catalog = Rc::clone(&catalog);
+                    let history = Rc::clone(&history);
+                    items.push((
+                        "Clear launch history".to_string(),
+                        Box::new(move || {
+                            clear_launch_history(&config, &ui, &catalog, &history);
+                        }),
+                    ));
+                }
+                // Developer aid, only offered when there is a terminal to open.
+                if let Some(terminal) = detect_terminal() {
+                    let window = window.clone();
+                    let catalog = Rc::clone(&catalog);
+                    items.push((
+                        "Open terminal here".to_string(),
+                        Box::new(move || {
+                            open_terminal_here(&window, &primary, &terminal, &catalog);
+                        }),
+                    ));
+                }
+                show_context_menu(row, items);
+            });
+            {
+                let on_menu = Rc::clone(&on_menu);
+                add_context_menu_trigger(&programs_list, move |row| on_menu(row));
+            }
+            add_grid_context_menu_trigger(&programs_grid, move |row| on_menu(row));
+        }
+
+        for container in [
+            programs_list.upcast_ref::<gtk::Widget>(),
+            programs_grid.upcast_ref(),
+        ] {
+            let catalog = Rc::clone(&catalog);
+            add_focused_item_shortcut(
+                container,
+                gdk::Key::i,
+                gdk::ModifierType::CONTROL_MASK,
+                move |row| show_diagnostics(row, &catalog),
+            );
+        }
 
         window.present();
+        if settings.compact_mode {
+            ui.search_entry.grab_focus();
+        }
+        if is_first_run(&settings) {
+            show_first_run_dialog(&window, |setup| {
+                let mut saved = save_config_value("first_run_completed", "true");
+                if let Some(setup) = setup {
+                    apply_accessibility_settings(setup);
+                    saved = saved
+                        .and_then(|()| {
+                            save_config_value("high_contrast", &setup.high_contrast.to_string())
+                        })
+                        .and_then(|()| {
+                            save_config_value("font_scale", &setup.font_scale.to_string())
+                        });
+                }
+                if let Err(err) = saved {
+                    log_error!("Failed to save accessibility setup: {err}");
+                }
+            });
+        }
     });
 
-    app.run();
+    // Flags were handled above; keep GTK from rejecting them as unknown.
+    app.run_with_args(&env::args().take(1).collect::<Vec<_>>());
 }
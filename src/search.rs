@@ -0,0 +1,375 @@
+use crate::desktop::DesktopEntry;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SearchMode {
+    #[default]
+    Substring,
+    Fuzzy,
+}
+
+impl SearchMode {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "substring" => Some(Self::Substring),
+            "fuzzy" => Some(Self::Fuzzy),
+            _ => None,
+        }
+    }
+
+    pub fn score(self, query: &str, candidate: &str) -> Option<i32> {
+        match self {
+            Self::Substring => substring_score(query, candidate),
+            Self::Fuzzy => fuzzy_score(query, candidate),
+        }
+    }
+
+    /// Char indices of `candidate` that `query` matched, as found by the same
+    /// matcher [`SearchMode::score`] uses. `None` when it does not match.
+    pub fn matched_chars(self, query: &str, candidate: &str) -> Option<Vec<usize>> {
+        match self {
+            Self::Substring => {
+                substring_match(query, candidate).map(|(start, len)| (start..start + len).collect())
+            }
+            Self::Fuzzy => fuzzy_match(query, candidate).map(|(_, positions)| positions),
+        }
+    }
+}
+
+/// Which fields of an entry a search looks at, from narrowest to broadest.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SearchScope {
+    Name,
+    #[default]
+    NameKeywords,
+    NameKeywordsComment,
+}
+
+/// Every scope, in the order the search scope menu lists them.
+pub const SEARCH_SCOPES: [SearchScope; 3] = [
+    SearchScope::Name,
+    SearchScope::NameKeywords,
+    SearchScope::NameKeywordsComment,
+];
+
+impl SearchScope {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "name" => Some(Self::Name),
+            "keywords" => Some(Self::NameKeywords),
+            "comment" => Some(Self::NameKeywordsComment),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Name => "name",
+            Self::NameKeywords => "keywords",
+            Self::NameKeywordsComment => "comment",
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Name => "Name only",
+            Self::NameKeywords => "Name and keywords",
+            Self::NameKeywordsComment => "Name, keywords and description",
+        }
+    }
+}
+
+fn lower_char(c: char) -> char {
+    c.to_lowercase().next().unwrap_or(c)
+}
+
+/// Finds `query` in `candidate` case-insensitively, returning the char index
+/// where the first occurrence starts and its length in chars.
+fn substring_match(query: &str, candidate: &str) -> Option<(usize, usize)> {
+    let query: Vec<char> = query.trim().chars().map(lower_char).collect();
+    if query.is_empty() {
+        return Some((0, 0));
+    }
+    let candidate: Vec<char> = candidate.chars().map(lower_char).collect();
+    let start = candidate
+        .windows(query.len())
+        .position(|window| window == query.as_slice())?;
+    Some((start, query.len()))
+}
+
+/// Scores a case-insensitive substring match; earlier matches rank higher.
+pub fn substring_score(query: &str, candidate: &str) -> Option<i32> {
+    let (position, len) = substring_match(query, candidate)?;
+    if len == 0 {
+        return Some(0);
+    }
+    let bonus = if position == 0 { 100 } else { 0 };
+    Some(bonus + 100 - position.min(100) as i32)
+}
+
+/// Scores `query` as a case-insensitive subsequence of `candidate`.
+///
+/// Each matched character earns a point plus a bonus that grows with the
+/// length of the contiguous run it extends, and characters that start a word
+/// earn extra. The offset of the first match is subtracted so earlier matches
+/// win ties. Returns `None` when `query` is not a subsequence.
+pub fn fuzzy_score(query: &str, candidate: &str) -> Option<i32> {
+    fuzzy_match(query, candidate).map(|(score, _)| score)
+}
+
+/// [`fuzzy_score`] plus the char indices of the matched characters.
+fn fuzzy_match(query: &str, candidate: &str) -> Option<(i32, Vec<usize>)> {
+    let query: Vec<char> = query
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .flat_map(char::to_lowercase)
+        .collect();
+    if query.is_empty() {
+        return Some((0, Vec::new()));
+    }
+
+    let mut score = 0;
+    let mut matched = 0;
+    let mut run = 0;
+    let mut positions = Vec::with_capacity(query.len());
+    let mut previous: Option<char> = None;
+
+    for (position, c) in candidate.chars().enumerate() {
+        if matched == query.len() {
+            break;
+        }
+        if lower_char(c) == query[matched] {
+            positions.push(position);
+            run += 1;
+            score += 1 + 2 * run;
+            if previous.is_none_or(|p| !p.is_alphanumeric()) {
+                score += 3;
+            }
+            matched += 1;
+        } else {
+            run = 0;
+        }
+        previous = Some(c);
+    }
+
+    if matched < query.len() {
+        return None;
+    }
+    let first_match = positions.first().copied().unwrap_or(0);
+    Some((score - first_match as i32, positions))
+}
+
+/// Keyword-only matches rank below every name match.
+const KEYWORD_PENALTY: i32 = 1000;
+/// Comment-only matches rank below every keyword match.
+const COMMENT_PENALTY: i32 = 2 * KEYWORD_PENALTY;
+
+/// Scores an entry by its name, falling back to its best keyword and then
+/// its comment, as far as `scope` reaches. The untranslated name of a
+/// localized entry counts as a name; the original name of a renamed entry
+/// counts as a keyword.
+pub fn entry_score(
+    mode: SearchMode,
+    scope: SearchScope,
+    query: &str,
+    entry: &DesktopEntry,
+) -> Option<i32> {
+    let base_score = entry
+        .base_name
+        .as_deref()
+        .and_then(|base| mode.score(query, base));
+    if let Some(score) = mode.score(query, &entry.name).max(base_score) {
+        return Some(score);
+    }
+    if scope == SearchScope::Name {
+        return None;
+    }
+    let keyword_score = entry
+        .keywords
+        .split(';')
+        .chain(entry.original_name.as_deref())
+        .filter(|keyword| !keyword.is_empty())
+        .filter_map(|keyword| mode.score(query, keyword))
+        .max();
+    if let Some(score) = keyword_score {
+        return Some(score - KEYWORD_PENALTY);
+    }
+    if scope != SearchScope::NameKeywordsComment {
+        return None;
+    }
+    // Fuzzy matching a whole sentence would match almost any query, so
+    // comments are matched word by word.
+    entry
+        .comment
+        .as_deref()?
+        .split_whitespace()
+        .filter_map(|word| mode.score(query, word))
+        .max()
+        .map(|score| score - COMMENT_PENALTY)
+}
+
+/// Returns indices of entries matching `query` within `scope`, best
+/// matches first.
+///
+/// Ties keep the input order, which is alphabetical for scanned entries.
+pub fn search_entries(
+    entries: &[DesktopEntry],
+    query: &str,
+    mode: SearchMode,
+    scope: SearchScope,
+) -> Vec<usize> {
+    let mut scored: Vec<(i32, usize)> = entries
+        .iter()
+        .enumerate()
+        .filter_map(|(index, entry)| {
+            entry_score(mode, scope, query, entry).map(|score| (score, index))
+        })
+        .collect();
+    scored.sort_by_key(|&(score, _)| std::cmp::Reverse(score));
+    scored.into_iter().map(|(_, index)| index).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fuzzy_score_matches_subsequences() {
+        assert!(fuzzy_score("frfx", "Firefox").is_some());
+        assert!(fuzzy_score("FFX", "Firefox").is_some());
+        assert!(fuzzy_score("term", "GNOME Terminal").is_some());
+        assert_eq!(fuzzy_score("xyz", "Firefox"), None);
+        assert_eq!(fuzzy_score("fox fire", "Firefox"), None);
+    }
+
+    #[test]
+    fn fuzzy_score_prefers_contiguous_runs() {
+        let contiguous = fuzzy_score("fire", "Firefox").unwrap();
+        let scattered = fuzzy_score("fire", "File Roller Editor").unwrap();
+        assert!(contiguous > scattered);
+    }
+
+    #[test]
+    fn fuzzy_score_prefers_earlier_matches() {
+        let early = fuzzy_score("fire", "Firefox").unwrap();
+        let late = fuzzy_score("fire", "Wildfire").unwrap();
+        assert!(early > late);
+    }
+
+    #[test]
+    fn fuzzy_score_empty_query_matches_everything() {
+        assert_eq!(fuzzy_score("", "Firefox"), Some(0));
+        assert_eq!(fuzzy_score("  ", "Firefox"), Some(0));
+    }
+
+    #[test]
+    fn substring_score_is_case_insensitive_and_prefix_first() {
+        assert!(substring_score("FOX", "Firefox").is_some());
+        assert_eq!(substring_score("frfx", "Firefox"), None);
+        let prefix = substring_score("term", "Terminal").unwrap();
+        let inner = substring_score("term", "GNOME Terminal").unwrap();
+        assert!(prefix > inner);
+    }
+
+    #[test]
+    fn matched_chars_follow_the_scoring_matcher() {
+        assert_eq!(
+            SearchMode::Substring.matched_chars("FOX", "Firefox"),
+            Some(vec![4, 5, 6])
+        );
+        assert_eq!(
+            SearchMode::Fuzzy.matched_chars("frfx", "Firefox"),
+            Some(vec![0, 2, 4, 6])
+        );
+        assert_eq!(
+            SearchMode::Substring.matched_chars("é", "Café"),
+            Some(vec![3])
+        );
+        assert_eq!(SearchMode::Substring.matched_chars("frfx", "Firefox"), None);
+        assert_eq!(SearchMode::Fuzzy.matched_chars("", "Firefox"), Some(vec![]));
+    }
+
+    #[test]
+    fn search_entries_ranks_name_matches_above_keywords() {
+        let entries = vec![
+            DesktopEntry {
+                name: "Files".to_string(),
+                keywords: "folder;explorer;".to_string(),
+                ..Default::default()
+            },
+            DesktopEntry {
+                name: "Explorer".to_string(),
+                ..Default::default()
+            },
+        ];
+        let results = search_entries(
+            &entries,
+            "explorer",
+            SearchMode::Substring,
+            SearchScope::default(),
+        );
+        assert_eq!(results, vec![1, 0]);
+    }
+
+    #[test]
+    fn search_scope_decides_which_fields_match() {
+        let entries = vec![DesktopEntry {
+            name: "Files".to_string(),
+            keywords: "folder;".to_string(),
+            comment: Some("Access and organize zettelkasten notes".to_string()),
+            ..Default::default()
+        }];
+        let search = |query, scope| search_entries(&entries, query, SearchMode::Substring, scope);
+
+        assert_eq!(search("files", SearchScope::Name), vec![0]);
+        assert!(search("folder", SearchScope::Name).is_empty());
+        assert!(search("zettel", SearchScope::Name).is_empty());
+
+        assert_eq!(search("folder", SearchScope::NameKeywords), vec![0]);
+        assert!(search("zettel", SearchScope::NameKeywords).is_empty());
+
+        assert_eq!(search("zettel", SearchScope::NameKeywordsComment), vec![0]);
+        assert_eq!(search("folder", SearchScope::NameKeywordsComment), vec![0]);
+    }
+
+    #[test]
+    fn comment_matches_rank_below_keyword_matches() {
+        let entries = vec![
+            DesktopEntry {
+                name: "Notes".to_string(),
+                comment: Some("Write a journal".to_string()),
+                ..Default::default()
+            },
+            DesktopEntry {
+                name: "Diary".to_string(),
+                keywords: "journal;".to_string(),
+                ..Default::default()
+            },
+        ];
+        let results = search_entries(
+            &entries,
+            "journal",
+            SearchMode::Fuzzy,
+            SearchScope::NameKeywordsComment,
+        );
+        assert_eq!(results, vec![1, 0]);
+    }
+
+    #[test]
+    fn search_scope_round_trips_config_values() {
+        for scope in SEARCH_SCOPES {
+            assert_eq!(SearchScope::parse(scope.as_str()), Some(scope));
+        }
+        assert_eq!(SearchScope::parse("everything"), None);
+        assert_eq!(SearchScope::default(), SearchScope::NameKeywords);
+    }
+
+    #[test]
+    fn search_mode_parses_config_values() {
+        assert_eq!(SearchMode::parse("fuzzy"), Some(SearchMode::Fuzzy));
+        assert_eq!(
+            SearchMode::parse(" Substring "),
+            Some(SearchMode::Substring)
+        );
+        assert_eq!(SearchMode::parse("regex"), None);
+    }
+}
@@ -0,0 +1,56 @@
+//! Optional self-voicing through speech-dispatcher's `spd-say`, for setups
+//! without a screen reader.
+//!
+//! Speech is off until [`enable`] finds `spd-say` at startup. Without it,
+//! [`speak`] does nothing, and errors while speaking are ignored.
+
+use crate::launch::find_in_path;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::sync::OnceLock;
+use std::thread;
+
+static SPD_SAY: OnceLock<PathBuf> = OnceLock::new();
+
+/// Looks up `spd-say` on `PATH` and, if found, turns speech on. Returns
+/// whether speech is available.
+pub fn enable() -> bool {
+    match find_in_path("spd-say") {
+        Some(program) => {
+            let _ = SPD_SAY.set(program);
+            true
+        }
+        None => false,
+    }
+}
+
+pub fn enabled() -> bool {
+    SPD_SAY.get().is_some()
+}
+
+/// Speaks `text` in the background. `spd-say` sends it at text priority,
+/// so each message cuts off the one still being spoken, as moving focus
+/// quickly should.
+pub fn speak(text: &str) {
+    let Some(program) = SPD_SAY.get() else {
+        return;
+    };
+    let text = text.trim();
+    if text.is_empty() {
+        return;
+    }
+    let mut command = Command::new(program);
+    command
+        .arg("--")
+        .arg(text)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null());
+    // Spawning here lets the message outlive a window closed right after
+    // launching; a helper thread reaps it without blocking GTK.
+    if let Ok(mut child) = command.spawn() {
+        thread::spawn(move || {
+            let _ = child.wait();
+        });
+    }
+}
@@ -1,8 +1,35 @@
 use gtk4::prelude::*;
-use gtk4::{self as gtk, Orientation};
-use std::collections::BTreeMap;
+use gtk4::{self as gtk, gdk, gio, glib, Orientation};
+use std::cell::{Cell, RefCell};
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use std::rc::Rc;
+use std::sync::atomic::{AtomicI32, Ordering};
 
-use crate::desktop::DesktopEntry;
+use crate::category_tree::CategoryNode;
+use crate::desktop::{
+    find_icon_file, icon_search_dirs, strip_icon_extension, variant_label, DesktopEntry,
+};
+use crate::search::{SearchMode, SearchScope, SEARCH_SCOPES};
+use crate::speech;
+
+/// Margin around lists, labels, and other widgets, in pixels, from
+/// [`set_widget_margin`].
+static WIDGET_MARGIN: AtomicI32 = AtomicI32::new(6);
+
+/// Sets the margin widgets built from now on get; windows and dialogs get
+/// twice this around their content. Widgets already built keep theirs.
+pub fn set_widget_margin(margin: u32) {
+    WIDGET_MARGIN.store(margin.min(i32::MAX as u32 / 2) as i32, Ordering::Relaxed);
+}
+
+fn inner_margin() -> i32 {
+    WIDGET_MARGIN.load(Ordering::Relaxed)
+}
+
+fn outer_margin() -> i32 {
+    inner_margin() * 2
+}
 
 fn set_uniform_margins<W: WidgetExt>(widget: &W, margin: i32) {
     widget.set_margin_top(margin);
@@ -19,21 +46,252 @@ fn set_accessible_description<A: IsA<gtk::Accessible>>(widget: &A, description:
     widget.update_property(&[gtk::accessible::Property::Description(description)]);
 }
 
+/// Explains why GTK will not expose the window to screen readers when the
+/// environment turns its accessibility support off; `getenv` reads a
+/// variable. `None` when nothing is turned off.
+pub fn accessibility_disabled_reason(getenv: impl Fn(&str) -> Option<String>) -> Option<String> {
+    match getenv("GTK_A11Y")?.trim() {
+        "none" => Some(
+            "GTK_A11Y=none turns GTK accessibility off; unset it so screen readers can read \
+             the launcher"
+                .to_string(),
+        ),
+        "test" => Some(
+            "GTK_A11Y=test uses GTK's test backend, which screen readers cannot reach; \
+             unset it"
+                .to_string(),
+        ),
+        _ => None,
+    }
+}
+
 pub fn build_list_box(accessible_name: &str) -> gtk::ListBox {
     let list_box = gtk::ListBox::new();
     list_box.set_selection_mode(gtk::SelectionMode::Single);
     list_box.set_focusable(true);
-    set_uniform_margins(&list_box, 6);
+    set_uniform_margins(&list_box, inner_margin());
     set_accessible_label(&list_box, accessible_name);
     set_accessible_description(&list_box, "Use arrow keys to browse items.");
     list_box
 }
 
+/// Keys handled by [`add_list_navigation`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ListKey {
+    Home,
+    End,
+    Up,
+    Down,
+}
+
+/// Returns the row index `key` should move to among `count` visible rows,
+/// or `None` to leave the key to GTK's default handling.
+///
+/// Home and End always jump to the first and last row. Up on the first row
+/// and Down on the last row wrap around only when `wrap` is set.
+pub fn navigation_target(
+    key: ListKey,
+    current: Option<usize>,
+    count: usize,
+    wrap: bool,
+) -> Option<usize> {
+    let last = count.checked_sub(1)?;
+    match key {
+        ListKey::Home => Some(0),
+        ListKey::End => Some(last),
+        ListKey::Up if wrap && current == Some(0) => Some(last),
+        ListKey::Down if wrap && current == Some(last) => Some(0),
+        ListKey::Up | ListKey::Down => None,
+    }
+}
+
+fn visible_rows(list_box: &gtk::ListBox) -> Vec<gtk::ListBoxRow> {
+    let mut rows = Vec::new();
+    let mut child = list_box.first_child();
+    while let Some(widget) = child {
+        child = widget.next_sibling();
+        if let Ok(row) = widget.downcast::<gtk::ListBoxRow>() {
+            if row.is_visible() && row.is_selectable() {
+                rows.push(row);
+            }
+        }
+    }
+    rows
+}
+
+/// Moves focus into `list_box`, onto its selected row or else the first
+/// selectable one, so screen readers announce a row rather than the list.
+/// Returns false when the list has no such row.
+pub fn focus_list(list_box: &gtk::ListBox) -> bool {
+    let row = list_box
+        .selected_row()
+        .filter(|row| row.is_visible())
+        .or_else(|| visible_rows(list_box).into_iter().next());
+    match row {
+        Some(row) => {
+            list_box.select_row(Some(&row));
+            row.grab_focus()
+        }
+        None => false,
+    }
+}
+
+/// The grid counterpart of [`focus_list`].
+pub fn focus_grid(grid: &gtk::FlowBox) -> bool {
+    let tile = grid
+        .selected_children()
+        .into_iter()
+        .next()
+        .or_else(|| grid.child_at_index(0));
+    match tile {
+        Some(tile) => {
+            grid.select_child(&tile);
+            tile.grab_focus()
+        }
+        None => false,
+    }
+}
+
+/// Whether `tile` sits in the leftmost column of its grid.
+pub fn grid_tile_in_first_column(grid: &gtk::FlowBox, tile: &gtk::FlowBoxChild) -> bool {
+    grid.child_at_index(0)
+        .is_none_or(|first| tile.allocation().x() <= first.allocation().x())
+}
+
+/// Calls `on_key` when `key` is pressed without modifiers inside `widget`,
+/// stopping the event only if `on_key` returns true.
+pub fn add_pane_crossing(
+    widget: &impl IsA<gtk::Widget>,
+    key: gdk::Key,
+    on_key: impl Fn() -> bool + 'static,
+) {
+    // Capture phase, so the grid's own arrow-key handling does not run
+    // first and swallow the key at its left edge.
+    let keys = gtk::EventControllerKey::new();
+    keys.set_propagation_phase(gtk::PropagationPhase::Capture);
+    keys.connect_key_pressed(move |_, pressed, _, modifiers| {
+        let matches = pressed == key
+            || (key == gdk::Key::Left && pressed == gdk::Key::KP_Left)
+            || (key == gdk::Key::Right && pressed == gdk::Key::KP_Right);
+        if matches && modifiers.is_empty() && on_key() {
+            glib::Propagation::Stop
+        } else {
+            glib::Propagation::Proceed
+        }
+    });
+    widget.add_controller(keys);
+}
+
+/// Adds Home/End jumps to `list_box`, plus Up/Down wrapping when `wrap` is set.
+///
+/// Only these keys are intercepted, so typing still reaches the search box.
+pub fn add_list_navigation(list_box: &gtk::ListBox, wrap: bool) {
+    let keys = gtk::EventControllerKey::new();
+    keys.set_propagation_phase(gtk::PropagationPhase::Capture);
+    let list = list_box.clone();
+    keys.connect_key_pressed(move |_, key, _, modifiers| {
+        if !modifiers.is_empty() {
+            return glib::Propagation::Proceed;
+        }
+        let key = match key {
+            gdk::Key::Home | gdk::Key::KP_Home => ListKey::Home,
+            gdk::Key::End | gdk::Key::KP_End => ListKey::End,
+            gdk::Key::Up | gdk::Key::KP_Up => ListKey::Up,
+            gdk::Key::Down | gdk::Key::KP_Down => ListKey::Down,
+            _ => return glib::Propagation::Proceed,
+        };
+        let rows = visible_rows(&list);
+        let focused = list.focus_child().and_downcast::<gtk::ListBoxRow>();
+        let current = focused
+            .or_else(|| list.selected_row())
+            .and_then(|row| rows.iter().position(|candidate| *candidate == row));
+        match navigation_target(key, current, rows.len(), wrap) {
+            Some(index) => {
+                let row = &rows[index];
+                // In multi-select mode keys only move focus; Space selects.
+                if !is_multi_select(&list) {
+                    list.select_row(Some(row));
+                }
+                row.grab_focus();
+                glib::Propagation::Stop
+            }
+            None => glib::Propagation::Proceed,
+        }
+    });
+    list_box.add_controller(keys);
+}
+
+/// Whether `list_box` is in the multi-select mode set by [`set_multi_select`].
+pub fn is_multi_select(list_box: &gtk::ListBox) -> bool {
+    list_box.selection_mode() == gtk::SelectionMode::Multiple
+}
+
+/// Switches `list_box` between single selection and a toggle mode where
+/// Up and Down only move focus and Space selects or deselects the focused
+/// row. Leaving the mode selects just the focused row again.
+pub fn set_multi_select(list_box: &gtk::ListBox, on: bool) {
+    let focused = list_box.focus_child().and_downcast::<gtk::ListBoxRow>();
+    if on {
+        list_box.set_selection_mode(gtk::SelectionMode::Multiple);
+        if let Some(row) = focused {
+            list_box.unselect_all();
+            list_box.select_row(Some(&row));
+        }
+    } else {
+        list_box.set_selection_mode(gtk::SelectionMode::Single);
+        if let Some(row) = focused {
+            list_box.select_row(Some(&row));
+        }
+    }
+}
+
+/// Adds the multi-select keys to `list_box`: while [`is_multi_select`],
+/// Up and Down move focus without changing the selection and Space toggles
+/// the focused row.
+pub fn add_multi_select_keys(list_box: &gtk::ListBox) {
+    let keys = gtk::EventControllerKey::new();
+    keys.set_propagation_phase(gtk::PropagationPhase::Capture);
+    let list = list_box.clone();
+    keys.connect_key_pressed(move |_, key, _, modifiers| {
+        if !is_multi_select(&list) || !modifiers.is_empty() {
+            return glib::Propagation::Proceed;
+        }
+        match key {
+            gdk::Key::Up | gdk::Key::KP_Up => {
+                list.emit_move_cursor(gtk::MovementStep::DisplayLines, -1, false, true);
+            }
+            gdk::Key::Down | gdk::Key::KP_Down => {
+                list.emit_move_cursor(gtk::MovementStep::DisplayLines, 1, false, true);
+            }
+            gdk::Key::space | gdk::Key::KP_Space => {
+                let Some(row) = list.focus_child().and_downcast::<gtk::ListBoxRow>() else {
+                    return glib::Propagation::Proceed;
+                };
+                if row.is_selected() {
+                    list.unselect_row(&row);
+                } else {
+                    list.select_row(Some(&row));
+                }
+            }
+            _ => return glib::Propagation::Proceed,
+        }
+        glib::Propagation::Stop
+    });
+    list_box.add_controller(keys);
+}
+
+/// Returns the launch targets of the selected rows, top to bottom.
+pub fn selected_launch_targets(list_box: &gtk::ListBox) -> Vec<LaunchTarget> {
+    let mut rows = list_box.selected_rows();
+    rows.sort_by_key(|row| row.index());
+    rows.iter().filter_map(row_launch_target).collect()
+}
+
 pub fn append_text_row(list_box: &gtk::ListBox, label_text: &str, data_key: Option<&str>) {
     let row = gtk::ListBoxRow::new();
     let label = gtk::Label::new(Some(label_text));
     label.set_xalign(0.0);
-    set_uniform_margins(&label, 6);
+    set_uniform_margins(&label, inner_margin());
     row.set_child(Some(&label));
     set_accessible_label(&row, label_text);
     if let Some(key) = data_key {
@@ -44,24 +302,823 @@ pub fn append_text_row(list_box: &gtk::ListBox, label_text: &str, data_key: Opti
     list_box.append(&row);
 }
 
-fn append_program_row(list_box: &gtk::ListBox, entry: &DesktopEntry) {
-    let row = gtk::ListBoxRow::new();
-    let label = gtk::Label::new(Some(&entry.name));
-    label.set_xalign(0.0);
-    label.set_tooltip_text(Some(&entry.exec));
-    set_uniform_margins(&label, 6);
+/// Rebuilds the category sidebar from `rows` (see [`category_rows`]),
+/// keeping `selected` highlighted if it is still present and otherwise
+/// falling back to the first category.
+pub fn populate_category_list(
+    list_box: &gtk::ListBox,
+    rows: &[CategoryRow],
+    selected: Option<&str>,
+) {
+    clear_list_box(list_box);
+    for row in rows {
+        match row {
+            CategoryRow::Heading(title) => append_category_heading(list_box, title),
+            CategoryRow::Category(category) => {
+                append_text_row(list_box, category, Some("category"));
+            }
+        }
+    }
+
+    let is_category = |row: &CategoryRow| matches!(row, CategoryRow::Category(_));
+    let index = selected
+        .and_then(|selected| {
+            rows.iter()
+                .position(|row| *row == CategoryRow::Category(selected))
+        })
+        .or_else(|| rows.iter().position(is_category));
+    if let Some(row) = index.and_then(|index| list_box.row_at_index(index as i32)) {
+        list_box.select_row(Some(&row));
+    }
+}
+
+/// Heading above the special categories ("Recent", "Most Used", ...) when
+/// [`category_rows`] adds headings.
+pub const SPECIAL_CATEGORIES_HEADING: &str = "Quick access";
+
+/// Heading above the application categories.
+pub const CATEGORIES_HEADING: &str = "Categories";
+
+/// A row of the flat categories list.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CategoryRow<'a> {
+    /// A heading that cannot be selected; arrow keys skip it.
+    Heading(&'a str),
+    Category(&'a str),
+}
+
+/// Lays out `categories`, whose first `special` entries are the special
+/// categories. With `headings`, [`SPECIAL_CATEGORIES_HEADING`] goes above
+/// those and [`CATEGORIES_HEADING`] above the rest, each only when its
+/// group is not empty.
+pub fn category_rows<'a>(
+    categories: &[&'a str],
+    special: usize,
+    headings: bool,
+) -> Vec<CategoryRow<'a>> {
+    let (special, rest) = categories.split_at(special.min(categories.len()));
+    let mut rows = Vec::with_capacity(categories.len() + 2);
+    for (heading, group) in [
+        (SPECIAL_CATEGORIES_HEADING, special),
+        (CATEGORIES_HEADING, rest),
+    ] {
+        if headings && !group.is_empty() {
+            rows.push(CategoryRow::Heading(heading));
+        }
+        rows.extend(group.iter().map(|category| CategoryRow::Category(category)));
+    }
+    rows
+}
+
+/// Appends a heading row to the categories list. Like group headers it can
+/// be neither selected nor focused, and it is exposed as a heading rather
+/// than a list item.
+fn append_category_heading(list_box: &gtk::ListBox, title: &str) {
+    let row = gtk::ListBoxRow::builder()
+        .selectable(false)
+        .activatable(false)
+        .focusable(false)
+        .accessible_role(gtk::AccessibleRole::Heading)
+        .build();
+    let label = gtk::Label::builder()
+        .label(title)
+        .xalign(0.0)
+        .css_classes(["heading"])
+        .accessible_role(gtk::AccessibleRole::Presentation)
+        .build();
+    set_uniform_margins(&label, inner_margin());
     row.set_child(Some(&label));
-    set_accessible_label(&row, &entry.name);
-    set_accessible_description(&row, &entry.exec);
+    row.update_property(&[
+        gtk::accessible::Property::Label(title),
+        gtk::accessible::Property::Level(1),
+    ]);
+    list_box.append(&row);
+}
+
+/// Logical size of program icons, in application pixels.
+const ICON_SIZE: i32 = 24;
+
+/// Logical size of the icons in grid tiles.
+const GRID_ICON_SIZE: i32 = 48;
+
+/// Icon shown when neither an entry's icon nor its symbolic variant exists.
+pub const FALLBACK_ICON: &str = "application-x-executable";
+
+/// Icon marking entries whose Exec program is missing.
+const WARNING_ICON: &str = "dialog-warning";
+
+/// Returns the themed icon names to try for `icon`, in order: the exact
+/// name, its `-symbolic` variant, then [`FALLBACK_ICON`].
+pub fn icon_candidates(icon: Option<&str>) -> Vec<String> {
+    let mut candidates = Vec::with_capacity(3);
+    if let Some(icon) = icon.map(str::trim).filter(|icon| !icon.is_empty()) {
+        // Theme lookups take names, so `foo.png` is looked up as `foo`.
+        let icon = strip_icon_extension(icon).unwrap_or(icon);
+        candidates.push(icon.to_string());
+        if !icon.ends_with("-symbolic") {
+            candidates.push(format!("{icon}-symbolic"));
+        }
+    }
+    candidates.push(FALLBACK_ICON.to_string());
+    candidates
+}
+
+/// Adds the standard icon directories from [`icon_search_dirs`] that exist
+/// to the display's icon theme, so icons installed only into an app's own
+/// `hicolor` directory or into `pixmaps` still resolve.
+pub fn add_icon_search_paths(display: &gdk::Display) {
+    let theme = gtk::IconTheme::for_display(display);
+    let existing = theme.search_path();
+    for dir in icon_search_dirs() {
+        if dir.is_dir() && !existing.contains(&dir) {
+            theme.add_search_path(&dir);
+        }
+    }
+}
+
+/// Resolves an entry's icon at `size` for display inside `widget`.
+///
+/// Resolution order:
+/// 1. An absolute `Icon` path is loaded from disk as-is.
+/// 2. A bare file name such as `foo.png` is loaded from the first icon
+///    directory holding it, usually `/usr/share/pixmaps`.
+/// 3. Otherwise the first name from [`icon_candidates`] that the display's
+///    icon theme provides.
+///
+/// The lookup asks for `size` at the window's scale factor, so a scale of 2
+/// loads 48px artwork for a 24px icon instead of upscaling the 24px one.
+fn build_program_icon(widget: &impl IsA<gtk::Widget>, icon: Option<&str>, size: i32) -> gtk::Image {
+    let scale = widget
+        .root()
+        .map(|root| root.scale_factor())
+        .unwrap_or(1)
+        .max(1);
+    let theme = gtk::IconTheme::for_display(&widget.display());
+    let icon_file = icon.and_then(|icon| {
+        if icon.starts_with('/') {
+            Some(Path::new(icon).to_path_buf()).filter(|path| path.is_file())
+        } else {
+            find_icon_file(icon, &icon_search_dirs())
+        }
+    });
+    let paintable = match icon_file {
+        Some(path) => gtk::IconPaintable::for_file(&gio::File::for_path(path), size, scale),
+        None => {
+            let candidates = icon_candidates(icon.filter(|icon| !icon.starts_with('/')));
+            let name = candidates
+                .iter()
+                .find(|name| theme.has_icon(name))
+                .map_or(FALLBACK_ICON, String::as_str);
+            theme.lookup_icon(
+                name,
+                &[],
+                size,
+                scale,
+                widget.direction(),
+                gtk::IconLookupFlags::empty(),
+            )
+        }
+    };
+    gtk::Image::builder()
+        .paintable(&paintable)
+        .pixel_size(size)
+        .accessible_role(gtk::AccessibleRole::Presentation)
+        .build()
+}
+
+/// What launching a program row (or one of its alternates) needs.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LaunchTarget {
+    pub id: String,
+    pub path: String,
+    pub startup_notify: bool,
+    /// Packaging shown in menus, e.g. "Flatpak" or "system".
+    pub variant: String,
+    /// Run on the discrete GPU through PRIME offload: set from
+    /// `PrefersNonDefaultGPU`, or by "Run on discrete GPU" in the menu.
+    pub discrete_gpu: bool,
+}
+
+impl LaunchTarget {
+    pub fn from_entry(entry: &DesktopEntry) -> Self {
+        Self {
+            id: entry.id.clone(),
+            path: entry.path.to_string_lossy().to_string(),
+            startup_notify: entry.startup_notify,
+            variant: variant_label(entry).to_string(),
+            discrete_gpu: entry.prefers_non_default_gpu,
+        }
+    }
+}
+
+/// Returns the launch target stored on a program row or grid tile.
+pub fn row_launch_target(row: &impl IsA<glib::Object>) -> Option<LaunchTarget> {
+    let target = unsafe { row.data::<LaunchTarget>("launch-target") }?;
+    Some(unsafe { target.as_ref() }.clone())
+}
+
+/// The program last selected in each category this session. Programs are
+/// kept by desktop ID rather than row index, since "Recent" and "Most
+/// Used" reorder as apps launch.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ProgramPositions {
+    by_category: HashMap<String, String>,
+}
+
+impl ProgramPositions {
+    /// Records `id` as the program selected in `category`.
+    pub fn remember(&mut self, category: &str, id: &str) {
+        self.by_category
+            .insert(category.to_string(), id.to_string());
+    }
+
+    /// The program last selected in `category`, if any.
+    pub fn get(&self, category: &str) -> Option<&str> {
+        self.by_category.get(category).map(String::as_str)
+    }
+}
+
+/// Selects the row of program `id` without moving focus, so [`focus_list`]
+/// lands on it. Returns whether the list has such a row.
+pub fn select_program_row(list_box: &gtk::ListBox, id: &str) -> bool {
+    let row = (0..)
+        .map_while(|index| list_box.row_at_index(index))
+        .find(|row| row_launch_target(row).is_some_and(|target| target.id == id));
+    match row {
+        Some(row) => {
+            list_box.select_row(Some(&row));
+            true
+        }
+        None => false,
+    }
+}
+
+/// The grid counterpart of [`select_program_row`].
+pub fn select_program_tile(grid: &gtk::FlowBox, id: &str) -> bool {
+    let tile = (0..)
+        .map_while(|index| grid.child_at_index(index))
+        .find(|tile| row_launch_target(tile).is_some_and(|target| target.id == id));
+    match tile {
+        Some(tile) => {
+            grid.select_child(&tile);
+            true
+        }
+        None => false,
+    }
+}
+
+/// Returns the merged duplicates of a program row or grid tile, if any.
+pub fn row_alternate_targets(row: &impl IsA<glib::Object>) -> Vec<LaunchTarget> {
+    unsafe { row.data::<Vec<LaunchTarget>>("alternate-targets") }
+        .map(|targets| unsafe { targets.as_ref() }.clone())
+        .unwrap_or_default()
+}
+
+/// Accessible description of a program row: its command, its original name
+/// when renamed, and any merged alternates.
+pub fn program_description(entry: &DesktopEntry) -> String {
+    let mut description = entry.exec.clone();
+    if let Some(program) = &entry.missing_program {
+        description = format!("Warning, executable not found: {program}. {description}");
+    }
+    if entry.recently_installed {
+        description = format!("Recently installed. {description}");
+    }
+    if let Some(original) = &entry.original_name {
+        description.push_str(&format!(". Originally named {original}"));
+    }
+    if !entry.alternates.is_empty() {
+        description.push_str(&format!(
+            ". Also installed as {}; open the context menu to choose.",
+            entry
+                .alternates
+                .iter()
+                .map(variant_label)
+                .collect::<Vec<_>>()
+                .join(", ")
+        ));
+    }
+    description
+}
+
+fn program_tooltip(entry: &DesktopEntry) -> String {
+    let mut tooltip = match &entry.original_name {
+        Some(original) => format!("{original}\n{}", entry.exec),
+        None => entry.exec.clone(),
+    };
+    if let Some(program) = &entry.missing_program {
+        tooltip.push_str(&format!("\nExecutable not found: {program}"));
+    }
+    tooltip
+}
+
+/// The name a program row is announced by: its name, plus the source that
+/// tells it apart when another entry has the same name, as in
+/// "Firefox (Flatpak)".
+pub fn program_display_name(entry: &DesktopEntry) -> String {
+    match &entry.name_suffix {
+        Some(suffix) => format!("{} ({suffix})", entry.name),
+        None => entry.name.clone(),
+    }
+}
+
+/// Builds the dimmed source shown after a duplicated name, such as
+/// "(Flatpak)". The row's accessible label includes it, so the badge itself
+/// is presentational.
+fn build_source_badge(entry: &DesktopEntry) -> Option<gtk::Label> {
+    let suffix = entry.name_suffix.as_ref()?;
+    let badge = gtk::Label::builder()
+        .label(format!("({suffix})"))
+        .css_classes(["dim-label"])
+        .valign(gtk::Align::Center)
+        .accessible_role(gtk::AccessibleRole::Presentation)
+        .build();
+    Some(badge)
+}
+
+/// Builds the "New" badge shown on recently installed entries. The row's
+/// accessible description says "Recently installed", so the badge itself
+/// is presentational.
+fn build_new_badge(entry: &DesktopEntry) -> Option<gtk::Label> {
+    entry.recently_installed.then(|| {
+        gtk::Label::builder()
+            .label("New")
+            .css_classes(["accent"])
+            .valign(gtk::Align::Center)
+            .accessible_role(gtk::AccessibleRole::Presentation)
+            .build()
+    })
+}
+
+/// Builds the warning icon shown on entries whose Exec program is missing.
+/// The row's accessible description already says so, so the icon itself is
+/// presentational.
+fn build_warning_icon(entry: &DesktopEntry, size: i32) -> Option<gtk::Image> {
+    entry.missing_program.as_ref()?;
+    let icon = gtk::Image::builder()
+        .icon_name(WARNING_ICON)
+        .pixel_size(size)
+        .accessible_role(gtk::AccessibleRole::Presentation)
+        .build();
+    Some(icon)
+}
+
+/// Labels `widget` for assistive technology and stores what launching it needs.
+fn attach_program_data(widget: &impl IsA<gtk::Accessible>, entry: &DesktopEntry) {
+    set_accessible_label(widget, &program_display_name(entry));
+    set_accessible_description(widget, &program_description(entry));
+    let alternates: Vec<LaunchTarget> = entry
+        .alternates
+        .iter()
+        .map(LaunchTarget::from_entry)
+        .collect();
+    unsafe {
+        widget.set_data("launch-target", LaunchTarget::from_entry(entry));
+        if !alternates.is_empty() {
+            widget.set_data("alternate-targets", alternates);
+        }
+    }
+}
+
+/// The active search, used to highlight matches in program names.
+#[derive(Clone, Copy, Debug)]
+pub struct Highlight<'a> {
+    pub query: &'a str,
+    pub mode: SearchMode,
+}
+
+/// Returns Pango markup for `text` with the chars at `matched` (char
+/// indices, ascending) in bold. Every run is escaped.
+pub fn highlight_markup(text: &str, matched: &[usize]) -> String {
+    let mut markup = String::with_capacity(text.len() + matched.len() * 7);
+    let mut run = String::new();
+    let mut run_bold = false;
+    let mut matched = matched.iter().peekable();
+    for (index, c) in text.chars().enumerate() {
+        let bold = matched.next_if_eq(&&index).is_some();
+        if bold != run_bold && !run.is_empty() {
+            push_markup_run(&mut markup, &run, run_bold);
+            run.clear();
+        }
+        run_bold = bold;
+        run.push(c);
+    }
+    push_markup_run(&mut markup, &run, run_bold);
+    markup
+}
+
+fn push_markup_run(markup: &mut String, run: &str, bold: bool) {
+    if run.is_empty() {
+        return;
+    }
+    let escaped = glib::markup_escape_text(run);
+    if bold {
+        markup.push_str(&format!("<b>{escaped}</b>"));
+    } else {
+        markup.push_str(&escaped);
+    }
+}
+
+/// Builds the name label of a program, bolding what `highlight` matched.
+/// Without an active search the label stays plain text.
+fn build_program_label(entry: &DesktopEntry, highlight: Option<&Highlight>) -> gtk::Label {
+    let label = gtk::Label::new(None);
+    let matched = highlight
+        .and_then(|highlight| highlight.mode.matched_chars(highlight.query, &entry.name))
+        .filter(|matched| !matched.is_empty());
+    match matched {
+        Some(matched) => label.set_markup(&highlight_markup(&entry.name, &matched)),
+        None => label.set_text(&entry.name),
+    }
+    label
+}
+
+/// Makes rows added to `list_box` show each program's Exec command as a
+/// dimmed second line, so it is visible without hovering for the tooltip.
+pub fn set_exec_subtitles(list_box: &gtk::ListBox, show: bool) {
     unsafe {
-        row.set_data("desktop-path", entry.path.to_string_lossy().to_string());
+        list_box.set_data("exec-subtitles", show);
+    }
+}
+
+fn shows_exec_subtitles(list_box: &gtk::ListBox) -> bool {
+    unsafe { list_box.data::<bool>("exec-subtitles") }.is_some_and(|show| unsafe { *show.as_ref() })
+}
+
+fn append_program_row(
+    list_box: &gtk::ListBox,
+    entry: &DesktopEntry,
+    highlight: Option<&Highlight>,
+) {
+    let row = gtk::ListBoxRow::new();
+    let label = build_program_label(entry, highlight);
+    label.set_xalign(0.0);
+    label.set_tooltip_text(Some(&program_tooltip(entry)));
+    let content = gtk::Box::new(Orientation::Horizontal, 6);
+    set_uniform_margins(&content, inner_margin());
+    content.append(&build_program_icon(
+        list_box,
+        entry.icon.as_deref(),
+        ICON_SIZE,
+    ));
+    if shows_exec_subtitles(list_box) {
+        // The Exec line already leads the row's accessible description, so
+        // screen readers hear the subtitle without it being read twice.
+        let subtitle = gtk::Label::builder()
+            .label(entry.exec.as_str())
+            .xalign(0.0)
+            .ellipsize(gtk::pango::EllipsizeMode::End)
+            .css_classes(["dim-label"])
+            .accessible_role(gtk::AccessibleRole::Presentation)
+            .build();
+        let lines = gtk::Box::new(Orientation::Vertical, 2);
+        lines.append(&label);
+        lines.append(&subtitle);
+        content.append(&lines);
+    } else {
+        content.append(&label);
+    }
+    if let Some(badge) = build_source_badge(entry) {
+        content.append(&badge);
+    }
+    if let Some(badge) = build_new_badge(entry) {
+        content.append(&badge);
     }
+    if let Some(warning) = build_warning_icon(entry, ICON_SIZE) {
+        content.append(&warning);
+    }
+    row.set_child(Some(&content));
+    attach_program_data(&row, entry);
     list_box.append(&row);
 }
 
-pub fn build_pane(title: &str, list_box: &gtk::ListBox) -> gtk::Box {
+/// Builds the grid alternative to the programs list. Arrow keys move
+/// between tiles in two dimensions and Enter launches.
+pub fn build_program_grid(accessible_name: &str) -> gtk::FlowBox {
+    let grid = gtk::FlowBox::new();
+    grid.set_selection_mode(gtk::SelectionMode::Single);
+    grid.set_activate_on_single_click(false);
+    grid.set_homogeneous(true);
+    grid.set_valign(gtk::Align::Start);
+    grid.set_min_children_per_line(2);
+    grid.set_max_children_per_line(12);
+    grid.set_row_spacing(6);
+    grid.set_column_spacing(6);
+    set_accessible_label(&grid, accessible_name);
+    grid
+}
+
+fn append_program_tile(grid: &gtk::FlowBox, entry: &DesktopEntry, highlight: Option<&Highlight>) {
+    let label = build_program_label(entry, highlight);
+    label.set_wrap(true);
+    label.set_justify(gtk::Justification::Center);
+    label.set_max_width_chars(14);
+    let content = gtk::Box::new(Orientation::Vertical, 6);
+    set_uniform_margins(&content, inner_margin());
+    content.append(&build_program_icon(
+        grid,
+        entry.icon.as_deref(),
+        GRID_ICON_SIZE,
+    ));
+    content.append(&label);
+    if let Some(badge) = build_source_badge(entry) {
+        badge.set_halign(gtk::Align::Center);
+        content.append(&badge);
+    }
+    if let Some(badge) = build_new_badge(entry) {
+        badge.set_halign(gtk::Align::Center);
+        content.append(&badge);
+    }
+    if let Some(warning) = build_warning_icon(entry, ICON_SIZE) {
+        warning.set_halign(gtk::Align::Center);
+        content.append(&warning);
+    }
+
+    let tile = gtk::FlowBoxChild::new();
+    tile.set_child(Some(&content));
+    tile.set_tooltip_text(Some(&program_tooltip(entry)));
+    attach_program_data(&tile, entry);
+    grid.insert(&tile, -1);
+}
+
+/// Fills the programs grid with one tile per index, in order.
+pub fn update_program_grid(
+    grid: &gtk::FlowBox,
+    entries: &[DesktopEntry],
+    programs: &[usize],
+    highlight: Option<&Highlight>,
+) {
+    while let Some(child) = grid.first_child() {
+        grid.remove(&child);
+    }
+
+    if programs.is_empty() {
+        let text = empty_programs_text(highlight);
+        let label = gtk::Label::new(Some(&text));
+        set_accessible_label(&label, &text);
+        set_uniform_margins(&label, inner_margin());
+        let tile = gtk::FlowBoxChild::new();
+        tile.set_child(Some(&label));
+        tile.set_focusable(false);
+        grid.insert(&tile, -1);
+        return;
+    }
+
+    for &index in programs {
+        if let Some(entry) = entries.get(index) {
+            append_program_tile(grid, entry, highlight);
+        }
+    }
+}
+
+pub fn build_program_grid_toggle(active: bool) -> gtk::CheckButton {
+    let toggle = gtk::CheckButton::with_label("Show programs as a grid");
+    toggle.set_active(active);
+    set_accessible_description(
+        &toggle,
+        "Switch the programs pane between a list and a grid of icon tiles.",
+    );
+    toggle
+}
+
+/// Opens a popover menu on `row` with one button per `(label, action)`.
+///
+/// Focus moves to the first item; Escape closes the menu and returns focus
+/// to the row.
+pub fn show_context_menu(row: &impl IsA<gtk::Widget>, items: Vec<(String, Box<dyn Fn()>)>) {
+    let popover = gtk::Popover::new();
+    popover.set_has_arrow(false);
+    let menu = gtk::Box::new(Orientation::Vertical, 0);
+    let mut first_button = None;
+    for (label, action) in items {
+        let button = gtk::Button::with_label(&label);
+        button.set_has_frame(false);
+        if let Some(child) = button.child().and_downcast::<gtk::Label>() {
+            child.set_xalign(0.0);
+        }
+        let popover = popover.downgrade();
+        button.connect_clicked(move |_| {
+            if let Some(popover) = popover.upgrade() {
+                popover.popdown();
+            }
+            action();
+        });
+        menu.append(&button);
+        first_button.get_or_insert(button);
+    }
+    popover.set_child(Some(&menu));
+    set_accessible_label(&popover, "Application actions");
+    popover.set_parent(row);
+    {
+        let row = row.as_ref().downgrade();
+        popover.connect_closed(move |popover| {
+            if let Some(row) = row.upgrade() {
+                row.grab_focus();
+            }
+            popover.unparent();
+        });
+    }
+    popover.popup();
+    if let Some(button) = first_button {
+        button.grab_focus();
+    }
+}
+
+/// Opens a popover on `row` listing `fields` with a "Copy all" button that
+/// puts `copy_text` on the clipboard. Escape closes it and focus returns to
+/// the row.
+pub fn show_diagnostics_popover(
+    row: &impl IsA<gtk::Widget>,
+    fields: &[(&str, String)],
+    copy_text: String,
+) {
+    let grid = gtk::Grid::new();
+    grid.set_row_spacing(4);
+    grid.set_column_spacing(12);
+    for (index, (label, value)) in fields.iter().enumerate() {
+        let name = gtk::Label::new(Some(label));
+        name.set_xalign(0.0);
+        name.set_valign(gtk::Align::Start);
+        let text = gtk::Label::new(Some(value));
+        text.set_xalign(0.0);
+        text.set_wrap(true);
+        text.set_max_width_chars(60);
+        text.set_selectable(true);
+        set_accessible_label(&text, &format!("{label}: {value}"));
+        grid.attach(&name, 0, index as i32, 1, 1);
+        grid.attach(&text, 1, index as i32, 1, 1);
+    }
+
+    let status = build_status_label();
+    let copy = gtk::Button::with_label("Copy all");
+    set_accessible_description(
+        &copy,
+        "Copy these details to the clipboard for a bug report.",
+    );
+    {
+        let status = status.clone();
+        copy.connect_clicked(move |button| {
+            button.clipboard().set_text(&copy_text);
+            announce(&status, "Copied to clipboard");
+        });
+    }
+    let close = gtk::Button::with_label("Close");
+    let buttons = gtk::Box::new(Orientation::Horizontal, 6);
+    buttons.set_halign(gtk::Align::End);
+    buttons.append(&copy);
+    buttons.append(&close);
+
+    let content = gtk::Box::new(Orientation::Vertical, 6);
+    set_uniform_margins(&content, inner_margin());
+    content.append(&grid);
+    content.append(&status);
+    content.append(&buttons);
+
+    let popover = gtk::Popover::new();
+    popover.set_child(Some(&content));
+    set_accessible_label(&popover, "Application diagnostics");
+    popover.set_parent(row);
+    {
+        let popover = popover.downgrade();
+        close.connect_clicked(move |_| {
+            if let Some(popover) = popover.upgrade() {
+                popover.popdown();
+            }
+        });
+    }
+    {
+        let row = row.as_ref().downgrade();
+        popover.connect_closed(move |popover| {
+            if let Some(row) = row.upgrade() {
+                row.grab_focus();
+            }
+            popover.unparent();
+        });
+    }
+    popover.popup();
+    copy.grab_focus();
+}
+
+/// Calls `on_key` with the focused row or tile of `container` when `key` is
+/// pressed with exactly `modifiers`.
+pub fn add_focused_item_shortcut(
+    container: &impl IsA<gtk::Widget>,
+    key: gdk::Key,
+    modifiers: gdk::ModifierType,
+    on_key: impl Fn(&gtk::Widget) + 'static,
+) {
+    let keys = gtk::EventControllerKey::new();
+    let target = container.as_ref().downgrade();
+    keys.connect_key_pressed(move |_, pressed, _, state| {
+        if pressed.to_lower() != key || state != modifiers {
+            return glib::Propagation::Proceed;
+        }
+        match target
+            .upgrade()
+            .and_then(|container| container.focus_child())
+        {
+            Some(item) => {
+                on_key(&item);
+                glib::Propagation::Stop
+            }
+            None => glib::Propagation::Proceed,
+        }
+    });
+    container.add_controller(keys);
+}
+
+fn is_menu_key(key: gdk::Key, modifiers: gdk::ModifierType) -> bool {
+    key == gdk::Key::Menu || (key == gdk::Key::F10 && modifiers == gdk::ModifierType::SHIFT_MASK)
+}
+
+/// Calls `on_menu` for the row under a right-click, or the focused row when
+/// the Menu key or Shift+F10 is pressed.
+pub fn add_context_menu_trigger(list_box: &gtk::ListBox, on_menu: impl Fn(&gtk::Widget) + 'static) {
+    let on_menu = Rc::new(on_menu);
+
+    let clicks = gtk::GestureClick::new();
+    clicks.set_button(gdk::BUTTON_SECONDARY);
+    {
+        let list = list_box.clone();
+        let on_menu = Rc::clone(&on_menu);
+        clicks.connect_pressed(move |_, _, _, y| {
+            if let Some(row) = list.row_at_y(y as i32) {
+                list.select_row(Some(&row));
+                on_menu(row.upcast_ref());
+            }
+        });
+    }
+    list_box.add_controller(clicks);
+
+    let keys = gtk::EventControllerKey::new();
+    {
+        let list = list_box.clone();
+        keys.connect_key_pressed(move |_, key, _, modifiers| {
+            if !is_menu_key(key, modifiers) {
+                return glib::Propagation::Proceed;
+            }
+            match list.focus_child().and_downcast::<gtk::ListBoxRow>() {
+                Some(row) => {
+                    on_menu(row.upcast_ref());
+                    glib::Propagation::Stop
+                }
+                None => glib::Propagation::Proceed,
+            }
+        });
+    }
+    list_box.add_controller(keys);
+}
+
+/// The grid counterpart of [`add_context_menu_trigger`].
+pub fn add_grid_context_menu_trigger(
+    grid: &gtk::FlowBox,
+    on_menu: impl Fn(&gtk::Widget) + 'static,
+) {
+    let on_menu = Rc::new(on_menu);
+
+    let clicks = gtk::GestureClick::new();
+    clicks.set_button(gdk::BUTTON_SECONDARY);
+    {
+        let grid_for_click = grid.clone();
+        let on_menu = Rc::clone(&on_menu);
+        clicks.connect_pressed(move |_, _, x, y| {
+            if let Some(tile) = grid_for_click.child_at_pos(x as i32, y as i32) {
+                grid_for_click.select_child(&tile);
+                on_menu(tile.upcast_ref());
+            }
+        });
+    }
+    grid.add_controller(clicks);
+
+    let keys = gtk::EventControllerKey::new();
+    {
+        let grid_for_keys = grid.clone();
+        keys.connect_key_pressed(move |_, key, _, modifiers| {
+            if !is_menu_key(key, modifiers) {
+                return glib::Propagation::Proceed;
+            }
+            match grid_for_keys
+                .focus_child()
+                .and_downcast::<gtk::FlowBoxChild>()
+            {
+                Some(tile) => {
+                    on_menu(tile.upcast_ref());
+                    glib::Propagation::Stop
+                }
+                None => glib::Propagation::Proceed,
+            }
+        });
+    }
+    grid.add_controller(keys);
+}
+
+pub fn build_pane(
+    title: &str,
+    content: &impl IsA<gtk::Widget>,
+    extra: Option<&gtk::Widget>,
+) -> gtk::Box {
     let container = gtk::Box::new(Orientation::Vertical, 6);
-    set_uniform_margins(&container, 12);
+    set_uniform_margins(&container, outer_margin());
 
     let header = gtk::Label::new(Some(title));
     header.set_xalign(0.0);
@@ -70,14 +1127,552 @@ pub fn build_pane(title: &str, list_box: &gtk::ListBox) -> gtk::Box {
     let scroller = gtk::ScrolledWindow::new();
     scroller.set_hexpand(true);
     scroller.set_vexpand(true);
-    scroller.set_child(Some(list_box));
+    scroller.set_child(Some(content));
 
     container.append(&header);
+    if let Some(extra) = extra {
+        container.append(extra);
+    }
     container.append(&scroller);
 
     container
 }
 
+/// Builds the accessible label for a category tree row.
+///
+/// `depth` is zero for main categories and `expanded` is `None` for rows
+/// without children. The result reads like "Development, collapsed" or
+/// "IDE, level 2".
+pub fn tree_row_label(label: &str, depth: u32, expanded: Option<bool>) -> String {
+    let mut parts = vec![label.to_string()];
+    match expanded {
+        Some(true) => parts.push("expanded".to_string()),
+        Some(false) => parts.push("collapsed".to_string()),
+        None => {}
+    }
+    if depth > 0 {
+        parts.push(format!("level {}", depth + 1));
+    }
+    parts.join(", ")
+}
+
+/// Builds the hierarchical category view; fill it with
+/// [`populate_category_tree`].
+pub fn build_category_tree_view(accessible_name: &str) -> gtk::ListView {
+    let factory = gtk::SignalListItemFactory::new();
+    factory.connect_setup(|_, item| {
+        let Some(item) = item.downcast_ref::<gtk::ListItem>() else {
+            return;
+        };
+        let label = gtk::Label::new(None);
+        label.set_xalign(0.0);
+        set_uniform_margins(&label, inner_margin());
+        let expander = gtk::TreeExpander::new();
+        expander.set_child(Some(&label));
+        item.set_child(Some(&expander));
+    });
+    factory.connect_bind(|_, item| {
+        let Some(item) = item.downcast_ref::<gtk::ListItem>() else {
+            return;
+        };
+        let Some(row) = item.item().and_downcast::<gtk::TreeListRow>() else {
+            return;
+        };
+        let Some(expander) = item.child().and_downcast::<gtk::TreeExpander>() else {
+            return;
+        };
+        let Some(node) = row.item().and_downcast::<glib::BoxedAnyObject>() else {
+            return;
+        };
+        let label = node.borrow::<CategoryNode>().label.clone();
+        expander.set_list_row(Some(&row));
+        if let Some(child) = expander.child().and_downcast::<gtk::Label>() {
+            child.set_text(&label);
+        }
+        set_accessible_label(&expander, &tree_row_accessible_label(&row, &label));
+        let handler = row.connect_expanded_notify(move |row| {
+            set_accessible_label(&expander, &tree_row_accessible_label(row, &label));
+        });
+        unsafe {
+            item.set_data("expanded-handler", handler);
+        }
+    });
+    factory.connect_unbind(|_, item| {
+        let Some(item) = item.downcast_ref::<gtk::ListItem>() else {
+            return;
+        };
+        let handler = unsafe { item.steal_data::<glib::SignalHandlerId>("expanded-handler") };
+        if let (Some(row), Some(handler)) =
+            (item.item().and_downcast::<gtk::TreeListRow>(), handler)
+        {
+            row.disconnect(handler);
+        }
+    });
+
+    let selection = gtk::SingleSelection::new(None::<gio::ListModel>);
+    let list_view = gtk::ListView::new(Some(selection), Some(factory));
+    list_view.set_focusable(true);
+    set_uniform_margins(&list_view, inner_margin());
+    set_accessible_label(&list_view, accessible_name);
+    set_accessible_description(
+        &list_view,
+        "Use Up and Down to browse categories, Right to expand and Left to collapse. \
+         Control+Right expands all and Control+Left collapses all.",
+    );
+    list_view
+}
+
+pub fn build_compact_mode_toggle(active: bool) -> gtk::CheckButton {
+    let toggle = gtk::CheckButton::with_label("Hide categories");
+    toggle.set_active(active);
+    set_accessible_description(
+        &toggle,
+        "Hide the categories pane and list all applications; type to search them.",
+    );
+    toggle
+}
+
+pub fn build_category_tree_toggle(active: bool) -> gtk::CheckButton {
+    let toggle = gtk::CheckButton::with_label("Show category tree");
+    toggle.set_active(active);
+    set_accessible_description(
+        &toggle,
+        "Switch the category list between flat groups and freedesktop main categories with subcategories.",
+    );
+    toggle
+}
+
+fn tree_row_accessible_label(row: &gtk::TreeListRow, label: &str) -> String {
+    let expanded = row.is_expandable().then(|| row.is_expanded());
+    tree_row_label(label, row.depth(), expanded)
+}
+
+fn category_tree_selection(list_view: &gtk::ListView) -> Option<gtk::SingleSelection> {
+    list_view.model().and_downcast::<gtk::SingleSelection>()
+}
+
+fn category_tree_model(selection: &gtk::SingleSelection) -> Option<gtk::TreeListModel> {
+    selection.model().and_downcast::<gtk::TreeListModel>()
+}
+
+fn tree_row_node(row: &gtk::TreeListRow) -> Option<CategoryNode> {
+    let node = row.item().and_downcast::<glib::BoxedAnyObject>()?;
+    let node = node.borrow::<CategoryNode>().clone();
+    Some(node)
+}
+
+/// Returns the IDs of the expanded rows, so a refresh can restore them.
+fn expanded_node_ids(model: &gtk::TreeListModel) -> HashSet<String> {
+    (0..model.n_items())
+        .filter_map(|position| model.row(position))
+        .filter(|row| row.is_expanded())
+        .filter_map(|row| tree_row_node(&row))
+        .map(|node| node.id)
+        .collect()
+}
+
+/// Expands every row whose node ID is in `ids`, or every row when `ids` is
+/// `None`. Rows revealed by an expansion are visited too.
+fn expand_rows(model: &gtk::TreeListModel, ids: Option<&HashSet<String>>) {
+    let mut position = 0;
+    while position < model.n_items() {
+        if let Some(row) = model.row(position) {
+            let wanted = match ids {
+                Some(ids) => tree_row_node(&row).is_some_and(|node| ids.contains(&node.id)),
+                None => true,
+            };
+            if wanted && row.is_expandable() {
+                row.set_expanded(true);
+            }
+        }
+        position += 1;
+    }
+}
+
+fn select_tree_row(list_view: &gtk::ListView, selection: &gtk::SingleSelection, position: u32) {
+    selection.set_selected(position);
+    let _ = list_view.activate_action("list.scroll-to-item", Some(&position.to_variant()));
+}
+
+/// Expands or collapses every row, keeping the selection on the selected
+/// row's main category when collapsing hides it.
+fn set_all_expanded(list_view: &gtk::ListView, expanded: bool) {
+    let Some(selection) = category_tree_selection(list_view) else {
+        return;
+    };
+    let Some(model) = category_tree_model(&selection) else {
+        return;
+    };
+    if expanded {
+        expand_rows(&model, None);
+        return;
+    }
+    let mut top = selection.selected_item().and_downcast::<gtk::TreeListRow>();
+    while let Some(parent) = top.as_ref().and_then(|row| row.parent()) {
+        top = Some(parent);
+    }
+    for position in 0..model.n_items() {
+        if let Some(row) = model.row(position) {
+            row.set_expanded(false);
+        }
+    }
+    if let Some(top) = top {
+        select_tree_row(list_view, &selection, top.position());
+    }
+}
+
+/// Adds Left/Right expand and collapse to the category tree, with
+/// Control+Right and Control+Left expanding or collapsing every row.
+///
+/// Left on a collapsed subcategory moves to its main category and Right on
+/// an expanded row moves to its first child. Each change is announced
+/// through `status` as, e.g., "Development, expanded".
+pub fn connect_category_tree_keys(list_view: &gtk::ListView, status: &gtk::Label) {
+    let keys = gtk::EventControllerKey::new();
+    keys.set_propagation_phase(gtk::PropagationPhase::Capture);
+    let view = list_view.clone();
+    let status = status.clone();
+    keys.connect_key_pressed(move |_, key, _, state| {
+        let right = matches!(key, gdk::Key::Right | gdk::Key::KP_Right);
+        let left = matches!(key, gdk::Key::Left | gdk::Key::KP_Left);
+        if !right && !left {
+            return glib::Propagation::Proceed;
+        }
+        let modifiers = state & gtk::accelerator_get_default_mod_mask();
+        if modifiers == gdk::ModifierType::CONTROL_MASK {
+            set_all_expanded(&view, right);
+            announce(
+                &status,
+                if right {
+                    "All categories expanded"
+                } else {
+                    "All categories collapsed"
+                },
+            );
+            return glib::Propagation::Stop;
+        }
+        if !modifiers.is_empty() {
+            return glib::Propagation::Proceed;
+        }
+        let Some(selection) = category_tree_selection(&view) else {
+            return glib::Propagation::Proceed;
+        };
+        let Some(row) = selection.selected_item().and_downcast::<gtk::TreeListRow>() else {
+            return glib::Propagation::Proceed;
+        };
+        if right && row.is_expandable() && !row.is_expanded() {
+            row.set_expanded(true);
+        } else if right && row.is_expanded() {
+            select_tree_row(&view, &selection, row.position() + 1);
+            return glib::Propagation::Stop;
+        } else if left && row.is_expanded() {
+            row.set_expanded(false);
+        } else if let (true, Some(parent)) = (left, row.parent()) {
+            select_tree_row(&view, &selection, parent.position());
+            return glib::Propagation::Stop;
+        } else {
+            return glib::Propagation::Stop;
+        }
+        if let Some(node) = tree_row_node(&row) {
+            announce(&status, &tree_row_accessible_label(&row, &node.label));
+        }
+        glib::Propagation::Stop
+    });
+    list_view.add_controller(keys);
+}
+
+fn node_store(nodes: &[CategoryNode]) -> gio::ListStore {
+    let store = gio::ListStore::new::<glib::BoxedAnyObject>();
+    for node in nodes {
+        store.append(&glib::BoxedAnyObject::new(node.clone()));
+    }
+    store
+}
+
+/// Replaces the tree contents with `nodes`, reselecting the category
+/// `selected` when it is still present and otherwise the first row. Rows
+/// that were expanded before stay expanded.
+pub fn populate_category_tree(
+    list_view: &gtk::ListView,
+    nodes: &[CategoryNode],
+    selected: Option<&str>,
+) {
+    let Some(selection) = category_tree_selection(list_view) else {
+        return;
+    };
+    let expanded = category_tree_model(&selection)
+        .map(|model| expanded_node_ids(&model))
+        .unwrap_or_default();
+    let model = gtk::TreeListModel::new(node_store(nodes), false, false, |item| {
+        let node = item.downcast_ref::<glib::BoxedAnyObject>()?;
+        let node = node.borrow::<CategoryNode>();
+        (!node.children.is_empty()).then(|| node_store(&node.children).upcast())
+    });
+    selection.set_model(Some(&model));
+    expand_rows(&model, Some(&expanded));
+
+    let position = selected
+        .and_then(|selected| {
+            (0..model.n_items()).find(|&position| {
+                model
+                    .row(position)
+                    .and_then(|row| tree_row_node(&row))
+                    .is_some_and(|node| node.id == selected)
+            })
+        })
+        .unwrap_or(0);
+    selection.set_selected(position);
+}
+
+/// Returns the node behind the selected tree row.
+pub fn selected_tree_node(list_view: &gtk::ListView) -> Option<CategoryNode> {
+    let selection = category_tree_selection(list_view)?;
+    let row = selection
+        .selected_item()
+        .and_downcast::<gtk::TreeListRow>()?;
+    tree_row_node(&row)
+}
+
+/// Records the timestamp of the latest key press or click on `widget`.
+///
+/// Startup notification needs a recent user-interaction time, otherwise the
+/// compositor may refuse to raise the launched application's window.
+pub fn track_input_time(widget: &impl IsA<gtk::Widget>) -> Rc<Cell<u32>> {
+    let time = Rc::new(Cell::new(0));
+
+    let keys = gtk::EventControllerKey::new();
+    keys.set_propagation_phase(gtk::PropagationPhase::Capture);
+    {
+        let time = Rc::clone(&time);
+        keys.connect_key_pressed(move |controller, _, _, _| {
+            time.set(controller.current_event_time());
+            glib::Propagation::Proceed
+        });
+    }
+    widget.add_controller(keys);
+
+    let clicks = gtk::GestureClick::new();
+    clicks.set_propagation_phase(gtk::PropagationPhase::Capture);
+    {
+        let time = Rc::clone(&time);
+        clicks.connect_pressed(move |gesture, _, _, _| {
+            time.set(gesture.current_event_time());
+        });
+    }
+    widget.add_controller(clicks);
+
+    time
+}
+
+/// Calls `on_idle` once `seconds` pass without key, pointer, or scroll
+/// activity in `window`. The timer is removed when the window closes, so it
+/// never fires after teardown.
+#[derive(Clone)]
+pub struct IdleTimeout {
+    source: Rc<RefCell<Option<glib::SourceId>>>,
+    seconds: u32,
+    on_idle: Rc<dyn Fn()>,
+}
+
+impl IdleTimeout {
+    pub fn attach(
+        window: &gtk::ApplicationWindow,
+        seconds: u32,
+        on_idle: impl Fn() + 'static,
+    ) -> Self {
+        let timeout = Self {
+            source: Rc::new(RefCell::new(None)),
+            seconds,
+            on_idle: Rc::new(on_idle),
+        };
+
+        let keys = gtk::EventControllerKey::new();
+        keys.set_propagation_phase(gtk::PropagationPhase::Capture);
+        {
+            let timeout = timeout.clone();
+            keys.connect_key_pressed(move |_, _, _, _| {
+                timeout.reset();
+                glib::Propagation::Proceed
+            });
+        }
+        window.add_controller(keys);
+
+        let clicks = gtk::GestureClick::new();
+        clicks.set_propagation_phase(gtk::PropagationPhase::Capture);
+        {
+            let timeout = timeout.clone();
+            clicks.connect_pressed(move |_, _, _, _| timeout.reset());
+        }
+        window.add_controller(clicks);
+
+        let motion = gtk::EventControllerMotion::new();
+        {
+            let timeout = timeout.clone();
+            motion.connect_motion(move |_, _, _| timeout.reset());
+        }
+        window.add_controller(motion);
+
+        let scroll = gtk::EventControllerScroll::new(gtk::EventControllerScrollFlags::BOTH_AXES);
+        {
+            let timeout = timeout.clone();
+            scroll.connect_scroll(move |_, _, _| {
+                timeout.reset();
+                glib::Propagation::Proceed
+            });
+        }
+        window.add_controller(scroll);
+
+        {
+            let timeout = timeout.clone();
+            window.connect_close_request(move |_| {
+                timeout.cancel();
+                glib::Propagation::Proceed
+            });
+        }
+        timeout.reset();
+        timeout
+    }
+
+    /// Restarts the countdown, e.g. after a launch.
+    pub fn reset(&self) {
+        self.cancel();
+        let source = Rc::clone(&self.source);
+        let on_idle = Rc::clone(&self.on_idle);
+        let id = glib::timeout_add_seconds_local_once(self.seconds, move || {
+            // The source is gone once it fires; forget it before `on_idle`
+            // closes the window and cancels.
+            source.borrow_mut().take();
+            on_idle();
+        });
+        self.source.borrow_mut().replace(id);
+    }
+
+    fn cancel(&self) {
+        if let Some(id) = self.source.borrow_mut().take() {
+            id.remove();
+        }
+    }
+}
+
+pub fn build_launch_context(
+    widget: &impl IsA<gtk::Widget>,
+    startup_notify: bool,
+    event_time: u32,
+) -> gdk::AppLaunchContext {
+    let launch_context = widget.display().app_launch_context();
+    if startup_notify && event_time != 0 {
+        launch_context.set_timestamp(event_time);
+    }
+    launch_context
+}
+
+/// Builds a label exposed with the status role, so screen readers speak its
+/// text whenever it changes without moving focus.
+pub fn build_status_label() -> gtk::Label {
+    let status = gtk::Label::builder()
+        .accessible_role(gtk::AccessibleRole::Status)
+        .xalign(0.0)
+        .wrap(true)
+        .build();
+    status.set_margin_bottom(6);
+    status
+}
+
+/// Shows `message` in the status label, which screen readers announce, and
+/// speaks it when the `speak` setting is on.
+pub fn announce(status: &gtk::Label, message: &str) {
+    status.set_text(message);
+    speech::speak(message);
+}
+
+/// What speech says when a program gets focus: its
+/// [`program_display_name`] and, when the desktop file has one, its generic
+/// name, as in "Firefox, Web Browser".
+pub fn describe_focused_program(entry: &DesktopEntry) -> String {
+    let name = program_display_name(entry);
+    match &entry.generic_name {
+        Some(generic_name) if generic_name != &entry.name => format!("{name}, {generic_name}"),
+        _ => name,
+    }
+}
+
+/// Formats the announcement after launching the apps named `names`.
+pub fn describe_launched(names: &[String]) -> String {
+    match names {
+        [] => String::new(),
+        [name] => format!("Launched {name}"),
+        names => format!("Launched {} applications", names.len()),
+    }
+}
+
+/// Formats the question [`show_launch_confirmation`] asks before launching
+/// the apps named `names`.
+pub fn describe_launch_question(names: &[String]) -> String {
+    match names {
+        [name] => format!("Launch {name}?"),
+        names => format!("Launch {} applications?", names.len()),
+    }
+}
+
+/// Formats the announcement of how many apps multi-select mode has selected.
+pub fn describe_selection_count(count: usize) -> String {
+    match count {
+        0 => "No applications selected".to_string(),
+        1 => "1 application selected".to_string(),
+        _ => format!("{count} applications selected"),
+    }
+}
+
+/// Titles the dialog for a batch launch where `failed` of `total` apps
+/// did not start.
+pub fn describe_batch_failures(failed: usize, total: usize) -> String {
+    match (failed, total) {
+        (1, 1) => "Failed to launch 1 application".to_string(),
+        (failed, total) => format!("Failed to launch {failed} of {total} applications"),
+    }
+}
+
+/// Lists batch launch failures as `name: details` lines.
+pub fn format_batch_failures(failures: &[(String, String)]) -> String {
+    failures
+        .iter()
+        .map(|(name, details)| format!("{name}: {details}"))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Formats the status message for a list of `count` programs under `context`.
+pub fn describe_program_count(context: &str, count: usize) -> String {
+    match count {
+        0 => format!("{context}: No applications found"),
+        1 => format!("{context}: 1 application"),
+        _ => format!("{context}: {count} applications"),
+    }
+}
+
+/// Message for a search that matched nothing, kept apart from the
+/// "No applications found" of an empty category.
+pub fn describe_no_results(query: &str) -> String {
+    format!("No results for '{}'", query.trim())
+}
+
+/// Like [`describe_program_count`], but a search with no matches says so.
+pub fn describe_search_count(context: &str, query: &str, count: usize) -> String {
+    if count == 0 {
+        describe_no_results(query)
+    } else {
+        describe_program_count(context, count)
+    }
+}
+
+/// Text of the placeholder shown when the programs pane is empty.
+fn empty_programs_text(highlight: Option<&Highlight>) -> String {
+    match highlight {
+        Some(highlight) => describe_no_results(highlight.query),
+        None => "No applications found".to_string(),
+    }
+}
+
 pub fn show_error_dialog(parent: &impl IsA<gtk::Window>, title: &str, details: &str) {
     let dialog = gtk::MessageDialog::builder()
         .message_type(gtk::MessageType::Error)
@@ -92,28 +1687,776 @@ pub fn show_error_dialog(parent: &impl IsA<gtk::Window>, title: &str, details: &
     dialog.present();
 }
 
-pub fn update_program_list(
-    list_box: &gtk::ListBox,
-    entries: &[DesktopEntry],
-    category_map: &BTreeMap<String, Vec<usize>>,
-    category: &str,
+/// Asks `question` with Yes and No buttons before a launch. Yes has focus,
+/// so Enter confirms; No, Escape, or closing the dialog cancels.
+/// `on_confirm` is called only for Yes.
+pub fn show_launch_confirmation(
+    parent: &impl IsA<gtk::Window>,
+    question: &str,
+    on_confirm: impl Fn() + 'static,
+) {
+    let dialog = gtk::MessageDialog::builder()
+        .message_type(gtk::MessageType::Question)
+        .buttons(gtk::ButtonsType::YesNo)
+        .text(question)
+        .build();
+    dialog.set_transient_for(Some(parent));
+    dialog.set_modal(true);
+    dialog.set_destroy_with_parent(true);
+    dialog.set_default_response(gtk::ResponseType::Yes);
+    dialog.connect_response(move |dialog, response| {
+        dialog.close();
+        if response == gtk::ResponseType::Yes {
+            on_confirm();
+        }
+    });
+    dialog.present();
+    if let Some(yes) = dialog.widget_for_response(gtk::ResponseType::Yes) {
+        yes.grab_focus();
+    }
+}
+
+/// Text and labels for [`show_text_prompt`].
+struct TextPrompt<'a> {
+    title: String,
+    /// Visible label above the field, also used as its accessible label.
+    prompt: String,
+    description: &'a str,
+    placeholder: &'a str,
+    initial: &'a str,
+    action: &'a str,
+}
+
+/// Opens a small modal window with a single text field.
+///
+/// Focus starts in the field and Enter submits. `on_submit` receives the
+/// typed text; when it returns an error message the window stays open and
+/// the message is shown and announced beneath the field.
+fn show_text_prompt(
+    parent: &impl IsA<gtk::Window>,
+    text: TextPrompt,
+    on_submit: impl Fn(&str) -> Result<(), String> + 'static,
 ) {
+    let prompt = gtk::Label::new(Some(&format!("{}:", text.prompt)));
+    prompt.set_xalign(0.0);
+
+    let entry = gtk::Entry::new();
+    entry.set_text(text.initial);
+    entry.set_placeholder_text(Some(text.placeholder));
+    set_accessible_label(&entry, &text.prompt);
+    set_accessible_description(&entry, text.description);
+
+    let error = build_status_label();
+
+    let cancel = gtk::Button::with_label("Cancel");
+    let submit_button = gtk::Button::with_label(text.action);
+    submit_button.add_css_class("suggested-action");
+    let buttons = gtk::Box::new(Orientation::Horizontal, 6);
+    buttons.set_halign(gtk::Align::End);
+    buttons.append(&cancel);
+    buttons.append(&submit_button);
+
+    let content = gtk::Box::new(Orientation::Vertical, 6);
+    set_uniform_margins(&content, outer_margin());
+    content.append(&prompt);
+    content.append(&entry);
+    content.append(&error);
+    content.append(&buttons);
+
+    let window = gtk::Window::builder()
+        .title(text.title)
+        .modal(true)
+        .resizable(false)
+        .default_width(420)
+        .destroy_with_parent(true)
+        .child(&content)
+        .build();
+    window.set_transient_for(Some(parent));
+
+    let keys = gtk::EventControllerKey::new();
+    {
+        let window = window.downgrade();
+        keys.connect_key_pressed(move |_, key, _, _| {
+            if key != gdk::Key::Escape {
+                return glib::Propagation::Proceed;
+            }
+            if let Some(window) = window.upgrade() {
+                window.close();
+            }
+            glib::Propagation::Stop
+        });
+    }
+    window.add_controller(keys);
+
+    {
+        let window = window.clone();
+        cancel.connect_clicked(move |_| window.close());
+    }
+    let submit = {
+        let window = window.clone();
+        let entry = entry.clone();
+        Rc::new(move || match on_submit(&entry.text()) {
+            Ok(()) => window.close(),
+            Err(message) => {
+                announce(&error, &message);
+                entry.grab_focus();
+            }
+        })
+    };
+    {
+        let submit = Rc::clone(&submit);
+        entry.connect_activate(move |_| submit());
+    }
+    submit_button.connect_clicked(move |_| submit());
+
+    window.present();
+    entry.grab_focus();
+}
+
+/// Text sizes offered by [`show_first_run_dialog`], as scale factors.
+pub const FONT_SCALES: [f64; 4] = [1.0, 1.25, 1.5, 2.0];
+
+/// Choices made in [`show_first_run_dialog`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AccessibilitySetup {
+    pub high_contrast: bool,
+    pub font_scale: f64,
+}
+
+/// Switches to the high-contrast theme and scales text for the whole app.
+/// The theme and DPI in effect before the first call are remembered, so
+/// calling it again after a config reload scales from those, and turning
+/// high contrast off restores the original theme.
+pub fn apply_accessibility_settings(setup: AccessibilitySetup) {
+    let Some(settings) = gtk::Settings::default() else {
+        return;
+    };
+    let saved = unsafe { settings.data::<(Option<String>, i32)>("original-display") }
+        .map(|saved| unsafe { saved.as_ref() }.clone());
+    let (theme, dpi) = saved.unwrap_or_else(|| {
+        let original = (
+            settings.gtk_theme_name().map(String::from),
+            settings.gtk_xft_dpi(),
+        );
+        unsafe {
+            settings.set_data("original-display", original.clone());
+        }
+        original
+    });
+
+    let wanted_theme = if setup.high_contrast {
+        Some("HighContrast")
+    } else {
+        theme.as_deref()
+    };
+    if settings.gtk_theme_name().as_deref() != wanted_theme {
+        settings.set_gtk_theme_name(wanted_theme);
+    }
+    let wanted_dpi = if setup.font_scale == 1.0 {
+        dpi
+    } else {
+        // The DPI is in 1024ths of a dot per inch; -1 means the 96 default.
+        let base = if dpi > 0 { dpi } else { 96 * 1024 };
+        (f64::from(base) * setup.font_scale).round() as i32
+    };
+    if settings.gtk_xft_dpi() != wanted_dpi {
+        settings.set_gtk_xft_dpi(wanted_dpi);
+    }
+}
+
+/// CSS giving list rows and grid tiles `padding` pixels of padding and a
+/// minimum height of `min_height` pixels. Only the values given are set;
+/// the theme keeps the rest. Empty when neither is.
+pub fn row_spacing_css(padding: Option<u32>, min_height: Option<u32>) -> String {
+    let mut properties = String::new();
+    if let Some(padding) = padding {
+        properties.push_str(&format!(" padding: {padding}px;"));
+    }
+    if let Some(min_height) = min_height {
+        properties.push_str(&format!(" min-height: {min_height}px;"));
+    }
+    if properties.is_empty() {
+        return properties;
+    }
+    format!("listbox > row, flowbox > flowboxchild, listview > row {{{properties} }}\n")
+}
+
+/// Applies [`row_spacing_css`] to every window, replacing the spacing set
+/// by an earlier call. Only sizes change, so focus order and accessible
+/// labels stay as they are.
+pub fn apply_row_spacing(padding: Option<u32>, min_height: Option<u32>) {
+    let Some(display) = gdk::Display::default() else {
+        return;
+    };
+    let existing = unsafe { display.data::<gtk::CssProvider>("row-spacing") }
+        .map(|provider| unsafe { provider.as_ref() }.clone());
+    let provider = existing.unwrap_or_else(|| {
+        let provider = gtk::CssProvider::new();
+        gtk::style_context_add_provider_for_display(
+            &display,
+            &provider,
+            gtk::STYLE_PROVIDER_PRIORITY_APPLICATION,
+        );
+        unsafe {
+            display.set_data("row-spacing", provider.clone());
+        }
+        provider
+    });
+    provider.load_from_data(&row_spacing_css(padding, min_height));
+}
+
+/// Offers high contrast and a larger text size on first launch.
+///
+/// `on_done` is called once: with the choices when they are saved, or with
+/// `None` when the dialog is skipped with its button, Escape, or by closing
+/// the window.
+pub fn show_first_run_dialog(
+    parent: &impl IsA<gtk::Window>,
+    on_done: impl Fn(Option<AccessibilitySetup>) + 'static,
+) {
+    let intro = gtk::Label::new(Some(
+        "Choose how the launcher looks. You can change these later in the config file.",
+    ));
+    intro.set_wrap(true);
+    intro.set_xalign(0.0);
+
+    let high_contrast = gtk::CheckButton::with_mnemonic("Use _high contrast");
+
+    let scale_label = gtk::Label::with_mnemonic("_Text size:");
+    scale_label.set_xalign(0.0);
+    let scale_names: Vec<String> = FONT_SCALES
+        .iter()
+        .map(|scale| format!("{}%", (scale * 100.0).round()))
+        .collect();
+    let scale_names: Vec<&str> = scale_names.iter().map(String::as_str).collect();
+    let font_scale = gtk::DropDown::from_strings(&scale_names);
+    scale_label.set_mnemonic_widget(Some(&font_scale));
+    set_accessible_label(&font_scale, "Text size");
+
+    let skip = gtk::Button::with_mnemonic("_Skip");
+    let save = gtk::Button::with_mnemonic("_Save");
+    save.add_css_class("suggested-action");
+    let buttons = gtk::Box::new(Orientation::Horizontal, 6);
+    buttons.set_halign(gtk::Align::End);
+    buttons.append(&skip);
+    buttons.append(&save);
+
+    let content = gtk::Box::new(Orientation::Vertical, 12);
+    set_uniform_margins(&content, outer_margin());
+    content.append(&intro);
+    content.append(&high_contrast);
+    content.append(&scale_label);
+    content.append(&font_scale);
+    content.append(&buttons);
+
+    let window = gtk::Window::builder()
+        .title("Accessibility Setup")
+        .accessible_role(gtk::AccessibleRole::Dialog)
+        .modal(true)
+        .resizable(false)
+        .default_width(420)
+        .destroy_with_parent(true)
+        .child(&content)
+        .build();
+    window.set_transient_for(Some(parent));
+    set_accessible_description(&window, intro.text().as_str());
+
+    let on_done = Rc::new(on_done);
+    let done = Rc::new(Cell::new(false));
+    let finish = {
+        let window = window.downgrade();
+        move |setup: Option<AccessibilitySetup>| {
+            if !done.replace(true) {
+                on_done(setup);
+            }
+            if let Some(window) = window.upgrade() {
+                window.close();
+            }
+        }
+    };
+    let finish = Rc::new(finish);
+
+    {
+        let finish = Rc::clone(&finish);
+        window.connect_close_request(move |_| {
+            finish(None);
+            glib::Propagation::Proceed
+        });
+    }
+    let keys = gtk::EventControllerKey::new();
+    {
+        let finish = Rc::clone(&finish);
+        keys.connect_key_pressed(move |_, key, _, _| {
+            if key != gdk::Key::Escape {
+                return glib::Propagation::Proceed;
+            }
+            finish(None);
+            glib::Propagation::Stop
+        });
+    }
+    window.add_controller(keys);
+    {
+        let finish = Rc::clone(&finish);
+        skip.connect_clicked(move |_| finish(None));
+    }
+    save.connect_clicked(move |_| {
+        let index = font_scale.selected() as usize;
+        finish(Some(AccessibilitySetup {
+            high_contrast: high_contrast.is_active(),
+            font_scale: FONT_SCALES.get(index).copied().unwrap_or(1.0),
+        }));
+    });
+
+    window.present();
+    save.grab_focus();
+}
+
+/// Asks for extra arguments to launch `app_name` with; see
+/// [`show_text_prompt`] for how `on_launch` is called.
+pub fn show_arguments_dialog(
+    parent: &impl IsA<gtk::Window>,
+    app_name: &str,
+    on_launch: impl Fn(&str) -> Result<(), String> + 'static,
+) {
+    let text = TextPrompt {
+        title: format!("Launch {app_name} with Arguments"),
+        prompt: format!("Extra arguments for {app_name}"),
+        description: "Arguments are added after the application's own command. Quote values that contain spaces. Press Enter to launch or Escape to cancel.",
+        placeholder: "For example a URL or a file path",
+        initial: "",
+        action: "Launch",
+    };
+    show_text_prompt(parent, text, on_launch);
+}
+
+/// Asks for a new display name for the app currently shown as `name`.
+/// `on_rename` receives the trimmed text; an empty name restores `original`.
+pub fn show_rename_dialog(
+    parent: &impl IsA<gtk::Window>,
+    name: &str,
+    original: &str,
+    on_rename: impl Fn(&str) -> Result<(), String> + 'static,
+) {
+    let description = format!(
+        "Only changes how the launcher shows this app. Leave empty to restore the original name, {original}. Press Enter to save or Escape to cancel."
+    );
+    let text = TextPrompt {
+        title: format!("Rename {name}"),
+        prompt: format!("Display name for {original}"),
+        description: &description,
+        placeholder: original,
+        initial: name,
+        action: "Rename",
+    };
+    show_text_prompt(parent, text, move |text| on_rename(text.trim()));
+}
+
+/// Most results the quick-launch popup lists.
+pub const QUICK_LAUNCH_LIMIT: usize = 8;
+
+/// Widgets of the Ctrl+Space quick-launch popup.
+pub struct QuickLaunch {
+    pub window: gtk::Window,
+    pub entry: gtk::SearchEntry,
+    pub results: gtk::ListBox,
+    pub status: gtk::Label,
+}
+
+/// Maps the number keys 1–9 (main row or keypad) to result positions 0–8.
+pub fn quick_select_index(key: gdk::Key) -> Option<usize> {
+    const KEYS: [(gdk::Key, gdk::Key); 9] = [
+        (gdk::Key::_1, gdk::Key::KP_1),
+        (gdk::Key::_2, gdk::Key::KP_2),
+        (gdk::Key::_3, gdk::Key::KP_3),
+        (gdk::Key::_4, gdk::Key::KP_4),
+        (gdk::Key::_5, gdk::Key::KP_5),
+        (gdk::Key::_6, gdk::Key::KP_6),
+        (gdk::Key::_7, gdk::Key::KP_7),
+        (gdk::Key::_8, gdk::Key::KP_8),
+        (gdk::Key::_9, gdk::Key::KP_9),
+    ];
+    KEYS.iter()
+        .position(|&(digit, keypad)| key == digit || key == keypad)
+}
+
+/// Shows a number badge on each of the first nine rows of `list_box`, which
+/// list `entries`, and tells screen readers which key launches each one.
+pub fn add_quick_select_badges(list_box: &gtk::ListBox, entries: &[&DesktopEntry]) {
+    for (index, entry) in entries.iter().enumerate().take(9) {
+        let Some(row) = list_box.row_at_index(index as i32) else {
+            break;
+        };
+        let Some(content) = row.child().and_downcast::<gtk::Box>() else {
+            continue;
+        };
+        let number = index + 1;
+        let badge = gtk::Label::builder()
+            .label(number.to_string())
+            .width_chars(2)
+            .css_classes(["dim-label"])
+            .accessible_role(gtk::AccessibleRole::Presentation)
+            .build();
+        content.prepend(&badge);
+        set_accessible_description(
+            &row,
+            &format!(
+                "{}. Press {number} to launch {}",
+                program_description(entry),
+                entry.name
+            ),
+        );
+    }
+}
+
+/// Builds the quick-launch popup: a modal window with a search entry above
+/// a short results list. Escape closes it and focus starts in the entry.
+pub fn build_quick_launch(parent: &impl IsA<gtk::Window>) -> QuickLaunch {
+    let entry = gtk::SearchEntry::new();
+    entry.set_placeholder_text(Some("Type an application name"));
+    set_uniform_margins(&entry, inner_margin());
+    set_accessible_label(&entry, "Quick launch");
+    set_accessible_description(
+        &entry,
+        "Type to search all applications. Press Enter to launch the top result, Alt and a result's number to launch that one, Down to browse results, or Escape to close.",
+    );
+
+    let results = build_list_box("Quick launch results");
+    let status = build_status_label();
+
+    let content = gtk::Box::new(Orientation::Vertical, 6);
+    set_uniform_margins(&content, outer_margin());
+    content.append(&entry);
+    content.append(&status);
+    content.append(&results);
+
+    let window = gtk::Window::builder()
+        .title("Quick Launch")
+        .modal(true)
+        .resizable(false)
+        .default_width(420)
+        .destroy_with_parent(true)
+        .child(&content)
+        .build();
+    window.set_transient_for(Some(parent));
+
+    // Capture phase, so Escape closes the popup instead of only clearing
+    // the search entry.
+    let keys = gtk::EventControllerKey::new();
+    keys.set_propagation_phase(gtk::PropagationPhase::Capture);
+    {
+        let window = window.downgrade();
+        keys.connect_key_pressed(move |_, key, _, _| {
+            if key != gdk::Key::Escape {
+                return glib::Propagation::Proceed;
+            }
+            if let Some(window) = window.upgrade() {
+                window.close();
+            }
+            glib::Propagation::Stop
+        });
+    }
+    window.add_controller(keys);
+
+    {
+        let results = results.clone();
+        let down = gtk::EventControllerKey::new();
+        down.connect_key_pressed(move |_, key, _, _| {
+            if key != gdk::Key::Down {
+                return glib::Propagation::Proceed;
+            }
+            match results.row_at_index(0) {
+                Some(row) => {
+                    results.select_row(Some(&row));
+                    row.grab_focus();
+                    glib::Propagation::Stop
+                }
+                None => glib::Propagation::Proceed,
+            }
+        });
+        entry.add_controller(down);
+    }
+    entry.set_key_capture_widget(Some(&window));
+
+    QuickLaunch {
+        window,
+        entry,
+        results,
+        status,
+    }
+}
+
+/// One-line description shown in the About dialog.
+pub const APP_DESCRIPTION: &str =
+    "An accessible launcher that lists installed applications by category.";
+
+/// Opens the About dialog over `parent`.
+///
+/// The dialog is modal and takes focus, so screen readers announce it when
+/// it opens; Escape closes it. Focus then returns to whatever widget had it
+/// before, rather than to the window's default.
+pub fn show_about_dialog(parent: &impl IsA<gtk::Window>) {
+    let previous_focus = GtkWindowExt::focus(parent.as_ref());
+    let dialog = gtk::AboutDialog::builder()
+        .program_name("Access Launcher")
+        .version(env!("CARGO_PKG_VERSION"))
+        .comments(APP_DESCRIPTION)
+        .license_type(gtk::License::Gpl30)
+        .logo_icon_name("access-launcher")
+        .modal(true)
+        .destroy_with_parent(true)
+        .build();
+    dialog.set_transient_for(Some(parent));
+    set_accessible_description(
+        &dialog,
+        &format!(
+            "Version {}. Press Escape to close.",
+            env!("CARGO_PKG_VERSION")
+        ),
+    );
+    dialog.connect_close_request(move |_| {
+        if let Some(widget) = &previous_focus {
+            widget.grab_focus();
+        }
+        glib::Propagation::Proceed
+    });
+    dialog.present();
+}
+
+/// Formats one line of the shortcuts dialog, e.g. "Quit: Ctrl+Q".
+pub fn describe_shortcut(description: &str, keys: &[String]) -> String {
+    if keys.is_empty() {
+        format!("{description}: no shortcut")
+    } else {
+        format!("{description}: {}", keys.join(", "))
+    }
+}
+
+/// Lists the active keyboard shortcuts, one line from [`describe_shortcut`]
+/// per row, in a window that Escape or Close dismisses.
+pub fn show_shortcuts_dialog(parent: &impl IsA<gtk::Window>, lines: &[String]) {
+    let list = gtk::ListBox::new();
+    list.set_selection_mode(gtk::SelectionMode::None);
+    set_accessible_label(&list, "Keyboard shortcuts");
+    for line in lines {
+        let label = gtk::Label::new(Some(line));
+        label.set_xalign(0.0);
+        set_uniform_margins(&label, inner_margin());
+        let row = gtk::ListBoxRow::new();
+        row.set_child(Some(&label));
+        set_accessible_label(&row, line);
+        list.append(&row);
+    }
+
+    let close = gtk::Button::with_label("Close");
+    close.set_halign(gtk::Align::End);
+    let content = gtk::Box::new(Orientation::Vertical, 6);
+    set_uniform_margins(&content, outer_margin());
+    content.append(&list);
+    content.append(&close);
+
+    let window = gtk::Window::builder()
+        .title("Keyboard Shortcuts")
+        .modal(true)
+        .resizable(false)
+        .default_width(420)
+        .destroy_with_parent(true)
+        .child(&content)
+        .build();
+    window.set_transient_for(Some(parent));
+
+    let keys = gtk::EventControllerKey::new();
+    {
+        let window = window.downgrade();
+        keys.connect_key_pressed(move |_, key, _, _| {
+            if key != gdk::Key::Escape {
+                return glib::Propagation::Proceed;
+            }
+            if let Some(window) = window.upgrade() {
+                window.close();
+            }
+            glib::Propagation::Stop
+        });
+    }
+    window.add_controller(keys);
+    {
+        let window = window.clone();
+        close.connect_clicked(move |_| window.close());
+    }
+
+    window.present();
+    focus_list(&list);
+}
+
+pub fn clear_list_box(list_box: &gtk::ListBox) {
+    list_box.set_placeholder(None::<&gtk::Widget>);
     while let Some(child) = list_box.first_child() {
         list_box.remove(&child);
     }
-    let programs = category_map
-        .get(category)
-        .map(|items| items.as_slice())
-        .unwrap_or(&[]);
+}
+
+pub fn show_loading(list_box: &gtk::ListBox) {
+    clear_list_box(list_box);
+    append_text_row(list_box, "Loading...", None);
+}
+
+pub const NO_APPLICATIONS_MESSAGE: &str =
+    "No applications found. Check that your desktop files are in a standard location.";
+
+/// Replaces the list contents with guidance for when the scan found nothing.
+pub fn show_empty_state(list_box: &gtk::ListBox, on_refresh: impl Fn() + 'static) {
+    clear_list_box(list_box);
+
+    let container = gtk::Box::new(Orientation::Vertical, 12);
+    container.set_halign(gtk::Align::Center);
+    container.set_valign(gtk::Align::Center);
+    set_uniform_margins(&container, outer_margin());
+
+    let message = gtk::Label::new(Some(NO_APPLICATIONS_MESSAGE));
+    message.set_wrap(true);
+    message.set_justify(gtk::Justification::Center);
+
+    let refresh = gtk::Button::with_label("Refresh");
+    refresh.set_halign(gtk::Align::Center);
+    set_accessible_description(&refresh, NO_APPLICATIONS_MESSAGE);
+    refresh.connect_clicked(move |_| on_refresh());
+
+    container.append(&message);
+    container.append(&refresh);
+    set_accessible_label(&container, "No applications found");
+    set_accessible_description(&container, NO_APPLICATIONS_MESSAGE);
+
+    list_box.set_placeholder(Some(&container));
+}
+
+pub fn build_search_entry() -> gtk::SearchEntry {
+    let search_entry = gtk::SearchEntry::new();
+    search_entry.set_placeholder_text(Some("Search applications"));
+    search_entry.set_hexpand(true);
+    set_uniform_margins(&search_entry, inner_margin());
+    set_accessible_label(&search_entry, "Search applications");
+    set_accessible_description(
+        &search_entry,
+        "Type to filter applications across all categories.",
+    );
+    search_entry
+}
+
+/// Builds the menu choosing which fields search matches, with `scope`
+/// selected. Items follow [`SEARCH_SCOPES`].
+pub fn build_search_scope_dropdown(scope: SearchScope) -> gtk::DropDown {
+    let labels: Vec<&str> = SEARCH_SCOPES.iter().map(|scope| scope.label()).collect();
+    let dropdown = gtk::DropDown::from_strings(&labels);
+    let selected = SEARCH_SCOPES.iter().position(|&item| item == scope);
+    dropdown.set_selected(selected.unwrap_or_default() as u32);
+    dropdown.set_valign(gtk::Align::Center);
+    dropdown.set_margin_end(6);
+    set_accessible_label(&dropdown, "Search in");
+    set_accessible_description(
+        &dropdown,
+        "Choose whether search matches names only, also keywords, or also descriptions.",
+    );
+    dropdown
+}
+
+/// The scope selected in a menu from [`build_search_scope_dropdown`].
+pub fn selected_search_scope(dropdown: &gtk::DropDown) -> SearchScope {
+    SEARCH_SCOPES
+        .get(dropdown.selected() as usize)
+        .copied()
+        .unwrap_or_default()
+}
+
+fn append_group_header(list_box: &gtk::ListBox, title: &str, count: usize) {
+    let row = gtk::ListBoxRow::new();
+    row.set_selectable(false);
+    row.set_activatable(false);
+    let label = gtk::Label::new(None);
+    label.set_markup(&format!("<b>{}</b>", glib::markup_escape_text(title)));
+    label.set_xalign(0.0);
+    set_uniform_margins(&label, inner_margin());
+    row.set_child(Some(&label));
+    set_accessible_label(
+        &row,
+        &format!("Group {}", describe_program_count(title, count)),
+    );
+    list_box.append(&row);
+}
+
+/// Fills the programs list with one heading row per group followed by its
+/// programs. Heading rows cannot be selected, so arrow keys skip them.
+pub fn update_grouped_program_list(
+    list_box: &gtk::ListBox,
+    entries: &[DesktopEntry],
+    groups: &[(String, Vec<usize>)],
+) {
+    if groups.is_empty() {
+        update_program_list(list_box, entries, &[], None, 0);
+        return;
+    }
+    clear_list_box(list_box);
+    for (title, programs) in groups {
+        append_group_header(list_box, title, programs.len());
+        for &index in programs {
+            if let Some(entry) = entries.get(index) {
+                append_program_row(list_box, entry, None);
+            }
+        }
+    }
+}
+
+/// Splits `programs` into the rows to render and how many are left over,
+/// keeping at most `limit` rows; a `limit` of 0 keeps them all.
+pub fn visible_results(programs: &[usize], limit: usize) -> (&[usize], usize) {
+    if limit == 0 || programs.len() <= limit {
+        return (programs, 0);
+    }
+    (&programs[..limit], programs.len() - limit)
+}
+
+/// Text of the row that stands in for `hidden` programs past the cap.
+pub fn describe_hidden_results(hidden: usize) -> String {
+    format!("… and {hidden} more, refine your search")
+}
+
+fn append_truncation_row(list_box: &gtk::ListBox, hidden: usize) {
+    let text = describe_hidden_results(hidden);
+    let row = gtk::ListBoxRow::new();
+    row.set_selectable(false);
+    row.set_activatable(false);
+    let label = gtk::Label::new(Some(&text));
+    label.set_xalign(0.0);
+    label.add_css_class("dim-label");
+    set_uniform_margins(&label, inner_margin());
+    row.set_child(Some(&label));
+    set_accessible_label(&row, &text);
+    list_box.append(&row);
+}
+
+/// Fills the programs list with `entries[index]` for each index, in order.
+/// With `highlight`, the part of each name the search matched is bolded.
+/// At most `limit` rows are rendered (0 for no cap), followed by a row
+/// saying how many were left out.
+pub fn update_program_list(
+    list_box: &gtk::ListBox,
+    entries: &[DesktopEntry],
+    programs: &[usize],
+    highlight: Option<&Highlight>,
+    limit: usize,
+) {
+    clear_list_box(list_box);
 
     if programs.is_empty() {
-        append_text_row(list_box, "No applications found", None);
+        append_text_row(list_box, &empty_programs_text(highlight), None);
         return;
     }
 
-    for &index in programs {
+    let (shown, hidden) = visible_results(programs, limit);
+    for &index in shown {
         if let Some(entry) = entries.get(index) {
-            append_program_row(list_box, entry);
+            append_program_row(list_box, entry, highlight);
         }
     }
+    if hidden > 0 {
+        append_truncation_row(list_box, hidden);
+    }
 }
@@ -0,0 +1,111 @@
+use access_launcher::cache::{read_entries_cache, write_entries_cache, CacheKey};
+use access_launcher::desktop::collect_desktop_entries_from_with_report;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+struct TempDir {
+    path: PathBuf,
+}
+
+impl TempDir {
+    fn new(stem: &str) -> Self {
+        let path = env::temp_dir().join(format!("{stem}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir_all(&path).expect("create temp dir");
+        Self { path }
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.path);
+    }
+}
+
+/// Sets the modification time of `path`, a file or directory.
+fn set_modified(path: &Path, time: SystemTime) {
+    fs::File::open(path)
+        .and_then(|file| file.set_modified(time))
+        .expect("set modification time");
+}
+
+/// Writes a few desktop files covering the cached fields into a fresh
+/// `applications` directory under `root`. They are dated well in the past,
+/// since a cache written in the same clock tick counts as stale.
+fn write_apps(root: &TempDir) -> PathBuf {
+    let apps = root.path.join("applications");
+    fs::create_dir_all(apps.join("vendor")).expect("create apps dir");
+    fs::write(
+        apps.join("editor.desktop"),
+        "[Desktop Entry]\nType=Application\nName=Editor\nName[fr]=Éditeur \"texte\"\n\
+         GenericName=Text Editor\nComment=Edit text\nExec=sh -c 'editor %F'\n\
+         TryExec=sh\nCategories=Utility;TextEditor;\nKeywords=text;notes;\nIcon=editor\n\
+         Path=/tmp\nStartupNotify=true\nPrefersNonDefaultGPU=true\nStartupWMClass=Editor\nVersion=1.5\n\
+         Implements=org.example.Editor;\nX-GNOME-UsesNotifications=true\nActions=new;\n\n\
+         [Desktop Action new]\nName=New Window\nExec=sh --new\n",
+    )
+    .expect("write editor");
+    fs::write(
+        apps.join("vendor/shell.desktop"),
+        "[Desktop Entry]\nType=Application\nName=Shell\nExec=sh\nTerminal=true\n\
+         DBusActivatable=true\n",
+    )
+    .expect("write shell");
+    let scanned = SystemTime::UNIX_EPOCH + Duration::from_secs(1_000_000);
+    for path in [
+        apps.join("editor.desktop"),
+        apps.join("vendor/shell.desktop"),
+        apps.join("vendor"),
+        apps.clone(),
+    ] {
+        set_modified(&path, scanned);
+    }
+    apps
+}
+
+#[test]
+fn entries_cache_round_trips_a_fresh_scan() {
+    let root = TempDir::new("access-launcher-cache-round-trip");
+    let dirs = vec![write_apps(&root)];
+    let (entries, report) = collect_desktop_entries_from_with_report(&dirs, &[]);
+    assert_eq!(entries.len(), 2);
+
+    let cache = root.path.join("cache/entries.json");
+    let key = CacheKey::current(&dirs, &[]);
+    let broken = vec![dirs[0].join("broken.desktop")];
+    write_entries_cache(&cache, &key, &entries, &broken).expect("write cache");
+
+    let cached = read_entries_cache(&cache, &key).expect("fresh cache");
+    assert_eq!(cached.broken_exec, broken);
+    // DesktopEntry has no PartialEq; every field shows in its Debug output.
+    assert_eq!(format!("{:?}", cached.entries), format!("{entries:?}"));
+    assert!(report.broken_exec.is_empty());
+}
+
+#[test]
+fn entries_cache_is_ignored_when_stale_or_written_for_other_settings() {
+    let root = TempDir::new("access-launcher-cache-stale");
+    let dirs = vec![write_apps(&root)];
+    let (entries, _) = collect_desktop_entries_from_with_report(&dirs, &[]);
+    let cache = root.path.join("cache/entries.json");
+    let key = CacheKey::current(&dirs, &[]);
+    write_entries_cache(&cache, &key, &entries, &[]).expect("write cache");
+    assert!(read_entries_cache(&cache, &key).is_some());
+
+    let other = CacheKey {
+        lang: Some("xx_XX.UTF-8".to_string()),
+        ..key.clone()
+    };
+    assert!(read_entries_cache(&cache, &other).is_none());
+
+    // A cache older than the directories it lists is stale.
+    let long_ago = SystemTime::UNIX_EPOCH + Duration::from_secs(60);
+    set_modified(&cache, long_ago);
+    assert!(read_entries_cache(&cache, &key).is_none());
+
+    fs::write(&cache, "{not json").expect("corrupt cache");
+    assert!(read_entries_cache(&cache, &key).is_none());
+    assert!(read_entries_cache(&root.path.join("missing.json"), &key).is_none());
+}
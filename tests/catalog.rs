@@ -0,0 +1,56 @@
+use access_launcher::catalog::Catalog;
+use access_launcher::desktop::DesktopEntry;
+
+fn entry(name: &str, categories: &str) -> DesktopEntry {
+    DesktopEntry {
+        name: name.to_string(),
+        exec: "app".to_string(),
+        categories: categories.to_string(),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn catalog_builds_category_map_from_entries() {
+    let catalog = Catalog::new(vec![
+        entry("Browser", "Network;WebBrowser;"),
+        entry("Editor", "Utility;TextEditor;"),
+        entry("Chat", "Network;"),
+    ]);
+    assert_eq!(catalog.category_map.get("Internet"), Some(&vec![0, 2]));
+    assert!(!catalog.is_empty());
+    assert!(Catalog::default().is_empty());
+}
+
+#[test]
+fn catalog_rebuild_follows_edited_entries() {
+    let mut catalog = Catalog::new(vec![entry("Browser", "Network;")]);
+    catalog.entries.push(entry("Mystery", ""));
+    catalog.rebuild();
+    assert_eq!(catalog.category_map.get("Other"), Some(&vec![1]));
+
+    catalog.entries.remove(0);
+    catalog.rebuild();
+    assert_eq!(catalog.category_map.get("Internet"), None);
+    assert_eq!(catalog.category_map.get("Other"), Some(&vec![0]));
+}
+
+#[test]
+fn catalog_finds_entries_by_exec_program() {
+    let terminal = DesktopEntry {
+        exec: "env GDK_BACKEND=x11 /usr/bin/gnome-terminal --window".to_string(),
+        ..entry("Terminal", "System;TerminalEmulator;")
+    };
+    let catalog = Catalog::new(vec![entry("Browser", "Network;"), terminal]);
+    let found = catalog
+        .find_by_program("gnome-terminal")
+        .map(|entry| entry.name.as_str());
+    assert_eq!(found, Some("Terminal"));
+    assert_eq!(
+        catalog
+            .find_by_program("/usr/local/bin/gnome-terminal")
+            .map(|entry| entry.name.as_str()),
+        Some("Terminal")
+    );
+    assert!(catalog.find_by_program("xterm").is_none());
+}
@@ -0,0 +1,79 @@
+use access_launcher::category_tree::{build_category_tree, humanize_category, CategoryNode};
+use access_launcher::desktop::DesktopEntry;
+
+fn entry(name: &str, categories: &str) -> DesktopEntry {
+    DesktopEntry {
+        name: name.to_string(),
+        exec: "app".to_string(),
+        categories: categories.to_string(),
+        ..Default::default()
+    }
+}
+
+fn node<'a>(nodes: &'a [CategoryNode], id: &str) -> &'a CategoryNode {
+    nodes
+        .iter()
+        .find(|node| node.id == id)
+        .unwrap_or_else(|| panic!("missing node {id}"))
+}
+
+#[test]
+fn humanize_category_splits_camel_case_and_acronyms() {
+    assert_eq!(humanize_category("WebBrowser"), "Web Browser");
+    assert_eq!(humanize_category("IRCClient"), "IRC Client");
+    assert_eq!(humanize_category("2DGraphics"), "2D Graphics");
+    assert_eq!(humanize_category("IDE"), "IDE");
+}
+
+#[test]
+fn category_tree_nests_subcategories_under_main_categories() {
+    let entries = vec![
+        entry("Builder", "Development;IDE;"),
+        entry("Debugger", "Development;Debugger;"),
+        entry("Notes", "Development;"),
+    ];
+    let tree = build_category_tree(&entries);
+
+    assert_eq!(tree.len(), 1);
+    let development = node(&tree, "Development");
+    assert_eq!(development.entries, vec![0, 1, 2]);
+    let children: Vec<&str> = development
+        .children
+        .iter()
+        .map(|child| child.label.as_str())
+        .collect();
+    assert_eq!(children, vec!["Debugger", "IDE"]);
+    assert_eq!(node(&development.children, "IDE").entries, vec![0]);
+}
+
+#[test]
+fn category_tree_places_shared_subcategories_by_listed_parent() {
+    let entries = vec![
+        entry("Mail", "Office;Email;"),
+        entry("Orphan Mail", "Email;"),
+    ];
+    let tree = build_category_tree(&entries);
+
+    assert_eq!(
+        node(&node(&tree, "Office").children, "Email").entries,
+        vec![0]
+    );
+    // Without a listed parent, the first registered parent (Network) is used.
+    assert_eq!(
+        node(&node(&tree, "Network").children, "Email").entries,
+        vec![1]
+    );
+}
+
+#[test]
+fn category_tree_collects_unknown_entries_in_other() {
+    let entries = vec![
+        entry("Mystery", "X-Custom;"),
+        entry("Player", "AudioVideo;"),
+    ];
+    let tree = build_category_tree(&entries);
+
+    assert_eq!(tree.last().map(|node| node.id.as_str()), Some("Other"));
+    assert_eq!(node(&tree, "Other").entries, vec![0]);
+    assert!(node(&tree, "AudioVideo").children.is_empty());
+}
@@ -0,0 +1,153 @@
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+struct TempDir {
+    path: PathBuf,
+}
+
+impl TempDir {
+    fn new(stem: &str) -> Self {
+        let path =
+            env::temp_dir().join(format!("access-launcher-cli-{stem}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir_all(path.join("apps")).expect("create temp dir");
+        fs::write(
+            path.join("apps/sample.desktop"),
+            "[Desktop Entry]\nType=Application\nName=Sample\nExec=true\n",
+        )
+        .expect("write sample app");
+        fs::write(
+            path.join("link.desktop"),
+            "[Desktop Entry]\nType=Link\nName=Website\nURL=https://example.com\n",
+        )
+        .expect("write link");
+        Self { path }
+    }
+
+    /// Runs the launcher binary on `args`, scanning only this directory's
+    /// `apps` and keeping its configuration and state in here too.
+    fn run(&self, args: &[&str]) -> Output {
+        Command::new(env!("CARGO_BIN_EXE_access-launcher"))
+            .arg("--entries-dir")
+            .arg(self.path.join("apps"))
+            .args(args)
+            .env("XDG_CONFIG_HOME", self.path.join("config"))
+            .env("XDG_STATE_HOME", self.path.join("state"))
+            .env("XDG_CACHE_HOME", self.path.join("cache"))
+            .env("XDG_DATA_HOME", self.path.join("data"))
+            .output()
+            .expect("run access-launcher")
+    }
+
+    fn file(&self, relative: &str) -> String {
+        self.path.join(relative).display().to_string()
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.path);
+    }
+}
+
+fn write_refusing_hook(config: &Path) {
+    use std::os::unix::fs::PermissionsExt;
+    let dir = config.join("access-launcher");
+    fs::create_dir_all(&dir).expect("create config dir");
+    let hook = dir.join("pre-launch");
+    fs::write(&hook, "#!/bin/sh\nexit 1\n").expect("write hook");
+    fs::set_permissions(&hook, fs::Permissions::from_mode(0o755)).expect("chmod hook");
+}
+
+#[test]
+fn listed_apps_print_launch_and_explain_with_status_zero() {
+    let dir = TempDir::new("present");
+    let output = dir.run(&["--print-exec", "sample"]);
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "true\n");
+
+    assert_eq!(
+        dir.run(&["--launch", "sample.desktop"]).status.code(),
+        Some(0)
+    );
+    assert_eq!(dir.run(&["--why", "sample"]).status.code(), Some(0));
+    let output = dir.run(&["--list"]);
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "sample.desktop\tSample\n"
+    );
+}
+
+#[test]
+fn missing_apps_and_files_exit_with_status_two() {
+    let dir = TempDir::new("missing");
+    for args in [
+        vec!["--launch", "missing"],
+        vec!["--print-exec", "missing"],
+        vec!["--why", "missing"],
+    ] {
+        let output = dir.run(&args);
+        assert_eq!(output.status.code(), Some(2), "{args:?}");
+        assert!(!output.stderr.is_empty());
+    }
+    let missing = dir.file("missing.desktop");
+    assert_eq!(dir.run(&[&missing]).status.code(), Some(2));
+}
+
+#[test]
+fn refused_launches_exit_with_status_three() {
+    let dir = TempDir::new("refused");
+    write_refusing_hook(&dir.path.join("config"));
+    assert_eq!(dir.run(&["--launch", "sample"]).status.code(), Some(3));
+    // Printing the command does not run the hook.
+    assert_eq!(dir.run(&["--print-exec", "sample"]).status.code(), Some(0));
+}
+
+#[test]
+fn invalid_arguments_and_files_exit_with_status_four() {
+    let dir = TempDir::new("invalid");
+    assert_eq!(dir.run(&["--launch"]).status.code(), Some(4));
+    assert_eq!(dir.run(&["--why"]).status.code(), Some(4));
+    let link = dir.file("link.desktop");
+    assert_eq!(dir.run(&[&link]).status.code(), Some(4));
+}
+
+#[test]
+fn export_menu_honors_entries_dir_and_verbosity() {
+    let dir = TempDir::new("export");
+    let menu = dir.file("menu");
+    let output = dir.run(&["--export-menu", &menu, "--verbose"]);
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        format!("Linked 1 applications into {menu}\n")
+    );
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Scanning"));
+    assert!(dir.path.join("menu/sample.desktop").exists());
+}
+
+#[test]
+fn short_v_prints_the_version_and_vv_logs() {
+    let dir = TempDir::new("version");
+    let version = format!("{} {}\n", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION"));
+    let output = dir.run(&["-v"]);
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(String::from_utf8_lossy(&output.stdout), version);
+    assert!(output.stderr.is_empty());
+    let output = dir.run(&["-vv", "--list"]);
+    assert_eq!(output.status.code(), Some(0));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Scanning"));
+}
+
+#[test]
+fn why_passes_over_missing_directories_quietly() {
+    let dir = TempDir::new("why-missing");
+    let missing = dir.file("missing");
+    let output = dir.run(&["--entries-dir", &missing, "--why", "sample", "--verbose"]);
+    assert_eq!(output.status.code(), Some(0));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(!stderr.contains("Cannot read directory"), "{stderr}");
+}
@@ -0,0 +1,48 @@
+//! Collation depends on the process-wide locale, so these tests live in
+//! their own binary where switching `LC_COLLATE` cannot affect other tests.
+
+use access_launcher::desktop::{sort_by_name, DesktopEntry};
+use std::ffi::{c_char, c_int};
+
+extern "C" {
+    fn setlocale(category: c_int, locale: *const c_char) -> *mut c_char;
+}
+
+/// `LC_COLLATE` in glibc and musl.
+const LC_COLLATE: c_int = 3;
+
+fn sorted(names: &[&str]) -> Vec<String> {
+    let mut entries: Vec<DesktopEntry> = names
+        .iter()
+        .map(|name| DesktopEntry {
+            name: name.to_string(),
+            ..Default::default()
+        })
+        .collect();
+    sort_by_name(&mut entries);
+    entries.into_iter().map(|entry| entry.name).collect()
+}
+
+#[test]
+fn sort_by_name_ignores_ascii_case_without_a_locale() {
+    assert_eq!(
+        sorted(&["Zebra", "files", "Apple", "apple"]),
+        ["Apple", "apple", "files", "Zebra"]
+    );
+}
+
+#[test]
+fn sort_by_name_collates_accented_names_under_a_known_locale() {
+    // SAFETY: the strings are NUL-terminated; the other test here sorts the
+    // same way under either locale, so running alongside it is fine.
+    if unsafe { setlocale(LC_COLLATE, c"en_US.UTF-8".as_ptr()) }.is_null() {
+        eprintln!("en_US.UTF-8 is not installed; skipping");
+        return;
+    }
+    let names = sorted(&["Zebra", "Über", "Éditeur", "apple", "Files", "Ulysses"]);
+    unsafe { setlocale(LC_COLLATE, c"C".as_ptr()) };
+    assert_eq!(
+        names,
+        ["apple", "Éditeur", "Files", "Über", "Ulysses", "Zebra"]
+    );
+}
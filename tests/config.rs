@@ -0,0 +1,432 @@
+use access_launcher::config::{
+    load_config_from, parse_category_rules, read_blocklist, read_name_overrides, read_wrappers,
+    set_config_value, write_blocklist, write_name_overrides, CategoryOrder, Config, Keymap,
+    ProgramView, RowDensity, KEYMAP_ACTIONS, MAX_ROW_PADDING, MAX_WIDGET_MARGIN,
+};
+use access_launcher::desktop::MAX_CATEGORY_RULES;
+use access_launcher::search::{SearchMode, SearchScope};
+use std::collections::{BTreeMap, BTreeSet};
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+#[test]
+fn config_defaults_when_empty() {
+    let config = Config::parse("");
+    assert!(!config.show_empty_categories);
+    assert_eq!(config.search_mode, SearchMode::Substring);
+    assert_eq!(config.search_scope, SearchScope::NameKeywords);
+    assert_eq!(config.most_used_limit, 20);
+    assert_eq!(config.max_visible_results, 500);
+    assert!(!config.hide_terminal_apps);
+    assert!(!config.terminal_apps_category);
+    assert_eq!(config.new_app_days, 7);
+    assert!(!config.new_apps_category);
+    assert!(!config.quit_on_launch);
+    assert!(!config.show_exec_subtitle);
+    assert_eq!(config.idle_quit_seconds, 0);
+    assert!(!config.high_contrast);
+    assert_eq!(config.font_scale, 1.0);
+    assert!(!config.first_run_completed);
+    assert!(!config.dedup_symlinks);
+    assert!(!config.show_broken_apps);
+    assert!(!config.speak);
+    assert!(!config.compact_mode);
+    assert!(config.start_maximized);
+    assert!(!config.start_fullscreen);
+    assert!(!config.confirm_launch);
+    assert!(config.pinned_categories.is_empty());
+    assert_eq!(config.activate_shortcut, "<Control>Return");
+}
+
+#[test]
+fn config_parses_known_keys_and_skips_noise() {
+    let config = Config::parse(
+        r#"
+# comment line
+show_empty_categories = true
+search_mode=fuzzy
+search_scope = comment
+wrap_navigation=yes
+hide_terminal_apps=true
+quit_on_launch=1
+confirm_launch=yes
+dedup_symlinks=true
+new_app_days = 3
+new_apps_category=yes
+speak=true
+compact_mode=yes
+start_maximized=false
+start_fullscreen=1
+pinned_categories = development, Nonsense,Internet,Development
+activate_shortcut = <Alt>Return
+unknown_key=whatever
+not a pair
+"#,
+    );
+    assert!(config.show_empty_categories);
+    assert_eq!(config.search_mode, SearchMode::Fuzzy);
+    assert_eq!(config.search_scope, SearchScope::NameKeywordsComment);
+    assert!(config.wrap_navigation);
+    assert!(config.hide_terminal_apps);
+    assert!(config.quit_on_launch);
+    assert!(config.confirm_launch);
+    assert!(config.dedup_symlinks);
+    assert_eq!(config.new_app_days, 3);
+    assert!(config.new_apps_category);
+    assert!(config.speak);
+    assert!(config.compact_mode);
+    assert!(!config.start_maximized);
+    assert!(config.start_fullscreen);
+    assert_eq!(config.pinned_categories, ["Development", "Internet"]);
+    assert_eq!(config.activate_shortcut, "<Alt>Return");
+}
+
+#[test]
+fn config_apply_overrides_previous_values() {
+    let mut config = Config::parse("show_empty_categories=true");
+    config.apply("show_empty_categories=false");
+    assert!(!config.show_empty_categories);
+}
+
+#[test]
+fn config_parses_idle_quit_seconds() {
+    assert_eq!(
+        Config::parse("idle_quit_seconds=300").idle_quit_seconds,
+        300
+    );
+    assert_eq!(Config::parse("idle_quit_seconds=-5").idle_quit_seconds, 0);
+}
+
+#[test]
+fn config_parses_accessibility_setup() {
+    let config = Config::parse("high_contrast=true\nfont_scale=1.5\nfirst_run_completed=true");
+    assert!(config.high_contrast);
+    assert_eq!(config.font_scale, 1.5);
+    assert!(config.first_run_completed);
+    assert_eq!(Config::parse("font_scale=10").font_scale, 3.0);
+    assert_eq!(Config::parse("font_scale=NaN").font_scale, 1.0);
+}
+
+#[test]
+fn config_parses_row_spacing() {
+    assert_eq!(Config::default().row_density, RowDensity::Compact);
+    assert_eq!(Config::default().row_spacing(), (None, None));
+    assert_eq!(Config::default().widget_margin(), 6);
+
+    let comfortable = Config::parse("row_density = Comfortable");
+    assert_eq!(comfortable.row_density, RowDensity::Comfortable);
+    assert_eq!(comfortable.row_spacing(), (Some(12), Some(56)));
+    assert_eq!(comfortable.widget_margin(), 12);
+    assert_eq!(comfortable.row_density.toggled(), RowDensity::Compact);
+
+    // Explicit values override the preset, one at a time.
+    let custom = Config::parse("row_density=comfortable\nrow_padding=4\nrow_min_height=lots");
+    assert_eq!(custom.row_spacing(), (Some(4), Some(56)));
+    assert_eq!(
+        Config::parse("row_padding=1000").row_spacing(),
+        (Some(MAX_ROW_PADDING), None)
+    );
+    assert_eq!(Config::parse("widget_margin=9").widget_margin(), 9);
+    assert_eq!(
+        Config::parse("widget_margin=500").widget_margin(),
+        MAX_WIDGET_MARGIN
+    );
+    assert_eq!(
+        Config::parse("row_density=huge").row_density,
+        RowDensity::Compact
+    );
+}
+
+#[test]
+fn config_parses_most_used_limit() {
+    assert_eq!(Config::parse("most_used_limit = 5").most_used_limit, 5);
+    assert_eq!(Config::parse("most_used_limit=lots").most_used_limit, 20);
+    assert_eq!(
+        Config::parse("max_visible_results=50").max_visible_results,
+        50
+    );
+    assert_eq!(
+        Config::parse("max_visible_results=0").max_visible_results,
+        0
+    );
+}
+
+#[test]
+fn initial_category_prefers_remembered_then_default() {
+    let available = ["Recent", "Most Used", "Internet", "Office"];
+    let config = Config::parse("default_category=office");
+    assert_eq!(config.initial_category(None, &available), Some("Office"));
+    // The remembered category is ignored unless remember_last_category is set.
+    assert_eq!(
+        config.initial_category(Some("Internet"), &available),
+        Some("Office")
+    );
+
+    let config = Config::parse("default_category=Office\nremember_last_category=true");
+    assert_eq!(
+        config.initial_category(Some("Internet"), &available),
+        Some("Internet")
+    );
+    assert_eq!(
+        config.initial_category(Some("Games"), &available),
+        Some("Office")
+    );
+}
+
+#[test]
+fn initial_category_ignores_unknown_default() {
+    let available = ["Recent", "Internet"];
+    assert_eq!(
+        Config::parse("default_category=All").initial_category(None, &available),
+        None
+    );
+    assert_eq!(Config::parse("").initial_category(None, &available), None);
+}
+
+#[test]
+fn config_parses_excluded_subdirs() {
+    assert_eq!(Config::default().excluded_subdirs, vec!["screensavers"]);
+    let config = Config::parse("excluded_subdirs = screensavers; wine ;");
+    assert_eq!(config.excluded_subdirs, vec!["screensavers", "wine"]);
+    assert!(Config::parse("excluded_subdirs=")
+        .excluded_subdirs
+        .is_empty());
+}
+
+#[test]
+fn config_parses_appimage_dirs() {
+    assert!(Config::default().appimage_dirs.is_empty());
+    let config = Config::parse("appimage_dirs = /opt/apps; /srv/AppImages ;");
+    assert_eq!(
+        config.appimage_dirs,
+        [PathBuf::from("/opt/apps"), PathBuf::from("/srv/AppImages")]
+    );
+}
+
+#[test]
+fn config_parses_remember_program_position() {
+    assert!(!Config::default().remember_program_position);
+    assert!(Config::parse("remember_program_position=true").remember_program_position);
+}
+
+#[test]
+fn config_parses_category_headings() {
+    assert!(!Config::default().category_headings);
+    assert!(Config::parse("category_headings=yes").category_headings);
+}
+
+#[test]
+fn config_parses_entries_cache() {
+    assert!(!Config::default().entries_cache);
+    assert!(Config::parse("entries_cache = yes").entries_cache);
+    assert!(!Config::parse("entries_cache=off").entries_cache);
+}
+
+#[test]
+fn name_overrides_round_trip_and_skip_noise() {
+    let path = env::temp_dir().join(format!(
+        "access-launcher-names-{}/name-overrides.conf",
+        std::process::id()
+    ));
+    assert!(read_name_overrides(&path).is_empty());
+
+    let overrides = BTreeMap::from([
+        ("libreoffice-calc.desktop".to_string(), "Calc".to_string()),
+        (
+            "org.gnome.Terminal.desktop".to_string(),
+            "Shell = Home".to_string(),
+        ),
+    ]);
+    write_name_overrides(&path, &overrides).expect("write overrides");
+    assert_eq!(read_name_overrides(&path), overrides);
+
+    fs::write(
+        &path,
+        "# comment\n\nnoise\nempty.desktop=\n a.desktop = A \n",
+    )
+    .unwrap();
+    assert_eq!(
+        read_name_overrides(&path),
+        BTreeMap::from([("a.desktop".to_string(), "A".to_string())])
+    );
+    let _ = fs::remove_dir_all(path.parent().unwrap());
+}
+
+#[test]
+fn wrappers_map_desktop_ids_to_commands() {
+    let dir = env::temp_dir().join(format!("access-launcher-wrappers-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("wrappers.conf");
+    assert!(read_wrappers(&path).is_empty());
+
+    fs::write(
+        &path,
+        "# games\nsteam.desktop = gamemoderun\nnet.lutris.Lutris=mangohud --dlsym\nempty=\n",
+    )
+    .unwrap();
+    assert_eq!(
+        read_wrappers(&path),
+        BTreeMap::from([
+            (
+                "net.lutris.Lutris.desktop".to_string(),
+                "mangohud --dlsym".to_string()
+            ),
+            ("steam.desktop".to_string(), "gamemoderun".to_string()),
+        ])
+    );
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn config_parses_program_view() {
+    assert_eq!(Config::default().program_view, ProgramView::List);
+    assert_eq!(
+        Config::parse("program_view = Grid").program_view,
+        ProgramView::Grid
+    );
+    assert_eq!(
+        Config::parse("program_view=tiles").program_view,
+        ProgramView::List
+    );
+}
+
+#[test]
+fn set_config_value_rewrites_or_appends_the_key() {
+    let contents = "# program_view=list\nsearch_mode=fuzzy\nprogram_view = list\n";
+    assert_eq!(
+        set_config_value(contents, "program_view", "grid"),
+        "# program_view=list\nsearch_mode=fuzzy\nprogram_view=grid\n"
+    );
+    assert_eq!(
+        set_config_value("search_mode=fuzzy", "program_view", "grid"),
+        "search_mode=fuzzy\nprogram_view=grid\n"
+    );
+    assert_eq!(
+        set_config_value("", "program_view", "grid"),
+        "program_view=grid\n"
+    );
+    let updated = set_config_value(contents, "program_view", "grid");
+    assert_eq!(Config::parse(&updated).program_view, ProgramView::Grid);
+}
+
+#[test]
+fn load_config_from_layers_user_over_system() {
+    let dir = env::temp_dir().join(format!("access-launcher-layered-{}", std::process::id()));
+    fs::create_dir_all(&dir).expect("create temp dir");
+    let system = dir.join("system");
+    let user = dir.join("user");
+    fs::write(&system, "default_category=Office\nwrap_navigation=true\n").expect("write");
+    fs::write(&user, "default_category=Games\n").expect("write");
+
+    let config = load_config_from(&[system.clone(), dir.join("missing"), user]);
+    assert_eq!(config.default_category.as_deref(), Some("Games"));
+    assert!(config.wrap_navigation);
+
+    let config = load_config_from(&[dir.join("missing")]);
+    assert_eq!(config.default_category, None);
+
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn config_parses_category_order() {
+    assert_eq!(Config::parse("").category_order, CategoryOrder::Fixed);
+    assert_eq!(
+        Config::parse("category_order=Frequency").category_order,
+        CategoryOrder::Frequency
+    );
+    assert_eq!(
+        Config::parse("category_order=alphabetical\ncategory_order=bogus").category_order,
+        CategoryOrder::Alphabetical
+    );
+}
+
+#[test]
+fn blocklist_round_trips_and_skips_noise() {
+    let path = env::temp_dir().join(format!(
+        "access-launcher-blocklist-{}/blocklist.conf",
+        std::process::id()
+    ));
+    assert!(read_blocklist(&path).is_empty());
+
+    let blocklist = BTreeSet::from(["htop.desktop".to_string(), "xterm.desktop".to_string()]);
+    write_blocklist(&path, &blocklist).expect("write blocklist");
+    assert_eq!(read_blocklist(&path), blocklist);
+
+    fs::write(&path, "# comment\n\n  vim.desktop  \n").expect("write");
+    assert_eq!(
+        read_blocklist(&path),
+        BTreeSet::from(["vim.desktop".to_string()])
+    );
+
+    if let Some(dir) = path.parent() {
+        let _ = fs::remove_dir_all(dir);
+    }
+}
+
+#[test]
+fn keymap_parses_bindings_and_reports_bad_lines() {
+    let (keymap, warnings) = Keymap::parse(
+        "# comment\n\
+         quit = <Control>w ; <Alt>F4\n\
+         about=\n\
+         no equals sign\n\
+         fly=<Control>x\n",
+    );
+    assert_eq!(
+        keymap.bindings.get("quit"),
+        Some(&vec!["<Control>w".to_string(), "<Alt>F4".to_string()])
+    );
+    assert_eq!(keymap.bindings.get("about"), Some(&Vec::new()));
+    assert_eq!(
+        warnings,
+        [
+            "line 4: expected action=shortcut",
+            "line 5: unknown action fly"
+        ]
+    );
+}
+
+#[test]
+fn keymap_falls_back_to_default_accels() {
+    let (keymap, _) = Keymap::parse("about=\n");
+    let action = |name: &str| *KEYMAP_ACTIONS.iter().find(|a| a.name == name).unwrap();
+    assert_eq!(keymap.accels(&action("about")), Vec::<String>::new());
+    assert_eq!(
+        keymap.accels(&action("focus-other-pane")),
+        ["F6", "<Shift>F6"]
+    );
+}
+
+#[test]
+fn category_rules_parse_in_order_and_report_bad_lines() {
+    let (rules, warnings) = parse_category_rules(
+        "# vendor games\n\
+         X-.*Game.*=Games\n\
+         X-Edu.*=Office\n\
+         no separator\n\
+         (Game=Games\n\
+         X-Toy=Toys\n",
+    );
+    let buckets: Vec<&str> = rules.iter().map(|rule| rule.bucket).collect();
+    assert_eq!(buckets, ["Games", "Office"]);
+    assert_eq!(
+        warnings,
+        [
+            "line 4: expected pattern=category",
+            "line 5: invalid pattern (Game: unclosed group",
+            "line 6: unknown category Toys",
+        ]
+    );
+}
+
+#[test]
+fn category_rules_are_capped() {
+    let contents: String = (0..MAX_CATEGORY_RULES + 5)
+        .map(|i| format!("X-Rule{i}=Games\n"))
+        .collect();
+    let (rules, warnings) = parse_category_rules(&contents);
+    assert_eq!(rules.len(), MAX_CATEGORY_RULES);
+    assert_eq!(warnings.len(), 1);
+}
@@ -1,11 +1,26 @@
+use access_launcher::config::{read_extra_dirs, Config};
 use access_launcher::desktop::{
-    build_category_map, exec_looks_valid, matches_lang_tag, normalize_lang_tag, parse_bool,
-    parse_desktop_entry, DesktopEntry,
+    add_appimage_entries, add_broken_entries, appimage_entry, appimage_name, apply_name_overrides,
+    build_category_map, build_category_map_with_rules, collect_desktop_entries,
+    collect_desktop_entries_from, collect_desktop_entries_from_with_report,
+    dedup_symlinked_entries, desktop_id_owner, entries_to_json, entry_diagnostics,
+    exec_looks_valid, explain_desktop_file, export_desktop_links, fallback_name,
+    filter_terminal_apps, find_desktop_files, find_icon_file, format_diagnostics,
+    group_by_raw_category, is_flatpak_entry, mark_duplicate_names, mark_recently_installed,
+    matches_lang_tag, merge_duplicate_entries, new_app_indices, normalize_lang_tag, parse_bool,
+    parse_current_desktops, parse_desktop_entry, parse_desktop_entry_result,
+    parse_desktop_entry_with_hidden, parse_entries_dirs, pin_categories, remove_blocklisted,
+    should_merge_duplicate, spec_version_supported, strip_icon_extension, terminal_app_indices,
+    visible_categories, CategoryRule, DesktopAction, DesktopEntry, DesktopParseError, ScanReport,
+    CATEGORIES,
 };
+use access_launcher::search::{search_entries, SearchMode, SearchScope};
+use std::collections::{BTreeMap, BTreeSet};
 use std::env;
 use std::fs;
 use std::path::PathBuf;
 use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::SystemTime;
 
 struct TempFile {
     path: PathBuf,
@@ -29,6 +44,37 @@ impl Drop for TempFile {
     }
 }
 
+struct TempDir {
+    path: PathBuf,
+}
+
+impl TempDir {
+    fn new(stem: &str) -> Self {
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+        let id = COUNTER.fetch_add(1, Ordering::Relaxed);
+        let pid = std::process::id();
+        let path = env::temp_dir().join(format!("{stem}-{pid}-{id}"));
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir_all(&path).expect("create temp dir");
+        Self { path }
+    }
+
+    fn write(&self, relative: &str, contents: &str) -> PathBuf {
+        let path = self.path.join(relative);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).expect("create parent dir");
+        }
+        fs::write(&path, contents).expect("write temp file");
+        path
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.path);
+    }
+}
+
 #[test]
 fn normalize_lang_tag_strips_variants() {
     assert_eq!(normalize_lang_tag("en_US.UTF-8"), "en_US");
@@ -73,6 +119,293 @@ Categories=Utility;Development;
     assert_eq!(entry.categories, "Utility;Development;");
 }
 
+#[test]
+fn parse_desktop_entry_skips_a_leading_bom() {
+    let file = TempFile::new(
+        "\u{feff}[Desktop Entry]\nType=Application\nName=Notepad Port\nExec=notepad\n",
+        "access-launcher-bom",
+    );
+    let mut line_buf = String::new();
+    let entry = parse_desktop_entry(&file.path, None, None, &mut line_buf).expect("entry present");
+    assert_eq!(entry.name, "Notepad Port");
+    assert_eq!(entry.exec, "notepad");
+
+    let fields = explain_desktop_file(&file.path, None, None);
+    assert_eq!(
+        fields.last().map(|(_, value)| value.as_str()),
+        Some("shown")
+    );
+}
+
+#[test]
+fn parse_desktop_entry_reads_crlf_files_like_lf_files() {
+    let lf = "[Desktop Entry]\nType=Application\nName=Editor\nName[fr]=Éditeur\nExec=editor %F\n\
+              Categories=Utility;TextEditor;\nKeywords=text;\nTerminal=false\n\
+              Actions=new;\n\n[Desktop Action new]\nName=New Window\nExec=editor --new\n";
+    let lf_file = TempFile::new(lf, "access-launcher-lf");
+    let crlf_file = TempFile::new(&lf.replace('\n', "\r\n"), "access-launcher-crlf");
+    let parse = |path| {
+        let mut entry = parse_desktop_entry(path, Some("fr_FR.UTF-8"), None, &mut String::new())
+            .expect("entry present");
+        // Only the file name, and so the ID, differs between the two.
+        entry.id.clear();
+        entry.path = PathBuf::new();
+        entry.modified = None;
+        entry
+    };
+    let from_crlf = parse(&crlf_file.path);
+    // DesktopEntry has no PartialEq; every field shows in its Debug output.
+    assert_eq!(
+        format!("{from_crlf:?}"),
+        format!("{:?}", parse(&lf_file.path))
+    );
+    assert_eq!(from_crlf.name, "Éditeur");
+    assert_eq!(from_crlf.exec, "editor %F");
+    assert_eq!(from_crlf.actions[0].exec, "editor --new");
+}
+
+#[test]
+fn parse_desktop_entry_reads_startup_hints() {
+    let file = TempFile::new(
+        r#"
+[Desktop Entry]
+Type=Application
+Name=Startup App
+Exec=app
+StartupNotify=true
+StartupWMClass=startup-app
+"#,
+        "access-launcher-startup",
+    );
+    let mut line_buf = String::new();
+    let entry = parse_desktop_entry(&file.path, None, None, &mut line_buf).expect("entry present");
+    assert!(entry.startup_notify);
+    assert_eq!(entry.startup_wm_class.as_deref(), Some("startup-app"));
+}
+
+#[test]
+fn parse_desktop_entry_reads_dbus_activatable() {
+    let file = TempFile::new(
+        r#"
+[Desktop Entry]
+Type=Application
+Name=Activatable App
+Exec=app
+DBusActivatable=true
+"#,
+        "access-launcher-dbus",
+    );
+    let mut line_buf = String::new();
+    let entry = parse_desktop_entry(&file.path, None, None, &mut line_buf).expect("entry present");
+    assert!(entry.dbus_activatable);
+}
+
+#[test]
+fn parse_desktop_entry_reads_prefers_non_default_gpu() {
+    let file = TempFile::new(
+        r#"
+[Desktop Entry]
+Type=Application
+Name=Game
+Exec=game
+PrefersNonDefaultGPU=true
+"#,
+        "access-launcher-gpu",
+    );
+    let mut line_buf = String::new();
+    let entry = parse_desktop_entry(&file.path, None, None, &mut line_buf).expect("entry present");
+    assert!(entry.prefers_non_default_gpu);
+}
+
+#[test]
+fn entries_to_json_exports_startup_wm_class() {
+    let entry = DesktopEntry {
+        name: "Startup App".to_string(),
+        exec: "app".to_string(),
+        startup_wm_class: Some("startup-app".to_string()),
+        ..Default::default()
+    };
+    let json = access_launcher::json::parse(&entries_to_json(&[entry])).expect("valid JSON");
+    let app = &json.as_array().expect("array")[0];
+    assert_eq!(
+        app.get("startup_wm_class").and_then(|value| value.as_str()),
+        Some("startup-app")
+    );
+}
+
+#[test]
+fn parse_desktop_entry_finds_entry_group_after_action_groups() {
+    let file = TempFile::new(
+        r#"
+[Desktop Action new-window]
+Name=New Window
+Name[de]=Neues Fenster
+Exec=browser --new-window
+
+[Desktop Entry]
+Type=Application
+Name=Browser
+Exec=browser %u
+Actions=private;new-window;unlisted-typo;
+
+[Desktop Action private]
+Name=Private Window
+Exec=browser --private
+
+[Desktop Action unlisted]
+Name=Unlisted
+Exec=browser --unlisted
+"#,
+        "access-launcher-action-groups",
+    );
+    let mut line_buf = String::new();
+    let entry =
+        parse_desktop_entry(&file.path, Some("de_DE"), None, &mut line_buf).expect("entry present");
+    assert_eq!(entry.name, "Browser");
+    assert_eq!(entry.exec, "browser %u");
+    assert_eq!(
+        entry.actions,
+        vec![
+            DesktopAction {
+                id: "private".to_string(),
+                name: "Private Window".to_string(),
+                exec: "browser --private".to_string(),
+            },
+            DesktopAction {
+                id: "new-window".to_string(),
+                name: "Neues Fenster".to_string(),
+                exec: "browser --new-window".to_string(),
+            },
+        ]
+    );
+}
+
+#[test]
+fn parse_desktop_entry_reads_spec_version() {
+    let file = TempFile::new(
+        r#"
+[Desktop Entry]
+Version=1.5
+Type=Application
+Name=Versioned
+Exec=app
+"#,
+        "access-launcher-version",
+    );
+    let mut line_buf = String::new();
+    let entry = parse_desktop_entry(&file.path, None, None, &mut line_buf).expect("entry present");
+    assert_eq!(entry.version.as_deref(), Some("1.5"));
+    assert!(entries_to_json(&[entry]).contains("\"version\": \"1.5\""));
+}
+
+#[test]
+fn parse_desktop_entry_reads_implements_and_notification_hints() {
+    let file = TempFile::new(
+        r#"
+[Desktop Entry]
+Type=Application
+Name=Player
+Exec=app
+Implements=org.mpris.MediaPlayer2; org.freedesktop.FileManager1;;
+X-GNOME-UsesNotifications=true
+"#,
+        "access-launcher-implements",
+    );
+    let mut line_buf = String::new();
+    let entry = parse_desktop_entry(&file.path, None, None, &mut line_buf).expect("entry present");
+    assert_eq!(
+        entry.implements,
+        vec!["org.mpris.MediaPlayer2", "org.freedesktop.FileManager1"]
+    );
+    assert!(entry.uses_notifications);
+
+    let fields = entry_diagnostics(&entry, &[]);
+    let value = |label: &str| {
+        fields
+            .iter()
+            .find(|(name, _)| *name == label)
+            .map(|(_, value)| value.as_str())
+    };
+    assert_eq!(
+        value("Implements"),
+        Some("org.mpris.MediaPlayer2, org.freedesktop.FileManager1")
+    );
+    assert_eq!(value("Uses notifications"), Some("yes"));
+
+    let json = access_launcher::json::parse(&entries_to_json(&[entry])).expect("valid JSON");
+    let app = &json.as_array().expect("array")[0];
+    let implements: Vec<&str> = app
+        .get("implements")
+        .and_then(|value| value.as_array())
+        .expect("implements array")
+        .iter()
+        .filter_map(|value| value.as_str())
+        .collect();
+    assert_eq!(
+        implements,
+        vec!["org.mpris.MediaPlayer2", "org.freedesktop.FileManager1"]
+    );
+    assert_eq!(
+        app.get("uses_notifications")
+            .and_then(|value| value.as_bool()),
+        Some(true)
+    );
+}
+
+#[test]
+fn parse_desktop_entry_leaves_implements_empty_when_absent() {
+    let file = TempFile::new(
+        "[Desktop Entry]\nType=Application\nName=Plain\nExec=app\n",
+        "access-launcher-no-implements",
+    );
+    let mut line_buf = String::new();
+    let entry = parse_desktop_entry(&file.path, None, None, &mut line_buf).expect("entry present");
+    assert!(entry.implements.is_empty());
+    assert!(!entry.uses_notifications);
+    assert_eq!(entry.startup_wm_class, None);
+    let json = entries_to_json(&[entry]);
+    assert!(json.contains("\"implements\": [], \"uses_notifications\": false"));
+    assert!(json.contains("\"startup_wm_class\": null"));
+}
+
+#[test]
+fn spec_version_supported_rejects_newer_and_malformed_versions() {
+    assert!(spec_version_supported("1.0"));
+    assert!(spec_version_supported(" 1.5 "));
+    assert!(!spec_version_supported("1.6"));
+    assert!(!spec_version_supported("2.0"));
+    assert!(!spec_version_supported("one"));
+}
+
+#[test]
+fn parse_desktop_entry_collects_standard_and_vendor_keywords() {
+    let file = TempFile::new(
+        r#"
+[Desktop Entry]
+Type=Application
+Name=Settings
+Exec=settings
+Keywords=Preferences;Configuration;
+X-GNOME-Keywords=Control Center;
+X-KDE-Keywords[de]=Einstellungen;
+X-Purism-FormFactor=Workstation;Mobile;
+"#,
+        "access-launcher-keywords",
+    );
+    let mut line_buf = String::new();
+    let entry = parse_desktop_entry(&file.path, Some("en_US.UTF-8"), None, &mut line_buf)
+        .expect("entry present");
+    assert_eq!(entry.keywords, "Preferences;Configuration;Control Center;");
+
+    let results = search_entries(
+        std::slice::from_ref(&entry),
+        "control",
+        SearchMode::Substring,
+        SearchScope::NameKeywords,
+    );
+    assert_eq!(results, vec![0]);
+}
+
 #[test]
 fn parse_desktop_entry_uses_localized_name() {
     let file = TempFile::new(
@@ -91,6 +424,115 @@ Exec=app
     assert_eq!(entry.name, "Localized Name");
 }
 
+#[test]
+fn parse_desktop_entry_reads_localized_generic_name() {
+    let file = TempFile::new(
+        "[Desktop Entry]\nType=Application\nName=Firefox\nExec=firefox\n\
+         GenericName=Web Browser\nGenericName[de]=Webbrowser\n",
+        "access-launcher-generic-name",
+    );
+    let mut buf = String::new();
+    let german = parse_desktop_entry(&file.path, Some("de_DE.UTF-8"), None, &mut buf)
+        .expect("entry should parse");
+    assert_eq!(german.generic_name.as_deref(), Some("Webbrowser"));
+    let english = parse_desktop_entry(&file.path, Some("en_US.UTF-8"), None, &mut buf)
+        .expect("entry should parse");
+    assert_eq!(english.generic_name.as_deref(), Some("Web Browser"));
+}
+
+#[test]
+fn parse_desktop_entry_reads_localized_comment() {
+    let file = TempFile::new(
+        "[Desktop Entry]\nType=Application\nName=Files\nExec=files\n\
+         Comment=Browse folders\nComment[de]=Ordner durchsuchen\n",
+        "access-launcher-comment",
+    );
+    let mut buf = String::new();
+    let german = parse_desktop_entry(&file.path, Some("de_DE.UTF-8"), None, &mut buf)
+        .expect("entry should parse");
+    assert_eq!(german.comment.as_deref(), Some("Ordner durchsuchen"));
+    let english = parse_desktop_entry(&file.path, Some("en_US.UTF-8"), None, &mut buf)
+        .expect("entry should parse");
+    assert_eq!(english.comment.as_deref(), Some("Browse folders"));
+}
+
+#[test]
+fn search_finds_localized_entries_by_their_base_name() {
+    let file = TempFile::new(
+        "[Desktop Entry]\nType=Application\nName=Files\nName[de]=Dateien\nExec=files\n",
+        "access-launcher-base-name",
+    );
+    let mut buf = String::new();
+    let german = parse_desktop_entry(&file.path, Some("de_DE.UTF-8"), None, &mut buf)
+        .expect("entry should parse");
+    assert_eq!(german.name, "Dateien");
+    assert_eq!(german.base_name.as_deref(), Some("Files"));
+    let english = parse_desktop_entry(&file.path, Some("en_US.UTF-8"), None, &mut buf)
+        .expect("entry should parse");
+    assert_eq!(english.base_name, None);
+
+    let entries = [german];
+    for query in ["files", "dateien"] {
+        assert_eq!(
+            search_entries(&entries, query, SearchMode::Substring, SearchScope::Name),
+            [0],
+            "{query}"
+        );
+    }
+}
+
+#[test]
+fn parse_desktop_entry_uses_localized_icon() {
+    let file = TempFile::new(
+        "[Desktop Entry]\nType=Application\nName=App\nExec=app\nIcon=app\nIcon[de]=app-de\n",
+        "access-launcher-icon",
+    );
+    let mut buf = String::new();
+    let german = parse_desktop_entry(&file.path, Some("de_DE.UTF-8"), None, &mut buf)
+        .expect("entry should parse");
+    assert_eq!(german.icon.as_deref(), Some("app-de"));
+    let english = parse_desktop_entry(&file.path, Some("en_US.UTF-8"), None, &mut buf)
+        .expect("entry should parse");
+    assert_eq!(english.icon.as_deref(), Some("app"));
+}
+
+#[test]
+fn parse_desktop_entry_result_reports_skip_reasons() {
+    let cases = [
+        ("Type=Link\nName=Link\n", "NotApplication"),
+        (
+            "Type=Application\nName=App\nExec=app\nNoDisplay=true\n",
+            "NoDisplay",
+        ),
+        (
+            "Type=Application\nName=App\nExec=app\nHidden=true\n",
+            "Hidden",
+        ),
+        ("Type=Application\nName=App\n", "MissingExec"),
+        (
+            "Type=Application\nName=App\nExec=/access-launcher/missing/app\n",
+            "InvalidExec(\"/access-launcher/missing/app\")",
+        ),
+    ];
+
+    let mut buf = String::new();
+    for (body, expected) in cases {
+        let file = TempFile::new(
+            &format!("[Desktop Entry]\n{body}"),
+            "access-launcher-parse-error",
+        );
+        let err = parse_desktop_entry_result(&file.path, None, None, &mut buf)
+            .expect_err("entry should be rejected");
+        assert_eq!(format!("{err:?}"), expected);
+    }
+
+    let missing = env::temp_dir().join("access-launcher-no-such-file.desktop");
+    let err = parse_desktop_entry_result(&missing, None, None, &mut buf)
+        .expect_err("missing file should fail");
+    assert!(matches!(err, DesktopParseError::Io(_)));
+    assert!(err.to_string().starts_with("could not be read"));
+}
+
 #[test]
 fn parse_desktop_entry_only_show_in_filters() {
     let file = TempFile::new(
@@ -110,6 +552,31 @@ OnlyShowIn=GNOME;
     assert!(parse_desktop_entry(&file.path, None, Some(&kde), &mut line_buf).is_none());
 }
 
+#[test]
+fn parse_desktop_entry_only_show_in_matches_composite_and_case() {
+    let file = TempFile::new(
+        r#"
+[Desktop Entry]
+Type=Application
+Name=Desktop Filter
+Exec=app
+OnlyShowIn=GNOME;X-Cinnamon;
+"#,
+        "access-launcher-only-show-in-composite",
+    );
+    let mut line_buf = String::new();
+    let mut shown = |desktops: &str| {
+        let desktops = parse_current_desktops(desktops);
+        parse_desktop_entry(&file.path, None, Some(&desktops), &mut line_buf).is_some()
+    };
+    assert!(shown("ubuntu:GNOME"));
+    assert!(shown("gnome"));
+    assert!(shown("Cinnamon"));
+    assert!(shown("x-cinnamon"));
+    assert!(!shown("KDE"));
+    assert!(!shown("ubuntu:X-GNOME-Flashback"));
+}
+
 #[test]
 fn parse_desktop_entry_only_show_in_without_current_desktop_is_allowed() {
     let file = TempFile::new(
@@ -126,6 +593,83 @@ OnlyShowIn=GNOME;
     assert!(parse_desktop_entry(&file.path, None, None, &mut line_buf).is_some());
 }
 
+#[test]
+fn parse_desktop_entry_ignores_show_in_keys_with_empty_current_desktop() {
+    let only = TempFile::new(
+        "[Desktop Entry]\nType=Application\nName=Only\nExec=app\nOnlyShowIn=GNOME;\n",
+        "access-launcher-only-show-in-empty",
+    );
+    let not = TempFile::new(
+        "[Desktop Entry]\nType=Application\nName=Not\nExec=app\nNotShowIn=GNOME;\n",
+        "access-launcher-not-show-in-empty",
+    );
+    let empty = parse_current_desktops("");
+    assert!(empty.is_empty());
+    let mut line_buf = String::new();
+    for file in [&only, &not] {
+        assert!(parse_desktop_entry(&file.path, None, Some(&empty), &mut line_buf).is_some());
+        assert!(parse_desktop_entry(&file.path, None, None, &mut line_buf).is_some());
+    }
+
+    let gnome = vec!["GNOME".to_string()];
+    assert!(parse_desktop_entry(&not.path, None, Some(&gnome), &mut line_buf).is_none());
+    assert!(parse_desktop_entry(&only.path, None, Some(&gnome), &mut line_buf).is_some());
+}
+
+#[test]
+fn parse_desktop_entry_with_hidden_records_no_display_and_hidden_separately() {
+    let flags = |extra: &str, stem: &str| {
+        let file = TempFile::new(
+            &format!("[Desktop Entry]\nType=Application\nName=Helper\nExec=app\n{extra}"),
+            stem,
+        );
+        let mut line_buf = String::new();
+        let listed = parse_desktop_entry(&file.path, None, None, &mut line_buf).is_some();
+        let entry = parse_desktop_entry_with_hidden(&file.path, None, None, &mut line_buf)
+            .expect("entry kept");
+        (listed, entry.no_display, entry.hidden)
+    };
+    assert_eq!(
+        flags("", "access-launcher-flags-none"),
+        (true, false, false)
+    );
+    assert_eq!(
+        flags("NoDisplay=true\n", "access-launcher-flags-no-display"),
+        (false, true, false)
+    );
+    assert_eq!(
+        flags("Hidden=true\n", "access-launcher-flags-hidden"),
+        (false, false, true)
+    );
+    assert_eq!(
+        flags(
+            "NoDisplay=true\nHidden=true\n",
+            "access-launcher-flags-both"
+        ),
+        (false, true, true)
+    );
+}
+
+#[test]
+fn entry_diagnostics_labels_no_display_and_hidden_entries() {
+    let visibility = |entry: &DesktopEntry| {
+        entry_diagnostics(entry, &[])
+            .into_iter()
+            .find(|(name, _)| *name == "Visibility")
+            .map(|(_, value)| value)
+    };
+    let mut entry = DesktopEntry {
+        no_display: true,
+        ..Default::default()
+    };
+    assert_eq!(
+        visibility(&entry).as_deref(),
+        Some("NoDisplay (kept out of menus)")
+    );
+    entry.hidden = true;
+    assert_eq!(visibility(&entry).as_deref(), Some("Hidden/deleted"));
+}
+
 #[test]
 fn parse_desktop_entry_not_show_in_filters() {
     let file = TempFile::new(
@@ -220,6 +764,436 @@ fn exec_looks_valid_handles_complex_cases() {
     assert!(exec_looks_valid("env FOO=bar"));
 }
 
+#[test]
+fn exec_looks_valid_parses_reserved_characters() {
+    let temp = TempFile::new("", "access-launcher-reserved");
+    let existing = temp.path.to_string_lossy().to_string();
+
+    // `$` and `;` must be quoted per the spec, so these take the shell
+    // parser rather than the whitespace split.
+    assert!(exec_looks_valid(&format!("{existing} --home=\"$HOME\"")));
+    assert!(!exec_looks_valid("/non/existent/app $HOME"));
+    assert!(exec_looks_valid(&format!("{existing} 'a;b'")));
+    assert!(!exec_looks_valid("/non/existent/app;"));
+
+    // The shell parser does not treat an unquoted `;` or `$` as an operator,
+    // so it stays part of the program path, which does not exist.
+    assert!(!exec_looks_valid(&format!("{existing}; true")));
+    assert!(!exec_looks_valid(&format!("{existing}$HOME")));
+}
+
+#[test]
+fn exec_looks_valid_follows_the_shell_parser_where_splitting_differs() {
+    let flatpak = TempDir::new("access-launcher-reserved-flatpak");
+    let binary = flatpak.write("bin/flatpak", "");
+    let binary = binary.display();
+
+    // A `#` starting a word comments out the rest of the line, so the app
+    // ID and the program after the assignments are gone.
+    assert!(!exec_looks_valid(&format!("{binary} run #org.example.App")));
+    assert!(exec_looks_valid("env #FOO=bar /non/existent/app"));
+    assert!(!exec_looks_valid("#relative-command"));
+
+    // A line that cannot be split cannot be launched either.
+    assert!(!exec_looks_valid("relative-command \"unclosed"));
+    assert!(!exec_looks_valid("'relative-command"));
+}
+
+#[test]
+fn collect_desktop_entries_discovers_custom_dirs() {
+    let root = TempDir::new("access-launcher-custom-dirs");
+    let apps = root.path.join("custom-apps");
+    root.write(
+        "custom-apps/custom-app.desktop",
+        "[Desktop Entry]\nType=Application\nName=Custom App\nExec=custom-app\n",
+    );
+    let conf = root.write(
+        "dirs.conf",
+        &format!("# extra launchers\n\n{}\n", apps.display()),
+    );
+
+    let dirs = read_extra_dirs(&conf);
+    assert_eq!(dirs, vec![apps]);
+
+    let entries = collect_desktop_entries_from(&dirs, &[]);
+    assert!(entries.iter().any(|entry| entry.name == "Custom App"));
+}
+
+fn write_app(root: &TempDir, dir: &str, exec: &str, extra: &str) -> PathBuf {
+    root.write(
+        &format!("{dir}/shared.desktop"),
+        &format!("[Desktop Entry]\nType=Application\nName=Shared\nExec={exec}\n{extra}"),
+    );
+    root.path.join(dir)
+}
+
+fn shared_exec(dirs: &[PathBuf]) -> Vec<String> {
+    collect_desktop_entries_from(dirs, &[])
+        .into_iter()
+        .filter(|entry| entry.id == "shared.desktop")
+        .map(|entry| entry.exec)
+        .collect()
+}
+
+#[test]
+fn collect_desktop_entries_prefers_earlier_directories() {
+    let root = TempDir::new("access-launcher-precedence");
+    let dirs = [
+        write_app(&root, "first", "first-app", ""),
+        write_app(&root, "second", "second-app", ""),
+        write_app(&root, "third", "third-app", ""),
+    ];
+    assert_eq!(shared_exec(&dirs), vec!["first-app"]);
+}
+
+#[test]
+fn collect_desktop_entries_replaces_invalid_exec_with_later_entry() {
+    let root = TempDir::new("access-launcher-precedence-invalid");
+    let dirs = [
+        write_app(&root, "first", "/nonexistent/bin/app", ""),
+        write_app(&root, "second", "second-app", ""),
+        write_app(&root, "third", "third-app", ""),
+    ];
+    assert_eq!(shared_exec(&dirs), vec!["second-app"]);
+
+    let dirs = [
+        write_app(&root, "a", "/nonexistent/bin/app", ""),
+        write_app(&root, "b", "/nonexistent/bin/other", ""),
+        write_app(&root, "c", "third-app", ""),
+    ];
+    assert_eq!(shared_exec(&dirs), vec!["third-app"]);
+}
+
+#[test]
+fn collect_desktop_entries_hidden_entry_shadows_later_directories() {
+    let root = TempDir::new("access-launcher-precedence-hidden");
+    let dirs = [
+        write_app(&root, "first", "first-app", "Hidden=true\n"),
+        write_app(&root, "second", "second-app", ""),
+        write_app(&root, "third", "third-app", ""),
+    ];
+    assert!(shared_exec(&dirs).is_empty());
+
+    let dirs = [
+        write_app(&root, "a", "/nonexistent/bin/app", ""),
+        write_app(&root, "b", "second-app", "NoDisplay=true\n"),
+        write_app(&root, "c", "third-app", ""),
+    ];
+    assert!(shared_exec(&dirs).is_empty());
+}
+
+#[test]
+fn collect_desktop_entries_skips_marked_and_excluded_subdirs() {
+    let root = TempDir::new("access-launcher-hidden-subdirs");
+    let app = "[Desktop Entry]\nType=Application\nExec=app\n";
+    root.write("apps/visible/shown.desktop", app);
+    root.write("apps/marked/.hidden", "");
+    root.write("apps/marked/marked.desktop", app);
+    root.write(
+        "apps/nodisplay/.directory",
+        "[Desktop Entry]\nType=Directory\nNoDisplay=true\n",
+    );
+    root.write("apps/nodisplay/nodisplay.desktop", app);
+    root.write("apps/screensavers/saver.desktop", app);
+
+    let dirs = [root.path.join("apps")];
+    let ids = |excluded: &[String]| {
+        let mut ids: Vec<String> = collect_desktop_entries_from(&dirs, excluded)
+            .into_iter()
+            .map(|entry| entry.id)
+            .collect();
+        ids.sort();
+        ids
+    };
+
+    assert_eq!(ids(&["screensavers".to_string()]), vec!["shown.desktop"]);
+    assert_eq!(ids(&[]), vec!["saver.desktop", "shown.desktop"]);
+}
+
+#[test]
+fn read_extra_dirs_treats_missing_file_as_empty() {
+    let root = TempDir::new("access-launcher-missing-dirs");
+    assert!(read_extra_dirs(&root.path.join("dirs.conf")).is_empty());
+}
+
+#[test]
+fn exec_looks_valid_handles_snap_wrappers() {
+    // Snap exports launch through env with a hint variable before the wrapper.
+    let wrapper = TempFile::new("", "access-launcher-snap-wrapper");
+    assert!(exec_looks_valid(&format!(
+        "env BAMF_DESKTOP_FILE_HINT=/var/lib/snapd/desktop/applications/app_app.desktop {} %U",
+        wrapper.path.display()
+    )));
+    assert!(exec_looks_valid(&format!("{} %U", wrapper.path.display())));
+    assert!(!exec_looks_valid(
+        "/snap/bin/access-launcher-missing-snap-wrapper %U"
+    ));
+}
+
+#[test]
+fn exec_looks_valid_looks_past_env_assignments() {
+    let program = TempFile::new("", "access-launcher-env-program");
+    let program = program.path.display();
+    assert!(exec_looks_valid(&format!("env FOO=bar {program} --flag")));
+    assert!(exec_looks_valid(&format!(
+        "/usr/bin/env -i FOO=bar 'BAZ=q u x' {program}"
+    )));
+    assert!(exec_looks_valid("env FOO=bar relative-command"));
+    assert!(!exec_looks_valid(
+        "env FOO=bar /access-launcher/missing/program"
+    ));
+    assert!(!exec_looks_valid(
+        "/usr/bin/env FOO=bar '/access-launcher/missing/program'"
+    ));
+}
+
+#[test]
+fn exec_looks_valid_accepts_flatpak_run_when_flatpak_exists() {
+    let flatpak = TempDir::new("access-launcher-flatpak");
+    let binary = flatpak.write("bin/flatpak", "");
+    let binary = binary.display();
+    assert!(exec_looks_valid(&format!(
+        "{binary} run --branch=stable --command=app org.example.App %U"
+    )));
+    assert!(exec_looks_valid(&format!(
+        "env FOO=bar {binary} run org.example.App"
+    )));
+    assert!(!exec_looks_valid(&format!("{binary} run --branch=stable")));
+    assert!(!exec_looks_valid(
+        "/access-launcher/missing/flatpak run org.example.App"
+    ));
+}
+
+fn packaged(name: &str, path: &str, exec: &str) -> DesktopEntry {
+    DesktopEntry {
+        id: path.rsplit('/').next().unwrap_or(path).to_string(),
+        name: name.to_string(),
+        exec: exec.to_string(),
+        path: PathBuf::from(path),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn is_flatpak_entry_detects_export_dirs_and_flatpak_run() {
+    assert!(is_flatpak_entry(&packaged(
+        "GIMP",
+        "/var/lib/flatpak/exports/share/applications/org.gimp.GIMP.desktop",
+        "gimp",
+    )));
+    assert!(is_flatpak_entry(&packaged(
+        "GIMP",
+        "/home/me/.local/share/applications/gimp.desktop",
+        "/usr/bin/flatpak run org.gimp.GIMP %U",
+    )));
+    assert!(!is_flatpak_entry(&packaged(
+        "GIMP",
+        "/usr/share/applications/gimp.desktop",
+        "gimp %U",
+    )));
+}
+
+#[test]
+fn should_merge_duplicate_requires_same_name_and_different_packaging() {
+    let system = packaged("GIMP", "/usr/share/applications/gimp.desktop", "gimp");
+    let flatpak = packaged(
+        "gimp",
+        "/var/lib/flatpak/exports/share/applications/org.gimp.GIMP.desktop",
+        "/usr/bin/flatpak run org.gimp.GIMP",
+    );
+    let other_system = packaged(
+        "GIMP",
+        "/usr/local/share/applications/gimp2.desktop",
+        "gimp2",
+    );
+    let other_name = packaged(
+        "Inkscape",
+        "/var/lib/flatpak/exports/share/applications/org.inkscape.Inkscape.desktop",
+        "inkscape",
+    );
+
+    assert!(should_merge_duplicate(&system, &flatpak));
+    assert!(!should_merge_duplicate(&system, &other_system));
+    assert!(!should_merge_duplicate(&system, &other_name));
+}
+
+#[test]
+fn merge_duplicate_entries_keeps_system_version_as_primary() {
+    let entries = vec![
+        packaged(
+            "GIMP",
+            "/var/lib/flatpak/exports/share/applications/org.gimp.GIMP.desktop",
+            "/usr/bin/flatpak run org.gimp.GIMP",
+        ),
+        packaged("gimp", "/usr/share/applications/gimp.desktop", "gimp"),
+        packaged("Terminal", "/usr/share/applications/a.desktop", "a"),
+        packaged("Terminal", "/usr/share/applications/b.desktop", "b"),
+    ];
+
+    let merged = merge_duplicate_entries(entries);
+    let ids: Vec<&str> = merged.iter().map(|entry| entry.id.as_str()).collect();
+    assert_eq!(ids, vec!["gimp.desktop", "a.desktop", "b.desktop"]);
+    assert_eq!(merged[0].alternates.len(), 1);
+    assert_eq!(merged[0].alternates[0].id, "org.gimp.GIMP.desktop");
+    assert!(merged[1].alternates.is_empty());
+}
+
+#[test]
+fn mark_duplicate_names_suffixes_rows_sharing_a_name() {
+    let mut entries = vec![
+        packaged(
+            "Firefox",
+            "/var/lib/flatpak/exports/share/applications/org.mozilla.firefox.desktop",
+            "/usr/bin/flatpak run org.mozilla.firefox",
+        ),
+        packaged(
+            "firefox",
+            "/usr/share/applications/firefox.desktop",
+            "firefox",
+        ),
+        packaged("Terminal", "/usr/share/applications/a.desktop", "a"),
+        packaged("Terminal", "/usr/share/applications/b.desktop", "b"),
+        packaged("Editor", "/usr/share/applications/editor.desktop", "editor"),
+    ];
+    entries[4].name_suffix = Some("stale".to_string());
+
+    assert_eq!(mark_duplicate_names(&mut entries), ["Firefox", "Terminal"]);
+    let suffixes: Vec<Option<&str>> = entries
+        .iter()
+        .map(|entry| entry.name_suffix.as_deref())
+        .collect();
+    // Packaging tells Firefox apart; the two system Terminals fall back to
+    // their desktop IDs.
+    assert_eq!(
+        suffixes,
+        [Some("Flatpak"), Some("system"), Some("a"), Some("b"), None]
+    );
+}
+
+#[test]
+fn parse_entries_dirs_splits_on_commas() {
+    assert_eq!(
+        parse_entries_dirs(" /opt/apps, ,relative/dir,"),
+        vec![PathBuf::from("/opt/apps"), PathBuf::from("relative/dir")]
+    );
+    assert!(parse_entries_dirs("").is_empty());
+}
+
+#[test]
+fn collect_desktop_entries_scans_only_the_given_dirs() {
+    let root = TempDir::new("entries-dir");
+    root.write(
+        "curated/kiosk.desktop",
+        "[Desktop Entry]\nType=Application\nName=Kiosk\nExec=sh\n",
+    );
+    let dirs = [root.path.join("curated")];
+    let entries = collect_desktop_entries(Some(&dirs), &Config::default());
+    let ids: Vec<&str> = entries.iter().map(|entry| entry.id.as_str()).collect();
+    assert_eq!(ids, vec!["kiosk.desktop"]);
+    assert_eq!(
+        find_desktop_files("kiosk", Some(&dirs), &[]),
+        vec![root.path.join("curated/kiosk.desktop")]
+    );
+}
+
+#[test]
+fn collect_desktop_entries_skips_the_configured_subdirs() {
+    let root = TempDir::new("entries-config");
+    root.write(
+        "apps/kiosk.desktop",
+        "[Desktop Entry]\nType=Application\nName=Kiosk\nExec=sh\n",
+    );
+    root.write(
+        "apps/hidden/extra.desktop",
+        "[Desktop Entry]\nType=Application\nName=Extra\nExec=sh\n",
+    );
+    let dirs = [root.path.join("apps")];
+    let config = Config {
+        excluded_subdirs: vec!["hidden".to_string()],
+        ..Config::default()
+    };
+    let entries = collect_desktop_entries(Some(&dirs), &config);
+    let ids: Vec<&str> = entries.iter().map(|entry| entry.id.as_str()).collect();
+    assert_eq!(ids, vec!["kiosk.desktop"]);
+    assert!(find_desktop_files("extra", Some(&dirs), &config.excluded_subdirs).is_empty());
+}
+
+#[test]
+fn mark_recently_installed_flags_a_freshly_written_file() {
+    let root = TempDir::new("recently-installed");
+    root.write(
+        "apps/fresh.desktop",
+        "[Desktop Entry]\nType=Application\nName=Fresh\nExec=sh\n",
+    );
+    let mut entries = collect_desktop_entries_from(&[root.path.join("apps")], &[]);
+    entries.push(DesktopEntry {
+        name: "Old".to_string(),
+        modified: Some(SystemTime::UNIX_EPOCH),
+        ..Default::default()
+    });
+    assert!(entries[0].modified.is_some());
+
+    mark_recently_installed(&mut entries, SystemTime::now(), 7);
+    assert!(entries[0].recently_installed);
+    assert!(!entries[1].recently_installed);
+    assert_eq!(new_app_indices(&entries), vec![0]);
+
+    mark_recently_installed(&mut entries, SystemTime::now(), 0);
+    assert!(new_app_indices(&entries).is_empty());
+}
+
+#[test]
+fn dedup_symlinked_entries_lists_a_linked_file_once() {
+    let root = TempDir::new("dedup-symlinks");
+    root.write(
+        "apps/editor.desktop",
+        "[Desktop Entry]\nType=Application\nName=Editor\nExec=editor\n",
+    );
+    root.write(
+        "apps/viewer.desktop",
+        "[Desktop Entry]\nType=Application\nName=Viewer\nExec=viewer\n",
+    );
+    let apps = fs::canonicalize(root.path.join("apps")).expect("resolve temp dir");
+    std::os::unix::fs::symlink(apps.join("editor.desktop"), apps.join("alias.desktop"))
+        .expect("create symlink");
+
+    let entries = collect_desktop_entries_from(std::slice::from_ref(&apps), &[]);
+    assert_eq!(entries.len(), 3);
+
+    let deduped = dedup_symlinked_entries(entries);
+    let mut paths: Vec<PathBuf> = deduped.iter().map(|entry| entry.path.clone()).collect();
+    paths.sort();
+    assert_eq!(
+        paths,
+        vec![apps.join("editor.desktop"), apps.join("viewer.desktop")]
+    );
+}
+
+#[test]
+fn collect_desktop_entries_follows_directory_symlinks_without_looping() {
+    let root = TempDir::new("symlink-cycle");
+    root.write(
+        "apps/vendor/editor.desktop",
+        "[Desktop Entry]\nType=Application\nName=Editor\nExec=editor\n",
+    );
+    root.write(
+        "elsewhere/viewer.desktop",
+        "[Desktop Entry]\nType=Application\nName=Viewer\nExec=viewer\n",
+    );
+    let apps = root.path.join("apps");
+    // vendor/loop points back up at apps, so a naive walk never ends.
+    std::os::unix::fs::symlink(&apps, apps.join("vendor/loop")).expect("create cycle");
+    std::os::unix::fs::symlink(root.path.join("elsewhere"), apps.join("linked"))
+        .expect("create directory symlink");
+
+    let (entries, report) =
+        collect_desktop_entries_from_with_report(std::slice::from_ref(&apps), &[]);
+    let mut names: Vec<&str> = entries.iter().map(|entry| entry.name.as_str()).collect();
+    names.sort();
+    assert_eq!(names, ["Editor", "Viewer"]);
+    assert!(report.unreadable_dirs.is_empty());
+}
+
 #[test]
 fn build_category_map_groups_entries_preserving_order() {
     let mut entries = vec![
@@ -228,18 +1202,21 @@ fn build_category_map_groups_entries_preserving_order() {
             exec: "app".to_string(),
             categories: "Development".to_string(),
             path: PathBuf::from("/tmp/bapp.desktop"),
+            ..Default::default()
         },
         DesktopEntry {
             name: "Aapp".to_string(),
             exec: "app".to_string(),
             categories: "Development".to_string(),
             path: PathBuf::from("/tmp/aapp.desktop"),
+            ..Default::default()
         },
         DesktopEntry {
             name: "GameApp".to_string(),
             exec: "app".to_string(),
             categories: "Game".to_string(),
             path: PathBuf::from("/tmp/gameapp.desktop"),
+            ..Default::default()
         },
     ];
     // Pre-sort the entries to match how collect_desktop_entries works.
@@ -260,18 +1237,21 @@ fn build_category_map_respects_precedence() {
             exec: "app".to_string(),
             categories: "System;TerminalEmulator;".to_string(),
             path: PathBuf::from("/tmp/app1.desktop"),
+            ..Default::default()
         },
         DesktopEntry {
             name: "App2".to_string(),
             exec: "app".to_string(),
             categories: "Game;Internet;".to_string(),
             path: PathBuf::from("/tmp/app2.desktop"),
+            ..Default::default()
         },
         DesktopEntry {
             name: "App3".to_string(),
             exec: "app".to_string(),
             categories: "Unknown;Utility;".to_string(),
             path: PathBuf::from("/tmp/app3.desktop"),
+            ..Default::default()
         },
     ];
 
@@ -288,3 +1268,622 @@ fn build_category_map_respects_precedence() {
     // Utility (10) > Unknown (ignored)
     assert!(map.contains_key("Utilities"));
 }
+
+#[test]
+fn build_category_map_normalizes_case_and_whitespace() {
+    let entries = vec![
+        DesktopEntry {
+            name: "Lowercase".to_string(),
+            categories: "utility;".to_string(),
+            ..Default::default()
+        },
+        DesktopEntry {
+            name: "Padded".to_string(),
+            categories: " Development ;".to_string(),
+            ..Default::default()
+        },
+    ];
+
+    let map = build_category_map(&entries);
+    assert_eq!(map.get("Utilities"), Some(&vec![0]));
+    assert_eq!(map.get("Development"), Some(&vec![1]));
+    assert!(!map.contains_key("Other"));
+    // The raw token is kept as written for diagnostics.
+    assert_eq!(entries[1].categories, " Development ;");
+}
+
+#[test]
+fn category_rules_bucket_tokens_with_no_exact_match() {
+    let categorized = |categories: &str| DesktopEntry {
+        categories: categories.to_string(),
+        ..Default::default()
+    };
+    let entries = vec![
+        categorized("X-Steam-Game-Tools;"),
+        categorized("X-Vendor-Science;"),
+        categorized("Game;X-Editors;"),
+        categorized("X-Nothing;"),
+    ];
+    let rules = vec![
+        CategoryRule::new("X-.*Game.*", "Games").unwrap(),
+        CategoryRule::new("x-.*science", "office").unwrap(),
+        CategoryRule::new("X-Editors", "Development").unwrap(),
+    ];
+
+    let map = build_category_map_with_rules(&entries, &rules);
+    assert_eq!(map.get("Games"), Some(&vec![0, 2]));
+    assert_eq!(map.get("Office"), Some(&vec![1]));
+    assert_eq!(map.get("Other"), Some(&vec![3]));
+    assert!(!map.contains_key("Development"));
+}
+
+#[test]
+fn category_rule_patterns_match_whole_tokens() {
+    let rule = CategoryRule::new("X-.*Game.*", "Games").unwrap();
+    assert!(rule.matches("X-Game"));
+    assert!(rule.matches("x-retro-games"));
+    assert!(!rule.matches("Game"));
+    assert!(!rule.matches("X-Gam"));
+    let literal = CategoryRule::new(r"Ver\.1", "Other").unwrap();
+    assert!(literal.matches("Ver.1"));
+    assert!(!literal.matches("Ver11"));
+    let either = CategoryRule::new("X-(Retro|Arcade)Games?", "Games").unwrap();
+    assert!(either.matches("x-arcadegame"));
+    assert!(either.matches("X-RetroGames"));
+    assert!(!either.matches("X-RetroGamesPlus"));
+    // Would take exponential time in a backtracking engine.
+    let nested = CategoryRule::new(&"a*".repeat(30), "Other").unwrap();
+    assert!(!nested.matches(&format!("{}b", "a".repeat(40))));
+}
+
+#[test]
+fn category_rule_rejects_invalid_patterns_and_categories() {
+    assert!(CategoryRule::new("*Game", "Games").is_err());
+    assert!(CategoryRule::new("(Game", "Games").is_err());
+    // Checked alone, so the anchors added around it cannot balance it.
+    assert!(CategoryRule::new("Game)|(Play", "Games").is_err());
+    assert!(CategoryRule::new(r"Game\", "Games").is_err());
+    assert_eq!(
+        CategoryRule::new("(a{100}){100}", "Games").unwrap_err(),
+        "pattern (a{100}){100} is too large"
+    );
+    assert!(CategoryRule::new("", "Games").is_err());
+    assert!(CategoryRule::new("Game", "Toys").is_err());
+}
+
+#[test]
+fn pin_categories_moves_pinned_buckets_first_in_pinned_order() {
+    let mut buckets = vec!["Accessories", "Development", "Games", "Internet", "Office"];
+    let pinned = [
+        "Internet".to_string(),
+        "Graphics".to_string(),
+        "Development".to_string(),
+    ];
+    pin_categories(&mut buckets, &pinned);
+    // Graphics is pinned but has no apps, so it stays out.
+    assert_eq!(
+        buckets,
+        ["Internet", "Development", "Accessories", "Games", "Office"]
+    );
+}
+
+#[test]
+fn appimage_name_drops_version_and_architecture() {
+    assert_eq!(appimage_name("Krita-5.2.1-x86_64"), "Krita");
+    assert_eq!(appimage_name("Tor_Browser-v13.0-linux"), "Tor Browser");
+    assert_eq!(appimage_name("Obsidian-1.4.16"), "Obsidian");
+    assert_eq!(appimage_name("Inkscape"), "Inkscape");
+    assert_eq!(appimage_name("0ad-x86_64"), "0ad-x86_64");
+}
+
+#[test]
+fn appimage_entries_are_synthesized_from_executable_files() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let root = TempDir::new("access-launcher-appimages");
+    let app = root.write("Krita-5.2.1-x86_64.AppImage", "#!/bin/sh\n");
+    fs::set_permissions(&app, fs::Permissions::from_mode(0o755)).expect("chmod AppImage");
+    let plain = root.write("Notes-1.0.AppImage", "not executable");
+    fs::set_permissions(&plain, fs::Permissions::from_mode(0o644)).expect("chmod AppImage");
+
+    let entry = appimage_entry(&app).expect("executable AppImage");
+    assert_eq!(entry.name, "Krita");
+    assert_eq!(entry.id, "appimage-Krita-5.2.1-x86_64.desktop");
+    assert_eq!(entry.path, app);
+    assert!(entry.appimage);
+    assert!(exec_looks_valid(&entry.exec));
+    assert_eq!(
+        build_category_map(std::slice::from_ref(&entry))["Other"].len(),
+        1
+    );
+    let fields = entry_diagnostics(&entry, &[]);
+    assert!(fields
+        .iter()
+        .any(|(label, value)| *label == "Source" && value.contains("AppImage")));
+    assert!(appimage_entry(&plain).is_none());
+
+    let mut entries = vec![DesktopEntry {
+        name: "Zathura".to_string(),
+        id: "zathura.desktop".to_string(),
+        ..Default::default()
+    }];
+    add_appimage_entries(
+        &mut entries,
+        &[root.path.clone(), root.path.join("missing")],
+    );
+    let names: Vec<&str> = entries.iter().map(|entry| entry.name.as_str()).collect();
+    assert_eq!(names, ["Krita", "Zathura"]);
+}
+
+#[test]
+fn group_by_raw_category_splits_other_by_original_tokens() {
+    let entries = vec![
+        DesktopEntry {
+            name: "Stars".to_string(),
+            categories: "Science;Astronomy;".to_string(),
+            ..Default::default()
+        },
+        DesktopEntry {
+            name: "Atlas".to_string(),
+            categories: "Science;".to_string(),
+            ..Default::default()
+        },
+        DesktopEntry {
+            name: "Mystery".to_string(),
+            ..Default::default()
+        },
+    ];
+
+    let groups = group_by_raw_category(&entries, &[0, 1, 2]);
+    assert_eq!(
+        groups,
+        vec![
+            ("Astronomy".to_string(), vec![0]),
+            ("Science".to_string(), vec![0, 1]),
+            ("Uncategorized".to_string(), vec![2]),
+        ]
+    );
+    assert!(group_by_raw_category(&entries, &[]).is_empty());
+}
+
+#[test]
+fn visible_categories_skips_empty_buckets_unless_requested() {
+    let entries = vec![DesktopEntry {
+        name: "Editor".to_string(),
+        exec: "editor".to_string(),
+        categories: "TextEditor;".to_string(),
+        path: PathBuf::from("/tmp/editor.desktop"),
+        ..Default::default()
+    }];
+    let map = build_category_map(&entries);
+
+    assert_eq!(visible_categories(&map, false), vec!["Text Editors"]);
+    assert_eq!(visible_categories(&map, true), CATEGORIES.to_vec());
+}
+
+#[test]
+fn collect_desktop_entries_reports_skip_reasons() {
+    let root = TempDir::new("access-launcher-report");
+    let app = |name: &str, extra: &str| {
+        format!("[Desktop Entry]\nType=Application\nName={name}\nExec=true\n{extra}")
+    };
+    root.write("first/shown.desktop", &app("Shown", ""));
+    root.write("first/quiet.desktop", &app("Quiet", "NoDisplay=true"));
+    root.write("first/gone.desktop", &app("Gone", "Hidden=true"));
+    root.write(
+        "first/link.desktop",
+        "[Desktop Entry]\nType=Link\nName=Link\n",
+    );
+    root.write(
+        "first/broken.desktop",
+        "[Desktop Entry]\nType=Application\nName=Broken\n",
+    );
+    root.write("second/shown.desktop", &app("Shadowed", ""));
+    let dirs = [root.path.join("first"), root.path.join("second")];
+
+    let (entries, report) = collect_desktop_entries_from_with_report(&dirs, &[]);
+    assert_eq!(entries.len(), 1);
+    assert_eq!(
+        report,
+        ScanReport {
+            files_seen: 6,
+            no_display: 1,
+            hidden: 1,
+            not_application: 1,
+            invalid_exec: 1,
+            duplicate: 1,
+            ..Default::default()
+        }
+    );
+    assert_eq!(report.skipped(), 5);
+}
+
+#[test]
+fn collect_desktop_entries_records_unreadable_directories_and_goes_on() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let root = TempDir::new("access-launcher-unreadable");
+    let app = "[Desktop Entry]\nType=Application\nName=App\nExec=true\n";
+    root.write("apps/locked/hidden.desktop", app);
+    root.write("apps/open/shown.desktop", app);
+    let locked = root.path.join("apps/locked");
+    fs::set_permissions(&locked, fs::Permissions::from_mode(0o000)).expect("chmod");
+    if fs::read_dir(&locked).is_ok() {
+        // Root, or a filesystem ignoring modes, can list it anyway.
+        eprintln!("{} is still readable; skipping", locked.display());
+        fs::set_permissions(&locked, fs::Permissions::from_mode(0o755)).expect("chmod");
+        return;
+    }
+
+    let dirs = [root.path.join("apps")];
+    let (entries, report) = collect_desktop_entries_from_with_report(&dirs, &[]);
+    fs::set_permissions(&locked, fs::Permissions::from_mode(0o755)).expect("chmod");
+
+    let ids: Vec<&str> = entries.iter().map(|entry| entry.id.as_str()).collect();
+    assert_eq!(ids, ["shown.desktop"]);
+    assert_eq!(report.unreadable_dirs.len(), 1);
+    assert_eq!(report.unreadable_dirs[0].path, locked);
+    assert!(!report.unreadable_dirs[0].reason.is_empty());
+}
+
+#[test]
+fn add_broken_entries_flags_a_missing_exec_program() {
+    let root = TempDir::new("broken-exec");
+    root.write(
+        "apps/gone.desktop",
+        "[Desktop Entry]\nType=Application\nName=Gone\nExec=env LANG=C /nonexistent/gone --x\n",
+    );
+    root.write(
+        "apps/ok.desktop",
+        "[Desktop Entry]\nType=Application\nName=Ok\nExec=true\n",
+    );
+    let dirs = [root.path.join("apps")];
+
+    let (mut entries, report) = collect_desktop_entries_from_with_report(&dirs, &[]);
+    assert_eq!(
+        report.broken_exec,
+        vec![root.path.join("apps/gone.desktop")]
+    );
+    let ids: Vec<&str> = entries.iter().map(|entry| entry.id.as_str()).collect();
+    assert_eq!(ids, vec!["ok.desktop"]);
+
+    add_broken_entries(&mut entries, &report.broken_exec);
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[0].id, "gone.desktop");
+    assert_eq!(
+        entries[0].missing_program.as_deref(),
+        Some("/nonexistent/gone")
+    );
+    assert_eq!(entries[1].missing_program, None);
+}
+
+#[test]
+fn apply_name_overrides_renames_and_resorts() {
+    let mut entries: Vec<DesktopEntry> = ["Files", "LibreOffice Calc", "Terminal"]
+        .iter()
+        .enumerate()
+        .map(|(i, name)| DesktopEntry {
+            id: format!("app{i}.desktop"),
+            name: name.to_string(),
+            ..Default::default()
+        })
+        .collect();
+    let overrides = BTreeMap::from([
+        ("app1.desktop".to_string(), "Calc".to_string()),
+        ("app2.desktop".to_string(), "Terminal".to_string()),
+    ]);
+
+    apply_name_overrides(&mut entries, &overrides);
+    let names: Vec<&str> = entries.iter().map(|entry| entry.name.as_str()).collect();
+    assert_eq!(names, vec!["Calc", "Files", "Terminal"]);
+    assert_eq!(
+        entries[0].original_name.as_deref(),
+        Some("LibreOffice Calc")
+    );
+    assert_eq!(entries[2].original_name, None);
+
+    let results = search_entries(
+        &entries,
+        "libre",
+        SearchMode::Substring,
+        SearchScope::NameKeywords,
+    );
+    assert_eq!(results, vec![0]);
+}
+
+#[test]
+fn entry_diagnostics_reports_exec_and_try_exec() {
+    let file = TempFile::new(
+        r#"
+[Desktop Entry]
+Type=Application
+Name=Diagnosed
+Exec=sh -c true
+TryExec=/nonexistent/diagnosed-app
+Categories=Network;
+"#,
+        "access-launcher-diagnostics",
+    );
+    let mut line_buf = String::new();
+    let entry = parse_desktop_entry(&file.path, None, None, &mut line_buf).expect("entry present");
+    assert_eq!(
+        entry.try_exec.as_deref(),
+        Some("/nonexistent/diagnosed-app")
+    );
+
+    let fields = entry_diagnostics(&entry, &[]);
+    let value = |label: &str| {
+        fields
+            .iter()
+            .find(|(name, _)| *name == label)
+            .map(|(_, value)| value.as_str())
+    };
+    assert_eq!(value("Exec"), Some("sh -c true"));
+    assert_eq!(value("Exec check"), Some("valid"));
+    assert_eq!(
+        value("TryExec"),
+        Some("/nonexistent/diagnosed-app (not found)")
+    );
+    assert_eq!(value("Categories"), Some("Network;"));
+    assert_eq!(value("Listed under"), Some("Internet"));
+    assert_eq!(value("Visibility"), Some("shown"));
+
+    let text = format_diagnostics(&fields);
+    assert!(text.starts_with(&format!("Desktop ID: {}\n", entry.id)));
+    assert_eq!(text.lines().count(), fields.len());
+}
+
+#[test]
+fn filter_terminal_apps_drops_terminal_entries_only_when_hiding() {
+    let entries = vec![
+        DesktopEntry {
+            name: "htop".to_string(),
+            terminal: true,
+            ..Default::default()
+        },
+        DesktopEntry {
+            name: "Files".to_string(),
+            ..Default::default()
+        },
+    ];
+    assert_eq!(terminal_app_indices(&entries), vec![0]);
+
+    let shown = filter_terminal_apps(entries.clone(), false);
+    assert_eq!(shown.len(), 2);
+
+    let hidden = filter_terminal_apps(entries, true);
+    let names: Vec<&str> = hidden.iter().map(|entry| entry.name.as_str()).collect();
+    assert_eq!(names, ["Files"]);
+}
+
+#[test]
+fn parse_desktop_entry_with_only_and_not_show_in_is_hidden() {
+    let file = TempFile::new(
+        r#"
+[Desktop Entry]
+Type=Application
+Name=Desktop Filter
+Exec=app
+OnlyShowIn=GNOME;
+NotShowIn=KDE;
+"#,
+        "access-launcher-show-in-conflict",
+    );
+    let gnome = vec!["GNOME".to_string()];
+    let mut line_buf = String::new();
+    let err = parse_desktop_entry_result(&file.path, None, Some(&gnome), &mut line_buf)
+        .expect_err("conflicting keys should hide the entry");
+    assert!(matches!(err, DesktopParseError::NotShownIn));
+    assert!(parse_desktop_entry(&file.path, None, None, &mut line_buf).is_none());
+}
+
+#[test]
+fn find_icon_file_looks_up_bare_file_names_in_pixmap_dirs() {
+    let first = TempDir::new("access-launcher-icons-first");
+    let second = TempDir::new("access-launcher-icons-second");
+    let legacy = second.write("legacy.png", "");
+    second.write("themed", "");
+    let dirs = [first.path.clone(), second.path.clone()];
+
+    assert_eq!(find_icon_file("legacy.png", &dirs), Some(legacy));
+    assert_eq!(find_icon_file("missing.png", &dirs), None);
+    assert_eq!(find_icon_file("themed", &dirs), None);
+    assert_eq!(find_icon_file("../legacy.png", &dirs), None);
+    assert_eq!(strip_icon_extension("org.app.Name"), None);
+    assert_eq!(strip_icon_extension("legacy.SVG"), Some("legacy"));
+}
+
+#[test]
+fn remove_blocklisted_drops_matching_ids() {
+    let dir = TempDir::new("access-launcher-blocklist-scan");
+    let app = |name: &str| format!("[Desktop Entry]\nType=Application\nName={name}\nExec=app\n");
+    dir.write("keep.desktop", &app("Keep"));
+    dir.write("hide.desktop", &app("Hide"));
+
+    let mut entries = collect_desktop_entries_from(std::slice::from_ref(&dir.path), &[]);
+    remove_blocklisted(&mut entries, &BTreeSet::from(["hide.desktop".to_string()]));
+    let ids: Vec<&str> = entries.iter().map(|entry| entry.id.as_str()).collect();
+    assert_eq!(ids, ["keep.desktop"]);
+}
+
+#[test]
+fn rescans_do_not_reuse_exec_checks_from_earlier_scans() {
+    let dir = TempDir::new("access-launcher-exec-cache");
+    let program = dir.write("bin/wrapper", "");
+    let app = format!(
+        "[Desktop Entry]\nType=Application\nName=Wrapped\nExec={} %U\n",
+        program.display()
+    );
+    dir.write("apps/one.desktop", &app);
+    dir.write("apps/two.desktop", &app);
+    let dirs = [dir.path.join("apps")];
+
+    assert_eq!(collect_desktop_entries_from(&dirs, &[]).len(), 2);
+    fs::remove_file(&program).expect("remove program");
+    assert!(collect_desktop_entries_from(&dirs, &[]).is_empty());
+}
+
+#[test]
+fn explain_desktop_file_reports_flags_and_result() {
+    let dir = TempDir::new("access-launcher-why");
+    let hidden = dir.write(
+        "hidden.desktop",
+        "[Desktop Entry]\nType=Application\nName=Hidden\nExec=app\nNoDisplay=true\nOnlyShowIn=KDE;\n",
+    );
+    let gnome = vec!["GNOME".to_string()];
+    let fields = explain_desktop_file(&hidden, None, Some(&gnome));
+    let field = |label: &str| {
+        fields
+            .iter()
+            .find(|(name, _)| *name == label)
+            .map(|(_, value)| value.clone())
+    };
+    assert_eq!(field("Type").as_deref(), Some("Application"));
+    assert_eq!(field("NoDisplay").as_deref(), Some("true (hides)"));
+    assert_eq!(field("Hidden").as_deref(), Some("not set"));
+    assert_eq!(
+        field("OnlyShowIn").as_deref(),
+        Some("KDE; (does not match GNOME; hides)")
+    );
+    assert_eq!(field("Exec").as_deref(), Some("app (valid)"));
+    assert_eq!(
+        field("Result").as_deref(),
+        Some("hidden: sets NoDisplay=true")
+    );
+
+    let shown = dir.write(
+        "shown.desktop",
+        "[Desktop Entry]\nType=Application\nName=Shown\nExec=app\n",
+    );
+    let fields = explain_desktop_file(&shown, None, None);
+    assert_eq!(
+        fields.last().map(|(_, value)| value.as_str()),
+        Some("shown")
+    );
+}
+
+#[test]
+fn explain_desktop_file_ignores_comment_lines() {
+    let dir = TempDir::new("access-launcher-why-comment");
+    let path = dir.write(
+        "commented.desktop",
+        "[Desktop Entry]\nType=Application\nName=Commented\nExec=app\n# NoDisplay=true\n",
+    );
+    let fields = explain_desktop_file(&path, None, None);
+    assert!(fields.contains(&("NoDisplay", "not set".to_string())));
+    assert_eq!(
+        fields.last().map(|(_, value)| value.as_str()),
+        Some("shown")
+    );
+}
+
+#[test]
+fn desktop_id_owner_follows_the_scan() {
+    let dir = TempDir::new("access-launcher-why-owner");
+    let broken = dir.write(
+        "user/app.desktop",
+        "[Desktop Entry]\nType=Application\nName=App\nExec=/nonexistent/app\n",
+    );
+    let hiding = dir.write(
+        "local/app.desktop",
+        "[Desktop Entry]\nType=Application\nName=App\nExec=sh\nNoDisplay=true\n",
+    );
+    let working = dir.write(
+        "system/app.desktop",
+        "[Desktop Entry]\nType=Application\nName=App\nExec=sh\n",
+    );
+    // A broken Exec passes the ID on, as the scan does.
+    assert_eq!(
+        desktop_id_owner(&[broken.clone(), working.clone()], None, None),
+        Some(1)
+    );
+    let dirs = [dir.path.join("user"), dir.path.join("system")];
+    let entries = collect_desktop_entries_from(&dirs, &[]);
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].path, working);
+
+    // A NoDisplay copy claims it, hiding the app.
+    assert_eq!(
+        desktop_id_owner(&[hiding, working.clone()], None, None),
+        Some(0)
+    );
+    assert_eq!(desktop_id_owner(&[broken], None, None), None);
+}
+
+#[test]
+fn export_desktop_links_links_entries_and_keeps_regular_files() {
+    let dir = TempDir::new("access-launcher-export");
+    let source = dir.write("apps/editor.desktop", "[Desktop Entry]\n");
+    let other = dir.write("apps/viewer.desktop", "[Desktop Entry]\n");
+    let entry = |id: &str, path: &PathBuf| DesktopEntry {
+        id: id.to_string(),
+        path: path.clone(),
+        ..Default::default()
+    };
+    let target = dir.path.join("menu");
+    dir.write("menu/viewer.desktop", "user copy");
+
+    let entries = [
+        entry("editor.desktop", &source),
+        entry("viewer.desktop", &other),
+    ];
+    let skipped = export_desktop_links(&entries, &target).expect("export");
+    assert_eq!(skipped, vec![target.join("viewer.desktop")]);
+    assert_eq!(
+        fs::read_link(target.join("editor.desktop")).ok(),
+        Some(source)
+    );
+    assert_eq!(
+        fs::read_to_string(target.join("viewer.desktop"))
+            .ok()
+            .as_deref(),
+        Some("user copy")
+    );
+
+    // Exporting again refreshes the links instead of failing.
+    assert!(export_desktop_links(&entries, &target).is_ok());
+
+    let file = dir.write("not-a-dir", "");
+    assert!(export_desktop_links(&entries, &file).is_err());
+}
+
+#[test]
+fn export_desktop_links_leaves_out_appimages() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let dir = TempDir::new("access-launcher-export-appimage");
+    let source = dir.write("apps/editor.desktop", "[Desktop Entry]\n");
+    let image = dir.write("Krita-5.2.1-x86_64.AppImage", "#!/bin/sh\n");
+    fs::set_permissions(&image, fs::Permissions::from_mode(0o755)).expect("chmod AppImage");
+    let appimage = appimage_entry(&image).expect("executable AppImage");
+    let editor = DesktopEntry {
+        id: "editor.desktop".to_string(),
+        path: source.clone(),
+        ..Default::default()
+    };
+
+    let target = dir.path.join("menu");
+    let skipped = export_desktop_links(&[editor, appimage.clone()], &target).expect("export");
+    assert!(skipped.is_empty());
+    assert_eq!(
+        fs::read_link(target.join("editor.desktop")).ok(),
+        Some(source)
+    );
+    assert!(fs::symlink_metadata(target.join(&appimage.id)).is_err());
+}
+
+#[test]
+fn parse_desktop_entry_names_reverse_dns_ids_by_their_last_component() {
+    let dir = TempDir::new("access-launcher-fallback-name");
+    let path = dir.write(
+        "org.mozilla.firefox.desktop",
+        "[Desktop Entry]\nType=Application\nExec=firefox\n",
+    );
+    let mut line_buf = String::new();
+    let entry = parse_desktop_entry(&path, None, None, &mut line_buf).expect("entry present");
+    assert_eq!(entry.name, "Firefox");
+
+    assert_eq!(fallback_name("org.gnome.Calculator"), "Calculator");
+    assert_eq!(fallback_name("my-app"), "my-app");
+    assert_eq!(fallback_name("app.v2"), "app.v2");
+}
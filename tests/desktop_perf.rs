@@ -1,4 +1,5 @@
-use access_launcher::desktop::collect_desktop_entries;
+use access_launcher::config::Config;
+use access_launcher::desktop::{collect_desktop_entries, collect_desktop_entries_from};
 use std::env;
 use std::fs;
 use std::time::Instant;
@@ -52,7 +53,7 @@ fn bench_parsing_performance() {
 
     println!("Starting benchmark...");
     let start = Instant::now();
-    let entries = collect_desktop_entries();
+    let entries = collect_desktop_entries(None, &Config::default());
     let duration = start.elapsed();
 
     println!(
@@ -64,3 +65,49 @@ fn bench_parsing_performance() {
     // Cleanup
     fs::remove_dir_all(&temp_dir).unwrap();
 }
+
+#[test]
+#[ignore]
+fn bench_large_directory_scan() {
+    let temp_dir = env::temp_dir().join("bolt_bench_large_dir");
+    if temp_dir.exists() {
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+    let apps = temp_dir.join("applications");
+    fs::create_dir_all(&apps).unwrap();
+
+    let exec_path = env::current_exe().unwrap();
+    let exec_str = exec_path.to_str().unwrap();
+
+    println!("Generating 5000 files...");
+    // 4000 desktop files and 1000 other files the scan must skip cheaply.
+    for i in 0..4000 {
+        let content = format!(
+            "[Desktop Entry]\nType=Application\nName=App {}\nExec={}\nCategories=Utility;",
+            i, exec_str
+        );
+        fs::write(apps.join(format!("app-{}.desktop", i)), content).unwrap();
+    }
+    for i in 0..1000 {
+        fs::write(apps.join(format!("mimeinfo-{}.cache", i)), "").unwrap();
+    }
+    // Symlinked desktop files must still be found.
+    #[cfg(unix)]
+    std::os::unix::fs::symlink(apps.join("app-0.desktop"), apps.join("linked.desktop")).unwrap();
+
+    println!("Starting benchmark...");
+    let start = Instant::now();
+    let entries = collect_desktop_entries_from(&[apps], &[]);
+    let duration = start.elapsed();
+
+    println!(
+        "Parsed {} entries (4001 expected) in {:?}",
+        entries.len(),
+        duration
+    );
+    #[cfg(unix)]
+    assert!(entries.iter().any(|entry| entry.id == "linked.desktop"));
+
+    // Cleanup
+    fs::remove_dir_all(&temp_dir).unwrap();
+}
@@ -0,0 +1,131 @@
+use access_launcher::desktop::DesktopEntry;
+use access_launcher::history::{History, RECENT_LIMIT};
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+
+fn entry(id: &str, name: &str) -> DesktopEntry {
+    DesktopEntry {
+        id: id.to_string(),
+        name: name.to_string(),
+        exec: "app".to_string(),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn record_launch_moves_id_to_front_and_counts() {
+    let mut history = History::default();
+    history.record_launch("a.desktop");
+    history.record_launch("b.desktop");
+    history.record_launch("a.desktop");
+
+    assert_eq!(history.recent, vec!["a.desktop", "b.desktop"]);
+    assert_eq!(history.launch_counts.get("a.desktop"), Some(&2));
+    assert_eq!(history.launch_counts.get("b.desktop"), Some(&1));
+}
+
+#[test]
+fn record_launch_caps_recent_list() {
+    let mut history = History::default();
+    for i in 0..RECENT_LIMIT + 5 {
+        history.record_launch(&format!("app-{i}.desktop"));
+    }
+    assert_eq!(history.recent.len(), RECENT_LIMIT);
+    assert_eq!(history.launch_counts.len(), RECENT_LIMIT + 5);
+}
+
+#[test]
+fn most_used_sorts_by_count_then_name_and_limits() {
+    // Entries are alphabetical, as collect_desktop_entries returns them.
+    let entries = vec![
+        entry("alpha.desktop", "Alpha"),
+        entry("beta.desktop", "Beta"),
+        entry("gamma.desktop", "Gamma"),
+        entry("unused.desktop", "Unused"),
+    ];
+    let mut history = History::default();
+    for id in [
+        "gamma.desktop",
+        "beta.desktop",
+        "gamma.desktop",
+        "alpha.desktop",
+    ] {
+        history.record_launch(id);
+    }
+
+    assert_eq!(history.most_used_indices(&entries, 20), vec![2, 0, 1]);
+    assert_eq!(history.most_used_indices(&entries, 2), vec![2, 0]);
+    assert_eq!(history.recent_indices(&entries), vec![0, 2, 1]);
+}
+
+#[test]
+fn history_round_trips_through_json_file() {
+    let dir = env::temp_dir().join(format!("access-launcher-history-{}", std::process::id()));
+    let path = dir.join("recent.json");
+    let _ = fs::remove_dir_all(&dir);
+
+    let mut history = History::default();
+    history.record_launch("org.example.\"Quoted\".desktop");
+    history.record_launch("firefox.desktop");
+    history.save_to(&path).expect("save history");
+
+    assert_eq!(History::load_from(&path), history);
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn clearing_history_empties_the_saved_store() {
+    let dir = env::temp_dir().join(format!(
+        "access-launcher-history-clear-{}",
+        std::process::id()
+    ));
+    let path = dir.join("recent.json");
+    let _ = fs::remove_dir_all(&dir);
+
+    let mut history = History::default();
+    history.record_launch("firefox.desktop");
+    history.record_launch("files.desktop");
+    history.save_to(&path).expect("save history");
+
+    history.clear();
+    assert_eq!(history, History::default());
+    history.save_to(&path).expect("save cleared history");
+    let loaded = History::load_from(&path);
+    assert!(loaded.recent.is_empty());
+    assert!(loaded.launch_counts.is_empty());
+    assert!(loaded
+        .most_used_indices(&[entry("firefox.desktop", "Firefox")], 10)
+        .is_empty());
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn malformed_history_loads_as_empty() {
+    assert_eq!(History::parse("{not json"), History::default());
+    assert_eq!(History::parse(""), History::default());
+}
+
+#[test]
+fn sort_categories_by_use_puts_most_launched_first() {
+    let entries = vec![
+        entry("calc.desktop", "Calculator"),
+        entry("gimp.desktop", "GIMP"),
+        entry("term.desktop", "Terminal"),
+    ];
+    let category_map: BTreeMap<String, Vec<usize>> = [
+        ("Accessories".to_string(), vec![0]),
+        ("Graphics".to_string(), vec![1]),
+        ("System".to_string(), vec![2]),
+    ]
+    .into_iter()
+    .collect();
+    let mut history = History::default();
+    history.record_launch("term.desktop");
+    history.record_launch("term.desktop");
+    history.record_launch("gimp.desktop");
+
+    let mut categories = vec!["Accessories", "Graphics", "System"];
+    history.sort_categories_by_use(&mut categories, &entries, &category_map);
+    assert_eq!(categories, ["System", "Graphics", "Accessories"]);
+}
@@ -0,0 +1,513 @@
+use access_launcher::desktop::DesktopEntry;
+use access_launcher::launch::{
+    add_prime_offload, build_command, build_wrapped_command, dbus_name, dbus_object_path,
+    expand_env_vars, expand_exec, find_entry, format_command, gpu_count, offload_vars,
+    parse_extra_args, resolve_command_with_args, run_fallback, run_pre_launch_hook,
+    secondary_gpu_driver, session_environment, spawn_with_env, terminal_command,
+    terminal_here_command, xdg_open_fallback, ActivationGuard, LaunchCommand, LaunchError,
+    Terminal, ACTIVATION_DEBOUNCE, MESA_OFFLOAD_VARS, NVIDIA_OFFLOAD_VARS,
+};
+use std::cell::RefCell;
+use std::env;
+use std::path::PathBuf;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+fn entry(exec: &str) -> DesktopEntry {
+    DesktopEntry {
+        id: "sample.desktop".to_string(),
+        name: "Sample App".to_string(),
+        exec: exec.to_string(),
+        path: PathBuf::from("/usr/share/applications/sample.desktop"),
+        icon: Some("sample-icon".to_string()),
+        ..Default::default()
+    }
+}
+
+fn strings(values: &[&str]) -> Vec<String> {
+    values.iter().map(|value| value.to_string()).collect()
+}
+
+#[test]
+fn expand_exec_drops_file_codes_without_files() {
+    let entry = entry("sample %U --flag %f");
+    assert_eq!(
+        expand_exec(&entry.exec, &entry, &[]).unwrap(),
+        strings(&["sample", "--flag"])
+    );
+}
+
+#[test]
+fn expand_exec_substitutes_files() {
+    let entry = entry("sample %F");
+    let files = strings(&["/tmp/a.txt", "/tmp/b.txt"]);
+    assert_eq!(
+        expand_exec(&entry.exec, &entry, &files).unwrap(),
+        strings(&["sample", "/tmp/a.txt", "/tmp/b.txt"])
+    );
+}
+
+#[test]
+fn expand_exec_expands_icon_name_and_location() {
+    let entry = entry("sample %i --title=%c --from %k 100%%");
+    assert_eq!(
+        expand_exec(&entry.exec, &entry, &[]).unwrap(),
+        strings(&[
+            "sample",
+            "--icon",
+            "sample-icon",
+            "--title=Sample App",
+            "--from",
+            "/usr/share/applications/sample.desktop",
+            "100%",
+        ])
+    );
+}
+
+#[test]
+fn expand_exec_removes_deprecated_codes() {
+    let entry = entry("sample %d %D %n %N %v %m");
+    assert_eq!(
+        expand_exec(&entry.exec, &entry, &[]).unwrap(),
+        strings(&["sample"])
+    );
+}
+
+#[test]
+fn expand_exec_rejects_empty_exec() {
+    let entry = entry("   ");
+    assert!(matches!(
+        expand_exec(&entry.exec, &entry, &[]),
+        Err(LaunchError::EmptyExec)
+    ));
+}
+
+#[test]
+fn expand_env_vars_handles_plain_and_braced_references() {
+    env::set_var("ACCESS_LAUNCHER_TEST_VAR", "value");
+    env::remove_var("ACCESS_LAUNCHER_TEST_UNSET");
+    assert_eq!(expand_env_vars("$ACCESS_LAUNCHER_TEST_VAR/x"), "value/x");
+    assert_eq!(expand_env_vars("${ACCESS_LAUNCHER_TEST_VAR}x"), "valuex");
+    assert_eq!(expand_env_vars("a$ACCESS_LAUNCHER_TEST_UNSET"), "a");
+    assert_eq!(expand_env_vars("cost: $"), "cost: $");
+}
+
+#[test]
+fn build_command_wraps_terminal_entries() {
+    let mut entry = entry("htop");
+    entry.terminal = true;
+    entry.working_dir = Some("/srv".to_string());
+    let terminal = Terminal {
+        program: "gnome-terminal".to_string(),
+        exec_args: strings(&["--"]),
+    };
+
+    let command = build_command(&entry, Some(&terminal)).unwrap();
+    assert_eq!(command.argv, strings(&["gnome-terminal", "--", "htop"]));
+    assert_eq!(command.working_dir, Some(PathBuf::from("/srv")));
+
+    assert!(matches!(
+        build_command(&entry, None),
+        Err(LaunchError::NoTerminal)
+    ));
+}
+
+#[test]
+fn build_wrapped_command_prepends_the_wrapper_inside_the_terminal() {
+    let mut entry = entry("game --fullscreen");
+    entry.working_dir = Some("/srv/game".to_string());
+    let wrapper = strings(&["gamemoderun", "mangohud"]);
+
+    let command = build_wrapped_command(&entry, None, &wrapper).unwrap();
+    assert_eq!(
+        command.argv,
+        strings(&["gamemoderun", "mangohud", "game", "--fullscreen"])
+    );
+    assert_eq!(command.working_dir, Some(PathBuf::from("/srv/game")));
+
+    entry.terminal = true;
+    let terminal = Terminal {
+        program: "xterm".to_string(),
+        exec_args: strings(&["-e"]),
+    };
+    let command = build_wrapped_command(&entry, Some(&terminal), &wrapper).unwrap();
+    assert_eq!(
+        command.argv,
+        strings(&[
+            "xterm",
+            "-e",
+            "gamemoderun",
+            "mangohud",
+            "game",
+            "--fullscreen"
+        ])
+    );
+}
+
+#[test]
+fn parse_extra_args_splits_like_a_shell() {
+    assert_eq!(
+        parse_extra_args(r#"--new-window "https://example.com/a b" '$HOME'"#).unwrap(),
+        strings(&["--new-window", "https://example.com/a b", "$HOME"])
+    );
+    assert!(parse_extra_args("  ").unwrap().is_empty());
+    assert!(matches!(
+        parse_extra_args("\"unterminated"),
+        Err(LaunchError::InvalidArguments(_))
+    ));
+}
+
+#[test]
+fn resolve_command_with_args_appends_after_exec() {
+    let entry = entry("sample --flag %U");
+    let command = resolve_command_with_args(&entry, &strings(&["/tmp/notes.txt"])).unwrap();
+    assert_eq!(
+        command.argv,
+        strings(&["sample", "--flag", "/tmp/notes.txt"])
+    );
+}
+
+#[test]
+fn dbus_name_requires_activatable_entry_with_valid_id() {
+    let mut activatable = entry("calculator");
+    activatable.id = "org.gnome.Calculator.desktop".to_string();
+    assert_eq!(dbus_name(&activatable), None);
+
+    activatable.dbus_activatable = true;
+    assert_eq!(dbus_name(&activatable), Some("org.gnome.Calculator"));
+
+    for id in [
+        "calculator.desktop",
+        "org..Calc.desktop",
+        "org.2048.Game.desktop",
+    ] {
+        activatable.id = id.to_string();
+        assert_eq!(dbus_name(&activatable), None, "{id}");
+    }
+}
+
+#[test]
+fn dbus_object_path_follows_freedesktop_convention() {
+    assert_eq!(
+        dbus_object_path("org.example.My-App"),
+        "/org/example/My_App"
+    );
+}
+
+#[test]
+fn terminal_here_command_prefers_path_then_desktop_file_dir() {
+    let terminal = Terminal {
+        program: "foot".to_string(),
+        exec_args: Vec::new(),
+    };
+    let mut entry = entry("sample");
+    let command = terminal_here_command(&entry, &terminal);
+    assert_eq!(command.argv, strings(&["foot"]));
+    assert_eq!(
+        command.working_dir,
+        Some(PathBuf::from("/usr/share/applications"))
+    );
+
+    entry.working_dir = Some("/opt/sample".to_string());
+    assert_eq!(
+        terminal_here_command(&entry, &terminal).working_dir,
+        Some(PathBuf::from("/opt/sample"))
+    );
+}
+
+#[test]
+fn terminal_command_opens_terminal_in_home() {
+    let terminal = Terminal {
+        program: "foot".to_string(),
+        exec_args: vec!["-e".to_string()],
+    };
+    let command = terminal_command(&terminal);
+    assert_eq!(command.argv, strings(&["foot"]));
+    assert_eq!(command.working_dir, env::var_os("HOME").map(PathBuf::from));
+}
+
+#[test]
+fn find_entry_accepts_id_with_or_without_suffix() {
+    let entries = vec![entry("sample")];
+    assert!(find_entry(&entries, "sample.desktop").is_some());
+    assert!(find_entry(&entries, "sample").is_some());
+    assert!(find_entry(&entries, "other").is_none());
+}
+
+#[test]
+fn format_command_quotes_arguments() {
+    let command = LaunchCommand {
+        argv: strings(&["sample", "--title=Sample App", "it's"]),
+        working_dir: Some(PathBuf::from("/srv")),
+    };
+    assert_eq!(
+        format_command(&command),
+        "sample '--title=Sample App' 'it'\\''s'\nWorking directory: /srv"
+    );
+}
+
+#[test]
+fn activation_guard_ignores_quick_repeats_of_the_same_app() {
+    let mut guard = ActivationGuard::default();
+    let start = Instant::now();
+    assert!(guard.allow("firefox.desktop", start));
+    assert!(!guard.allow("firefox.desktop", start + Duration::from_millis(120)));
+    assert!(guard.allow("firefox.desktop", start + ACTIVATION_DEBOUNCE));
+}
+
+#[test]
+fn activation_guard_allows_a_different_app_immediately() {
+    let mut guard = ActivationGuard::new(Duration::from_millis(500));
+    let start = Instant::now();
+    assert!(guard.allow("firefox.desktop", start));
+    assert!(guard.allow("gedit.desktop", start + Duration::from_millis(10)));
+    assert!(guard.allow("firefox.desktop", start + Duration::from_millis(20)));
+}
+
+#[test]
+fn session_environment_passes_on_display_bus_and_token() {
+    let lookup = |key: &str| match key {
+        "WAYLAND_DISPLAY" => Some("wayland-1".to_string()),
+        "DBUS_SESSION_BUS_ADDRESS" => Some("unix:path=/run/user/1000/bus".to_string()),
+        "XDG_SESSION_TYPE" => Some(String::new()),
+        _ => None,
+    };
+    let vars = session_environment(lookup, Some("wayland-0"), Some("token-1"));
+    assert_eq!(
+        vars,
+        [
+            ("WAYLAND_DISPLAY", "wayland-1"),
+            ("DBUS_SESSION_BUS_ADDRESS", "unix:path=/run/user/1000/bus"),
+            ("XDG_ACTIVATION_TOKEN", "token-1"),
+            ("DESKTOP_STARTUP_ID", "token-1"),
+        ]
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+    );
+}
+
+#[test]
+fn session_environment_falls_back_to_the_gdk_display_name() {
+    let none = |_: &str| None;
+    assert_eq!(
+        session_environment(none, Some(":1"), None),
+        [("DISPLAY".to_string(), ":1".to_string())]
+    );
+    assert_eq!(
+        session_environment(none, Some("wayland-0"), None),
+        [("WAYLAND_DISPLAY".to_string(), "wayland-0".to_string())]
+    );
+    assert!(session_environment(none, None, None).is_empty());
+}
+
+#[test]
+fn gpu_count_counts_cards_but_not_their_outputs() {
+    let dir = env::temp_dir().join(format!("access-launcher-drm-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    for name in [
+        "card0",
+        "card0-eDP-1",
+        "card1",
+        "card1-HDMI-A-1",
+        "renderD128",
+    ] {
+        std::fs::create_dir_all(dir.join(name)).expect("create drm entry");
+    }
+    assert_eq!(gpu_count(&dir), 2);
+    std::fs::remove_dir_all(dir.join("card1")).expect("remove card1");
+    assert_eq!(gpu_count(&dir), 1);
+    let _ = std::fs::remove_dir_all(&dir);
+    assert_eq!(gpu_count(&dir), 0);
+}
+
+/// Writes `script` as an executable hook in a fresh temp dir.
+fn write_hook(name: &str, script: &str) -> PathBuf {
+    use std::os::unix::fs::PermissionsExt;
+    let dir = env::temp_dir().join(format!("access-launcher-{name}-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).expect("create hook dir");
+    let hook = dir.join("pre-launch");
+    std::fs::write(&hook, script).expect("write hook");
+    std::fs::set_permissions(&hook, std::fs::Permissions::from_mode(0o755)).expect("chmod hook");
+    hook
+}
+
+#[test]
+fn prime_offload_variables_reach_the_spawned_command() {
+    let mut vars = vec![
+        ("DISPLAY".to_string(), ":0".to_string()),
+        ("DRI_PRIME".to_string(), "0".to_string()),
+    ];
+    add_prime_offload(&mut vars, &MESA_OFFLOAD_VARS);
+    add_prime_offload(&mut vars, &NVIDIA_OFFLOAD_VARS);
+    assert_eq!(
+        vars,
+        [
+            ("DISPLAY", ":0"),
+            ("DRI_PRIME", "1"),
+            ("__NV_PRIME_RENDER_OFFLOAD", "1"),
+            ("__GLX_VENDOR_LIBRARY_NAME", "nvidia"),
+        ]
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+    );
+
+    let dir = env::temp_dir().join(format!("access-launcher-prime-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).expect("create temp dir");
+    let command = LaunchCommand {
+        argv: strings(&["sh", "-c", "env > env.tmp && mv env.tmp env.txt"]),
+        working_dir: Some(dir.clone()),
+    };
+    spawn_with_env(&command, &vars).expect("spawn sh");
+    let output = dir.join("env.txt");
+    let started = Instant::now();
+    while !output.exists() && started.elapsed() < Duration::from_secs(10) {
+        std::thread::sleep(Duration::from_millis(20));
+    }
+    let env_lines = std::fs::read_to_string(&output).expect("child wrote its environment");
+    let _ = std::fs::remove_dir_all(&dir);
+    for line in [
+        "DRI_PRIME=1",
+        "__NV_PRIME_RENDER_OFFLOAD=1",
+        "__GLX_VENDOR_LIBRARY_NAME=nvidia",
+    ] {
+        assert!(env_lines.lines().any(|env_line| env_line == line), "{line}");
+    }
+}
+
+#[test]
+fn offload_variables_follow_the_secondary_gpu_driver() {
+    use std::os::unix::fs::symlink;
+    let dir = env::temp_dir().join(format!("access-launcher-drm-driver-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let card = |name: &str, driver: &str, boot: bool| {
+        let device = dir.join(format!("devices/{name}"));
+        std::fs::create_dir_all(&device).expect("create device");
+        std::fs::create_dir_all(dir.join(format!("drivers/{driver}"))).expect("create driver");
+        let _ = std::fs::remove_file(device.join("driver"));
+        symlink(dir.join(format!("drivers/{driver}")), device.join("driver")).expect("link driver");
+        if boot {
+            std::fs::write(device.join("boot_vga"), "1\n").expect("write boot_vga");
+        }
+        std::fs::create_dir_all(dir.join(format!("drm/{name}"))).expect("create card");
+        let _ = std::fs::remove_file(dir.join(format!("drm/{name}/device")));
+        symlink(&device, dir.join(format!("drm/{name}/device"))).expect("link device");
+    };
+    card("card0", "nvidia", false);
+    card("card1", "i915", true);
+    let drm = dir.join("drm");
+    assert_eq!(secondary_gpu_driver(&drm).as_deref(), Some("nvidia"));
+
+    // An Intel and AMD laptop gets DRI_PRIME only, never the NVIDIA variables.
+    card("card0", "amdgpu", false);
+    assert_eq!(secondary_gpu_driver(&drm).as_deref(), Some("amdgpu"));
+    assert_eq!(offload_vars("amdgpu"), Some(&MESA_OFFLOAD_VARS[..]));
+    assert_eq!(offload_vars("nvidia"), Some(&NVIDIA_OFFLOAD_VARS[..]));
+    assert_eq!(offload_vars("simpledrm"), None);
+
+    std::fs::remove_dir_all(drm.join("card0")).expect("remove card0");
+    assert_eq!(secondary_gpu_driver(&drm), None);
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn pre_launch_hook_receives_id_and_command_and_can_refuse() {
+    let hook = write_hook(
+        "hook-args",
+        "#!/bin/sh\nread id\nread command\n\
+         [ \"$1\" = \"$id\" ] && [ \"$2\" = \"$command\" ] || exit 9\n\
+         [ \"$1\" = firefox.desktop ] && exit 0\nexit 3\n",
+    );
+    let timeout = Duration::from_secs(10);
+    assert!(run_pre_launch_hook(&hook, "firefox.desktop", "firefox 'a b'", timeout).is_ok());
+    assert!(matches!(
+        run_pre_launch_hook(&hook, "gimp.desktop", "gimp", timeout),
+        Err(LaunchError::HookRejected(Some(3)))
+    ));
+}
+
+#[test]
+fn pre_launch_hook_is_skipped_unless_executable() {
+    use std::os::unix::fs::PermissionsExt;
+    let hook = write_hook("hook-plain", "#!/bin/sh\nexit 1\n");
+    std::fs::set_permissions(&hook, std::fs::Permissions::from_mode(0o644)).expect("chmod hook");
+    let timeout = Duration::from_secs(10);
+    assert!(run_pre_launch_hook(&hook, "app.desktop", "app", timeout).is_ok());
+    assert!(run_pre_launch_hook(
+        &hook.with_file_name("missing"),
+        "app.desktop",
+        "app",
+        timeout
+    )
+    .is_ok());
+}
+
+#[test]
+fn pre_launch_hook_that_hangs_is_killed() {
+    let hook = write_hook("hook-hang", "#!/bin/sh\nexec sleep 30\n");
+    let started = Instant::now();
+    let result = run_pre_launch_hook(&hook, "app.desktop", "app", Duration::from_millis(200));
+    assert!(matches!(result, Err(LaunchError::HookTimedOut(_))));
+    assert!(started.elapsed() < Duration::from_secs(10));
+}
+
+fn xdg_open(path: &str) -> LaunchCommand {
+    LaunchCommand {
+        argv: vec!["xdg-open".to_string(), path.to_string()],
+        working_dir: None,
+    }
+}
+
+/// Runs the fallback after a failed launch with a stand-in for spawning
+/// that reports `outcome`, returning the argv it was given and the details.
+fn fallback_details(
+    fallback: Option<LaunchCommand>,
+    outcome: Result<(), LaunchError>,
+) -> (Vec<Vec<String>>, String) {
+    let spawned = RefCell::new(Vec::new());
+    let details = Rc::new(RefCell::new(String::new()));
+    let shown = Rc::clone(&details);
+    run_fallback(
+        "No such file",
+        fallback,
+        |command, exited| {
+            spawned.borrow_mut().push(command.argv.clone());
+            exited(outcome);
+        },
+        move |text| *shown.borrow_mut() = text,
+    );
+    let details = details.borrow().clone();
+    (spawned.into_inner(), details)
+}
+
+#[test]
+fn fallback_runs_after_the_primary_launch_fails_and_is_still_reported() {
+    let fallback = xdg_open("/apps/sample.desktop");
+    let (spawned, details) = fallback_details(Some(fallback.clone()), Ok(()));
+    assert_eq!(spawned, vec![fallback.argv]);
+    assert!(details.starts_with("No such file\n\n"));
+    assert!(details.contains("xdg-open /apps/sample.desktop was run instead"));
+}
+
+#[test]
+fn fallback_outcome_is_reported_with_the_primary_error() {
+    let fallback = xdg_open("/apps/sample.desktop");
+    let (_, details) = fallback_details(Some(fallback), Err(LaunchError::Exited(Some(4))));
+    assert!(details.starts_with("No such file\n\n"));
+    assert!(details.ends_with("xdg-open /apps/sample.desktop, also failed: exited with status 4"));
+
+    let (spawned, details) = fallback_details(None, Ok(()));
+    assert!(spawned.is_empty());
+    assert!(details.starts_with("No such file\n\n"));
+    assert!(details.contains("xdg-open is not installed"));
+}
+
+#[test]
+fn fallback_skips_appimages_and_hook_refusals() {
+    let mut appimage = entry("/apps/Tool.AppImage");
+    appimage.appimage = true;
+    assert!(xdg_open_fallback(&appimage).is_none());
+
+    assert!(LaunchError::HookRejected(Some(1)).is_from_hook());
+    assert!(LaunchError::HookTimedOut(Duration::from_secs(1)).is_from_hook());
+    assert!(!LaunchError::EmptyExec.is_from_hook());
+}
@@ -0,0 +1,82 @@
+use access_launcher::catalog::Catalog;
+use access_launcher::desktop::DesktopEntry;
+use access_launcher::launch::LaunchError;
+use access_launcher::launcher::Launcher;
+use access_launcher::search::{SearchMode, SearchScope};
+
+fn entry(name: &str, categories: &str, keywords: &str) -> DesktopEntry {
+    DesktopEntry {
+        id: format!("{}.desktop", name.to_lowercase()),
+        name: name.to_string(),
+        exec: "app".to_string(),
+        categories: categories.to_string(),
+        keywords: keywords.to_string(),
+        ..Default::default()
+    }
+}
+
+fn launcher() -> Launcher {
+    Launcher::new(Catalog::new(vec![
+        entry("Chat", "Network;", ""),
+        entry("Files", "System;FileManager;", "folder;explorer;"),
+        entry("Firefox", "Network;WebBrowser;", "web;internet;"),
+        entry("Writer", "Office;WordProcessor;", ""),
+    ]))
+}
+
+fn names(entries: Vec<&DesktopEntry>) -> Vec<&str> {
+    entries.iter().map(|entry| entry.name.as_str()).collect()
+}
+
+#[test]
+fn launcher_lists_entries_by_category() {
+    let launcher = launcher();
+    assert_eq!(launcher.entries().len(), 4);
+    assert_eq!(names(launcher.by_category("Internet")), ["Chat", "Firefox"]);
+    assert_eq!(names(launcher.by_category("Office")), ["Writer"]);
+    assert!(launcher.by_category("Games").is_empty());
+    assert!(launcher.by_category("No Such Category").is_empty());
+}
+
+#[test]
+fn launcher_search_ranks_names_above_keywords() {
+    let launcher = launcher();
+    assert_eq!(names(launcher.search("fi")), ["Files", "Firefox"]);
+    // "internet" is only a keyword of Firefox.
+    assert_eq!(names(launcher.search("internet")), ["Firefox"]);
+    assert!(launcher.search("zzz").is_empty());
+}
+
+#[test]
+fn launcher_search_follows_mode_and_scope() {
+    let launcher = launcher().with_search(SearchMode::Fuzzy, SearchScope::Name);
+    assert_eq!(names(launcher.search("frfx")), ["Firefox"]);
+    assert!(launcher.search("internet").is_empty());
+}
+
+#[test]
+fn launcher_finds_entries_by_window_class() {
+    let mut firefox = entry("Firefox", "Network;WebBrowser;", "");
+    firefox.startup_wm_class = Some("firefox".to_string());
+    let launcher = Launcher::new(Catalog::new(vec![entry("Chat", "Network;", ""), firefox]));
+    assert_eq!(
+        launcher
+            .find_by_wm_class("Firefox")
+            .map(|entry| entry.name.as_str()),
+        Some("Firefox")
+    );
+    assert!(launcher.find_by_wm_class("chat").is_none());
+}
+
+#[test]
+fn launcher_launch_reports_an_empty_exec() {
+    let launcher = launcher();
+    let broken = DesktopEntry {
+        name: "Broken".to_string(),
+        ..Default::default()
+    };
+    assert!(matches!(
+        launcher.launch(&broken),
+        Err(LaunchError::EmptyExec)
+    ));
+}
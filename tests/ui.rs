@@ -0,0 +1,306 @@
+use access_launcher::desktop::DesktopEntry;
+use access_launcher::ui::{
+    accessibility_disabled_reason, category_rows, describe_batch_failures,
+    describe_focused_program, describe_hidden_results, describe_launch_question, describe_launched,
+    describe_no_results, describe_program_count, describe_search_count, describe_selection_count,
+    describe_shortcut, format_batch_failures, highlight_markup, icon_candidates, navigation_target,
+    program_description, program_display_name, quick_select_index, row_spacing_css, tree_row_label,
+    visible_results, CategoryRow, ListKey, ProgramPositions, CATEGORIES_HEADING, FALLBACK_ICON,
+    SPECIAL_CATEGORIES_HEADING,
+};
+use gtk4::gdk;
+
+#[test]
+fn describe_selection_count_announces_the_total() {
+    assert_eq!(describe_selection_count(0), "No applications selected");
+    assert_eq!(describe_selection_count(1), "1 application selected");
+    assert_eq!(describe_selection_count(3), "3 applications selected");
+}
+
+#[test]
+fn batch_failures_are_summarized_in_one_report() {
+    assert_eq!(
+        describe_batch_failures(1, 1),
+        "Failed to launch 1 application"
+    );
+    assert_eq!(
+        describe_batch_failures(2, 3),
+        "Failed to launch 2 of 3 applications"
+    );
+    let failures = vec![
+        ("Firefox".to_string(), "No such file".to_string()),
+        ("Gimp".to_string(), "Permission denied".to_string()),
+    ];
+    assert_eq!(
+        format_batch_failures(&failures),
+        "Firefox: No such file\nGimp: Permission denied"
+    );
+}
+
+#[test]
+fn describe_program_count_announces_category_and_total() {
+    assert_eq!(
+        describe_program_count("Internet", 12),
+        "Internet: 12 applications"
+    );
+    assert_eq!(describe_program_count("Games", 1), "Games: 1 application");
+    assert_eq!(
+        describe_program_count("Office", 0),
+        "Office: No applications found"
+    );
+}
+
+#[test]
+fn search_with_no_matches_is_announced_as_no_results() {
+    assert_eq!(describe_no_results(" zzz "), "No results for 'zzz'");
+    assert_eq!(
+        describe_search_count("Search results", "zzz", 0),
+        "No results for 'zzz'"
+    );
+    assert_eq!(
+        describe_search_count("Search results", "fire", 2),
+        "Search results: 2 applications"
+    );
+}
+
+#[test]
+fn tree_row_label_conveys_expansion_and_level() {
+    assert_eq!(
+        tree_row_label("Development", 0, Some(false)),
+        "Development, collapsed"
+    );
+    assert_eq!(
+        tree_row_label("Development", 0, Some(true)),
+        "Development, expanded"
+    );
+    assert_eq!(tree_row_label("Other", 0, None), "Other");
+    assert_eq!(tree_row_label("IDE", 1, None), "IDE, level 2");
+}
+
+#[test]
+fn icon_candidates_fall_back_to_symbolic_then_generic() {
+    assert_eq!(
+        icon_candidates(Some("firefox")),
+        vec!["firefox", "firefox-symbolic", FALLBACK_ICON]
+    );
+    assert_eq!(
+        icon_candidates(Some("audio-volume-high-symbolic")),
+        vec!["audio-volume-high-symbolic", FALLBACK_ICON]
+    );
+    assert_eq!(
+        icon_candidates(Some("legacy.png")),
+        vec!["legacy", "legacy-symbolic", FALLBACK_ICON]
+    );
+    assert_eq!(icon_candidates(None), vec![FALLBACK_ICON]);
+    assert_eq!(icon_candidates(Some(" ")), vec![FALLBACK_ICON]);
+}
+
+#[test]
+fn navigation_target_jumps_to_ends_and_wraps_when_enabled() {
+    assert_eq!(navigation_target(ListKey::Home, Some(3), 5, false), Some(0));
+    assert_eq!(navigation_target(ListKey::End, None, 5, false), Some(4));
+    assert_eq!(navigation_target(ListKey::Up, Some(0), 5, false), None);
+    assert_eq!(navigation_target(ListKey::Up, Some(0), 5, true), Some(4));
+    assert_eq!(navigation_target(ListKey::Down, Some(4), 5, true), Some(0));
+    assert_eq!(navigation_target(ListKey::Down, Some(2), 5, true), None);
+    assert_eq!(navigation_target(ListKey::Home, None, 0, true), None);
+}
+
+#[test]
+fn program_description_mentions_original_name() {
+    let mut entry = DesktopEntry {
+        name: "Calc".to_string(),
+        exec: "libreoffice --calc".to_string(),
+        ..Default::default()
+    };
+    assert_eq!(program_description(&entry), "libreoffice --calc");
+    entry.original_name = Some("LibreOffice Calc".to_string());
+    assert_eq!(
+        program_description(&entry),
+        "libreoffice --calc. Originally named LibreOffice Calc"
+    );
+}
+
+#[test]
+fn program_description_warns_about_a_missing_program() {
+    let entry = DesktopEntry {
+        name: "Gone".to_string(),
+        exec: "/opt/gone/bin/gone %U".to_string(),
+        missing_program: Some("/opt/gone/bin/gone".to_string()),
+        ..Default::default()
+    };
+    assert_eq!(
+        program_description(&entry),
+        "Warning, executable not found: /opt/gone/bin/gone. /opt/gone/bin/gone %U"
+    );
+}
+
+#[test]
+fn highlight_markup_bolds_matches_and_escapes_text() {
+    assert_eq!(highlight_markup("Firefox", &[4, 5, 6]), "Fire<b>fox</b>");
+    assert_eq!(
+        highlight_markup("Tom & <Jerry>", &[0, 6, 7]),
+        "<b>T</b>om &amp; <b>&lt;J</b>erry&gt;"
+    );
+    assert_eq!(highlight_markup("Café", &[3]), "Caf<b>é</b>");
+    assert_eq!(highlight_markup("Plain", &[]), "Plain");
+}
+
+#[test]
+fn quick_select_index_maps_number_keys_to_positions() {
+    assert_eq!(quick_select_index(gdk::Key::_1), Some(0));
+    assert_eq!(quick_select_index(gdk::Key::KP_9), Some(8));
+    assert_eq!(quick_select_index(gdk::Key::_0), None);
+    assert_eq!(quick_select_index(gdk::Key::a), None);
+}
+
+#[test]
+fn visible_results_truncate_at_the_configured_limit() {
+    let programs: Vec<usize> = (0..10).collect();
+    assert_eq!(visible_results(&programs, 10), (&programs[..], 0));
+    assert_eq!(visible_results(&programs, 4), (&programs[..4], 6));
+    assert_eq!(visible_results(&programs, 0), (&programs[..], 0));
+    assert_eq!(
+        describe_hidden_results(6),
+        "… and 6 more, refine your search"
+    );
+}
+
+#[test]
+fn describe_shortcut_lists_keys_or_says_there_are_none() {
+    assert_eq!(
+        describe_shortcut("Quit", &["Ctrl+Q".to_string()]),
+        "Quit: Ctrl+Q"
+    );
+    assert_eq!(
+        describe_shortcut(
+            "Move focus to the other pane",
+            &["F6".into(), "Shift+F6".into()]
+        ),
+        "Move focus to the other pane: F6, Shift+F6"
+    );
+    assert_eq!(describe_shortcut("About", &[]), "About: no shortcut");
+}
+
+#[test]
+fn describe_focused_program_adds_the_generic_name() {
+    let mut entry = DesktopEntry {
+        name: "Firefox".to_string(),
+        ..Default::default()
+    };
+    assert_eq!(describe_focused_program(&entry), "Firefox");
+    entry.generic_name = Some("Web Browser".to_string());
+    assert_eq!(describe_focused_program(&entry), "Firefox, Web Browser");
+    entry.generic_name = Some("Firefox".to_string());
+    assert_eq!(describe_focused_program(&entry), "Firefox");
+}
+
+#[test]
+fn category_rows_add_headings_above_nonempty_groups() {
+    let categories = ["Recent", "Most Used", "Internet", "Office"];
+    assert_eq!(
+        category_rows(&categories, 2, true),
+        [
+            CategoryRow::Heading(SPECIAL_CATEGORIES_HEADING),
+            CategoryRow::Category("Recent"),
+            CategoryRow::Category("Most Used"),
+            CategoryRow::Heading(CATEGORIES_HEADING),
+            CategoryRow::Category("Internet"),
+            CategoryRow::Category("Office"),
+        ]
+    );
+    assert_eq!(
+        category_rows(&categories, 2, false),
+        categories.map(CategoryRow::Category)
+    );
+    assert_eq!(
+        category_rows(&categories[..2], 2, true),
+        [
+            CategoryRow::Heading(SPECIAL_CATEGORIES_HEADING),
+            CategoryRow::Category("Recent"),
+            CategoryRow::Category("Most Used"),
+        ]
+    );
+}
+
+#[test]
+fn program_positions_are_kept_per_category() {
+    let mut positions = ProgramPositions::default();
+    assert_eq!(positions.get("Office"), None);
+    positions.remember("Office", "writer.desktop");
+    positions.remember("Internet", "firefox.desktop");
+    positions.remember("Office", "calc.desktop");
+    assert_eq!(positions.get("Office"), Some("calc.desktop"));
+    assert_eq!(positions.get("Internet"), Some("firefox.desktop"));
+    assert_eq!(positions.get("Games"), None);
+}
+
+#[test]
+fn program_display_name_adds_the_source_of_duplicates() {
+    let mut entry = DesktopEntry {
+        name: "Firefox".to_string(),
+        generic_name: Some("Web Browser".to_string()),
+        ..Default::default()
+    };
+    assert_eq!(program_display_name(&entry), "Firefox");
+    entry.name_suffix = Some("Flatpak".to_string());
+    assert_eq!(program_display_name(&entry), "Firefox (Flatpak)");
+    assert_eq!(
+        describe_focused_program(&entry),
+        "Firefox (Flatpak), Web Browser"
+    );
+}
+
+#[test]
+fn row_spacing_css_styles_rows_and_tiles() {
+    assert_eq!(row_spacing_css(None, None), "");
+    let css = row_spacing_css(Some(12), Some(56));
+    assert!(css.contains("listbox > row"), "{css}");
+    assert!(css.contains("flowbox > flowboxchild"), "{css}");
+    assert!(css.contains("padding: 12px; min-height: 56px;"), "{css}");
+
+    // A value left unset keeps the theme's.
+    let css = row_spacing_css(None, Some(64));
+    assert!(css.contains("{ min-height: 64px; }"), "{css}");
+    assert!(!css.contains("padding"), "{css}");
+}
+
+#[test]
+fn accessibility_disabled_reason_reports_gtk_a11y_overrides() {
+    let env = |value: Option<&'static str>| {
+        move |key: &str| {
+            assert_eq!(key, "GTK_A11Y");
+            value.map(str::to_string)
+        }
+    };
+    assert_eq!(accessibility_disabled_reason(env(None)), None);
+    assert_eq!(accessibility_disabled_reason(env(Some("atspi"))), None);
+    let reason = accessibility_disabled_reason(env(Some("none"))).expect("reason");
+    assert!(reason.starts_with("GTK_A11Y=none"), "{reason}");
+    assert!(accessibility_disabled_reason(env(Some("test"))).is_some());
+}
+
+#[test]
+fn describe_launch_question_names_a_single_app() {
+    assert_eq!(
+        describe_launch_question(&["Firefox".to_string()]),
+        "Launch Firefox?"
+    );
+    assert_eq!(
+        describe_launch_question(&["Firefox".to_string(), "Files".to_string()]),
+        "Launch 2 applications?"
+    );
+}
+
+#[test]
+fn describe_launched_names_a_single_app() {
+    assert_eq!(describe_launched(&[]), "");
+    assert_eq!(
+        describe_launched(&["Firefox".to_string()]),
+        "Launched Firefox"
+    );
+    assert_eq!(
+        describe_launched(&["Firefox".to_string(), "Files".to_string()]),
+        "Launched 2 applications"
+    );
+}